@@ -0,0 +1,66 @@
+// benchmarks need a recent toolchain anyway, as criterion does
+#![allow(clippy::incompatible_msrv)]
+
+use {
+    criterion::{criterion_group, criterion_main, Criterion, Throughput},
+    kind::*,
+    std::{fmt::Write, hint::black_box},
+};
+
+#[derive(Debug, Identifiable)]
+#[kind(class = "Cust")]
+pub struct Customer {}
+
+const SEED: u64 = 42;
+const N: usize = 1000;
+
+fn format(c: &mut Criterion) {
+    let ids = bench_support::ids::<Customer>(SEED, N);
+
+    let mut group = c.benchmark_group("format");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("display", |b| {
+        let mut output = String::with_capacity(64);
+        b.iter(|| {
+            for id in black_box(&ids) {
+                output.clear();
+                write!(output, "{id}").unwrap();
+                black_box(&output);
+            }
+        })
+    });
+    group.bench_function("public_id", |b| {
+        b.iter(|| {
+            for id in black_box(&ids) {
+                black_box(id.public_id());
+            }
+        })
+    });
+    group.bench_function("public_id_buf", |b| {
+        let mut buffer = [0; 64];
+        b.iter(|| {
+            for id in black_box(&ids) {
+                black_box(id.public_id_buf(&mut buffer));
+            }
+        })
+    });
+    group.bench_function("db_id", |b| {
+        b.iter(|| {
+            for id in black_box(&ids) {
+                black_box(id.db_id());
+            }
+        })
+    });
+    group.bench_function("db_id_buf", |b| {
+        let mut buffer = [0; DB_ID_LEN];
+        b.iter(|| {
+            for id in black_box(&ids) {
+                black_box(id.db_id_buf(&mut buffer));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, format);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+// benchmarks need a recent toolchain anyway, as criterion does
+#![allow(clippy::incompatible_msrv)]
+
+use {
+    criterion::{criterion_group, criterion_main, Criterion, Throughput},
+    kind::*,
+    std::hint::black_box,
+};
+
+#[derive(Debug, Identifiable)]
+#[kind(class = "Cust")]
+pub struct Customer {}
+
+const SEED: u64 = 42;
+const N: usize = 1000;
+
+fn parse(c: &mut Criterion) {
+    let public_ids = bench_support::public_ids::<Customer>(SEED, N);
+    let mixed_case_public_ids = bench_support::mixed_case_public_ids::<Customer>(SEED, N);
+    let invalid_public_ids = bench_support::invalid_public_ids::<Customer>(SEED, N);
+    let db_ids = bench_support::db_ids::<Customer>(SEED, N);
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("from_public_id", |b| {
+        b.iter(|| {
+            for public_id in black_box(&public_ids) {
+                black_box(Id::<Customer>::from_public_id(public_id).unwrap());
+            }
+        })
+    });
+    group.bench_function("from_public_id/mixed_case", |b| {
+        b.iter(|| {
+            for public_id in black_box(&mixed_case_public_ids) {
+                black_box(Id::<Customer>::from_public_id(public_id).unwrap());
+            }
+        })
+    });
+    group.bench_function("from_public_id/invalid", |b| {
+        b.iter(|| {
+            for public_id in black_box(&invalid_public_ids) {
+                black_box(Id::<Customer>::from_public_id(public_id).unwrap_err());
+            }
+        })
+    });
+    group.bench_function("parse_any_untrusted", |b| {
+        b.iter(|| {
+            for public_id in black_box(&public_ids) {
+                black_box(Id::<Customer>::parse_any_untrusted(public_id).unwrap());
+            }
+        })
+    });
+    group.bench_function("strip_prefix", |b| {
+        b.iter(|| {
+            for public_id in black_box(&public_ids) {
+                black_box(Customer::class().strip_prefix(public_id).unwrap());
+            }
+        })
+    });
+    group.bench_function("from_db_id", |b| {
+        b.iter(|| {
+            for db_id in black_box(&db_ids) {
+                black_box(Id::<Customer>::from_db_id(db_id).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+// benchmarks need a recent toolchain anyway, as criterion does
+#![allow(clippy::incompatible_msrv)]
+
+use {
+    criterion::{criterion_group, criterion_main, Criterion, Throughput},
+    kind::*,
+    serde::{Deserialize, Serialize},
+    std::hint::black_box,
+};
+
+#[derive(Debug, Identifiable, Serialize, Deserialize)]
+#[kind(class = "Cust")]
+pub struct Customer {
+    name: String,
+}
+
+const SEED: u64 = 42;
+const N: usize = 1000;
+
+fn serde(c: &mut Criterion) {
+    let ids = bench_support::ids::<Customer>(SEED, N);
+    let ids_json = serde_json::to_string(&ids).unwrap();
+    let customers: Vec<Ided<Customer>> = ids
+        .iter()
+        .map(|id| {
+            Ided::new(
+                *id,
+                Customer {
+                    name: "John".to_string(),
+                },
+            )
+        })
+        .collect();
+    let customers_json = serde_json::to_string(&customers).unwrap();
+
+    let mut group = c.benchmark_group("serde");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("serialize/ids", |b| {
+        b.iter(|| serde_json::to_string(black_box(&ids)).unwrap())
+    });
+    group.bench_function("serialize/serialize_ids", |b| {
+        b.iter(|| {
+            let mut json = Vec::new();
+            serialize_ids(black_box(&ids), &mut serde_json::Serializer::new(&mut json)).unwrap();
+            json
+        })
+    });
+    group.bench_function("deserialize/ids", |b| {
+        b.iter(|| serde_json::from_str::<Vec<Id<Customer>>>(black_box(&ids_json)).unwrap())
+    });
+    group.bench_function("serialize/ided", |b| {
+        b.iter(|| serde_json::to_string(black_box(&customers)).unwrap())
+    });
+    group.bench_function("deserialize/ided", |b| {
+        b.iter(|| serde_json::from_str::<Vec<Ided<Customer>>>(black_box(&customers_json)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serde);
+criterion_main!(benches);
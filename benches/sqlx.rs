@@ -0,0 +1,66 @@
+// benchmarks need a recent toolchain anyway, as criterion does
+#![allow(clippy::incompatible_msrv)]
+
+//! Decoding of ids from rows. The rows are fetched once from an in
+//! memory SQLite database, so that only the decoding is measured.
+
+use {
+    criterion::{criterion_group, criterion_main, Criterion, Throughput},
+    kind::*,
+    sqlx::{sqlite::SqliteRow, Connection, Row, SqliteConnection},
+    std::hint::black_box,
+};
+
+#[derive(Debug, Identifiable)]
+#[kind(class = "Cust")]
+pub struct Customer {}
+
+const SEED: u64 = 42;
+const N: usize = 1000;
+
+fn rows() -> Vec<SqliteRow> {
+    async_std::task::block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE customer (id BLOB PRIMARY KEY, legacy_id TEXT)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        for id in bench_support::ids::<Customer>(SEED, N) {
+            sqlx::query("INSERT INTO customer (id, legacy_id) VALUES ($1, $2)")
+                .bind(id)
+                .bind(id.db_id())
+                .execute(&mut conn)
+                .await
+                .unwrap();
+        }
+        sqlx::query("SELECT id, legacy_id FROM customer")
+            .fetch_all(&mut conn)
+            .await
+            .unwrap()
+    })
+}
+
+fn decode(c: &mut Criterion) {
+    let rows = rows();
+
+    let mut group = c.benchmark_group("sqlx");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("decode/blob", |b| {
+        b.iter(|| {
+            for row in black_box(&rows) {
+                black_box(row.get::<Id<Customer>, _>(0));
+            }
+        })
+    });
+    group.bench_function("decode/text", |b| {
+        b.iter(|| {
+            for row in black_box(&rows) {
+                black_box(row.get::<Id<Customer>, _>(1));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Deserialize arbitrary JSON documents holding ids, checking that
+//! deserialization never panics
+
+#![no_main]
+
+use {kind::*, libfuzzer_sys::fuzz_target, serde::Deserialize};
+
+#[derive(Debug, Identifiable, Deserialize)]
+#[kind(class = "Cust")]
+pub struct Customer {
+    pub name: Option<String>,
+}
+
+id_enum! { PartyId: Customer }
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Id<Customer>>(data);
+    let _ = serde_json::from_slice::<Ided<Customer>>(data);
+    let _ = serde_json::from_slice::<Vec<PartyId>>(data);
+});
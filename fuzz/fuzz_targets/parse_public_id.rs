@@ -0,0 +1,24 @@
+//! Parse arbitrary strings as public ids, checking that parsing never
+//! panics, and that the accepted ids round trip through their canonical
+//! form
+
+#![no_main]
+
+use {kind::*, libfuzzer_sys::fuzz_target};
+
+#[derive(Debug, Identifiable)]
+#[kind(class = "Cust")]
+pub struct Customer {}
+
+fuzz_target!(|input: &str| {
+    let untrusted = Id::<Customer>::parse_any_untrusted(input);
+    let parsed = Id::<Customer>::from_public_id(input);
+    match (untrusted, parsed) {
+        (Ok(id), Ok(parsed)) => {
+            assert_eq!(id, parsed);
+            assert_eq!(Id::<Customer>::from_public_id(&id.public_id()), Ok(id));
+        }
+        (Ok(_), Err(e)) => panic!("only accepted by parse_any_untrusted: {e}"),
+        (Err(e), _) => assert!(e.input().map_or(0, str::len) <= 64),
+    }
+});
@@ -0,0 +1,59 @@
+#ifndef KIND_H
+#define KIND_H
+
+/* Generated with cbindgen from src/ffi.rs, don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of the functions of the C interface
+typedef enum KindError {
+  KIND_ERROR_OK = 0,
+  // The prefix isn't the one of the expected class
+  KIND_ERROR_WRONG_CLASS,
+  // The input isn't a valid id
+  KIND_ERROR_INVALID_FORMAT,
+  // The prefix is valid, but not the UUID
+  KIND_ERROR_BAD_UUID,
+  // The input is longer than any valid id
+  KIND_ERROR_TOO_LONG,
+  // A pointer argument is null
+  KIND_ERROR_NULL_POINTER,
+  // A string argument isn't valid UTF-8
+  KIND_ERROR_INVALID_UTF8,
+  // The given prefix isn't a valid class prefix (`[a-zA-Z0-9]+`)
+  KIND_ERROR_INVALID_CLASS,
+  // The output buffer is too small
+  KIND_ERROR_BUFFER_TOO_SMALL,
+} KindError;
+
+// Parse a public id of the class with the given prefix, writing the 16
+// bytes of its UUID in `uuid_out`
+//
+// # Safety
+//
+// `public_id` and `class` must be null or point to NUL terminated
+// strings, and `uuid_out` must be null or point to 16 writable bytes.
+KindError kind_parse_public_id(const char *public_id, const char *class_, uint8_t *uuid_out);
+
+// Write the NUL terminated public id of the class with the given prefix
+// and of the 16 bytes UUID in `buffer`, which must hold at least the
+// length of the prefix plus 38 bytes
+//
+// # Safety
+//
+// `class` must be null or point to a NUL terminated string, `uuid`
+// must be null or point to 16 bytes, and `buffer` must be null or point
+// to `buffer_len` writable bytes.
+KindError kind_format_public_id(const char *class_,
+                                const uint8_t *uuid,
+                                char *buffer,
+                                size_t buffer_len);
+
+// Return the NUL terminated code of an error, eg `KIND_WRONG_CLASS`
+const char *kind_error_code(KindError error);
+
+#endif  /* KIND_H */
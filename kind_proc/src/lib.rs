@@ -1,14 +1,56 @@
 use {
-    darling::FromDeriveInput,
+    darling::{
+        util::{Flag, PathList},
+        FromDeriveInput,
+    },
     proc_macro::TokenStream,
     quote::{format_ident, quote},
-    syn::{parse_macro_input, DeriveInput},
+    syn::{
+        parse_macro_input, parse_quote, Data, DataStruct, DeriveInput, Fields, GenericArgument,
+        Path, PathArguments, Type,
+    },
 };
 
 #[derive(FromDeriveInput)]
 #[darling(attributes(kind))]
 struct Opts {
     class: String,
+    id_field: Option<String>,
+    #[darling(multiple, rename = "id_alias")]
+    id_aliases: Vec<String>,
+    prefixed_row: Flag,
+    sensitivity: Option<String>,
+    references: Option<PathList>,
+    belongs_to: Option<PathList>,
+    has_many: Option<PathList>,
+}
+
+/// Return the type argument of a type like `Ref<O>`, if its last
+/// segment is named `wrapper`
+fn type_argument<'t>(ty: &'t Type, wrapper: &str) -> Option<&'t Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    }
+}
+
+/// Tell whether the type is the referenced one, comparing the last
+/// segments of the paths, as the derive only sees tokens
+fn is_type(ty: &Type, referenced: &Path) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path.segments.last().map(|s| &s.ident) == referenced.segments.last().map(|s| &s.ident)
 }
 
 #[proc_macro_derive(Identifiable, attributes(kind))]
@@ -24,15 +66,178 @@ pub fn kind_macro_derive(input: TokenStream) -> TokenStream {
             panic!("Invalid character {c:?} in kind class {class:?}");
         }
     }
+    if opts.id_field.as_deref() == Some("") {
+        panic!("kind id_field can't be empty")
+    }
+    let id_field = opts.id_field.as_ref().map(|id_field| {
+        quote! { const ID_FIELD: &'static str = #id_field; }
+    });
+    let id_aliases = opts.id_aliases;
+    let id_aliases = (!id_aliases.is_empty()).then(|| {
+        quote! { const ID_ALIASES: &'static [&'static str] = &[#(#id_aliases),*]; }
+    });
+    let level = opts.sensitivity.as_deref().map(|sensitivity| {
+        match sensitivity {
+            "public" => quote! { Public },
+            "internal" => quote! { Internal },
+            "pii" => quote! { Pii },
+            "secret" => quote! { Secret },
+            _ => panic!("Invalid kind sensitivity {sensitivity:?}, expected public, internal, pii or secret"),
+        }
+    });
+    let sensitivity = level
+        .as_ref()
+        .map(|level| quote! { .with_sensitivity(Sensitivity::#level) });
     let class_const = format_ident!("KIND_CLASS_{}", class);
-    let DeriveInput { ident, .. } = input;
+    let registered = cfg!(feature = "registry").then(|| {
+        let sensitivity = level
+            .as_ref()
+            .map(|level| quote! { .with_sensitivity(::kind::Sensitivity::#level) });
+        let registered_const = format_ident!("KIND_REGISTERED_CLASS_{}", class);
+        let type_name = input.ident.to_string();
+        let id_field = opts.id_field.as_deref().unwrap_or("id");
+        let relation = |kind: &str, paths: &Option<PathList>| {
+            let kind = format_ident!("{}", kind);
+            let paths: Vec<&Path> = paths.iter().flat_map(|list| list.iter()).collect();
+            quote! {
+                #(
+                    ::kind::Relation::new(
+                        ::kind::RelationKind::#kind,
+                        <#paths as ::kind::Identifiable>::class,
+                    ),
+                )*
+            }
+        };
+        let belongs_to = relation("BelongsTo", &opts.belongs_to);
+        let has_many = relation("HasMany", &opts.has_many);
+        quote! {
+            #[::kind::__private::linkme::distributed_slice(::kind::KIND_CLASSES)]
+            #[linkme(crate = ::kind::__private::linkme)]
+            static #registered_const: ::kind::RegisteredClass = ::kind::RegisteredClass::new(
+                ::kind::IdClass::new(#class)#sensitivity,
+                #type_name,
+                #id_field,
+            )
+            .with_module_path(module_path!())
+            .with_relations(&[#belongs_to #has_many]);
+        }
+    });
+    let references = opts.references.unwrap_or_default();
+    let accessors = references.iter().map(|referenced| {
+        let Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) = &input.data
+        else {
+            panic!("kind references needs a struct with named fields");
+        };
+        let accessors: Vec<_> = fields
+            .named
+            .iter()
+            .filter_map(|f| {
+                let field = f.ident.as_ref()?;
+                let name = field.to_string();
+                let name = name.trim_start_matches("r#");
+                let method = if name.ends_with("_id") {
+                    format_ident!("{}", name)
+                } else {
+                    format_ident!("{}_id", name)
+                };
+                let optional = type_argument(&f.ty, "Option");
+                let inner = type_argument(optional.unwrap_or(&f.ty), "Ref")?;
+                if !is_type(inner, referenced) {
+                    return None;
+                }
+                Some(if optional.is_some() {
+                    quote! {
+                        pub fn #method(&self) -> Option<Id<#referenced>> {
+                            self.#field.map(|reference| reference.id())
+                        }
+                    }
+                } else {
+                    quote! {
+                        pub fn #method(&self) -> Id<#referenced> {
+                            self.#field.id()
+                        }
+                    }
+                })
+            })
+            .collect();
+        if accessors.is_empty() {
+            let name = quote!(#referenced).to_string();
+            panic!("kind references({name}) needs a Ref<{name}> field");
+        }
+        accessors
+    });
+    let accessors: Vec<_> = accessors.flatten().collect();
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        ..
+    } = input;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let references = (!accessors.is_empty()).then(|| {
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                #(#accessors)*
+            }
+        }
+    });
+    let prefixed_row = opts.prefixed_row.is_present().then(|| {
+        let Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) = data
+        else {
+            panic!("kind prefixed_row needs a struct with named fields");
+        };
+        let idents: Vec<_> = fields
+            .named
+            .iter()
+            .filter_map(|f| f.ident.clone())
+            .collect();
+        let columns = idents
+            .iter()
+            .map(|ident| ident.to_string().trim_start_matches("r#").to_string());
+        // the impl is for any sqlx row whose database decodes the fields
+        let mut row_generics = generics.clone();
+        row_generics.params.push(parse_quote!(R__: sqlx::Row));
+        let predicates = &mut row_generics.make_where_clause().predicates;
+        for field in &fields.named {
+            let ty = &field.ty;
+            predicates.push(parse_quote! {
+                #ty: for<'r__> sqlx::Decode<'r__, R__::Database> + sqlx::Type<R__::Database>
+            });
+        }
+        predicates.push(parse_quote!(for<'i__> &'i__ str: sqlx::ColumnIndex<R__>));
+        let (row_impl_generics, _, row_where_clause) = row_generics.split_for_impl();
+        quote! {
+            impl #row_impl_generics FromPrefixedRow<R__> for #ident #ty_generics #row_where_clause {
+                fn from_prefixed_row(prefix: &str, row: &R__) -> sqlx::Result<Self> {
+                    use sqlx::Row;
+                    Ok(Self {
+                        #(
+                            #idents: row.try_get(format!("{}{}", prefix, #columns).as_str())?,
+                        )*
+                    })
+                }
+            }
+        }
+    });
     let gen = quote! {
-        pub static #class_const: IdClass = IdClass::new(#class);
-        impl Identifiable for #ident {
+        pub static #class_const: IdClass = IdClass::new(#class)#sensitivity;
+        impl #impl_generics Identifiable for #ident #ty_generics #where_clause {
             fn class() -> IdClass {
                 #class_const
             }
+            const PUBLIC_ID_LEN: usize = IdClass::new(#class).public_id_len();
+            #id_field
+            #id_aliases
         }
+        #prefixed_row
+        #references
+        #registered
     };
     gen.into()
 }
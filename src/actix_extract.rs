@@ -0,0 +1,198 @@
+use {
+    super::*,
+    actix_web::{
+        dev::Payload, error::InternalError, http::StatusCode, FromRequest, HttpRequest,
+        HttpResponse, ResponseError,
+    },
+    std::future::{ready, Ready},
+};
+
+/// The status codes of the responses to invalid ids, which can be
+/// registered as app data to change the defaults:
+///
+/// ```ignore
+/// App::new().app_data(IdErrorConfig {
+///     wrong_class: StatusCode::BAD_REQUEST,
+///     ..Default::default()
+/// })
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct IdErrorConfig {
+    /// Status when the id is of another class, 404 by default (see
+    /// [`WrongClassStatus`])
+    pub wrong_class: StatusCode,
+    /// Status when the id isn't valid, 422 by default
+    pub invalid_format: StatusCode,
+    /// Status when there's no id parameter, 400 by default
+    pub missing: StatusCode,
+}
+
+impl Default for IdErrorConfig {
+    fn default() -> Self {
+        Self {
+            wrong_class: StatusCode::NOT_FOUND,
+            invalid_format: StatusCode::UNPROCESSABLE_ENTITY,
+            missing: StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IdErrorConfig {
+    fn status(&self, error: &IdError) -> StatusCode {
+        match error.kind() {
+            IdErrorKind::WrongClass | IdErrorKind::UnknownPrefix => self.wrong_class,
+            _ => self.invalid_format,
+        }
+    }
+    fn response(&self, rejection: IdRejection) -> actix_web::Error {
+        let status = match &rejection {
+            IdRejection::Invalid { error, .. } => self.status(error),
+            IdRejection::Missing { .. } => self.missing,
+        };
+        let response = HttpResponse::build(status).json(rejection.body());
+        InternalError::from_response(rejection, response).into()
+    }
+}
+
+/// An IdError returned by a handler is rendered as a JSON body,
+/// with the default status codes of [`IdErrorConfig`]
+impl ResponseError for IdError {
+    fn status_code(&self) -> StatusCode {
+        IdErrorConfig::default().status(self)
+    }
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(rejection::error_body(self))
+    }
+}
+
+/// An Id is extracted from the path parameter named like the id field
+/// of the class (`id` by default), or from the only path parameter:
+///
+/// ```ignore
+/// #[get("/customers/{id}")]
+/// async fn get_customer(id: Id<Customer>) -> impl Responder {
+///     // ...
+/// }
+/// ```
+///
+/// Invalid ids are rejected with a JSON body giving the expected prefix,
+/// and the status codes of the [`IdErrorConfig`] of the app.
+impl<O: Identifiable> FromRequest for Id<O> {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let params: Vec<(&str, &str)> = req.match_info().iter().collect();
+        let value = match params.iter().find(|(name, _)| *name == O::ID_FIELD) {
+            Some((_, value)) => Some(*value),
+            None if params.len() == 1 => Some(params[0].1),
+            None => None,
+        };
+        let id = value
+            .ok_or(IdRejection::Missing {
+                parameter: O::ID_FIELD,
+            })
+            .and_then(|value| IdRejection::parse(value, WrongClassStatus::default()));
+        ready(id.map_err(|rejection| {
+            req.app_data::<IdErrorConfig>()
+                .copied()
+                .unwrap_or_default()
+                .response(rejection)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        actix_web::{
+            body::to_bytes, http::StatusCode, test::TestRequest, FromRequest, ResponseError,
+        },
+        serde_json::{json, Value},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    async fn extract(request: TestRequest) -> Result<Id<Customer>, (StatusCode, Value)> {
+        let req = request.to_http_request();
+        match Id::<Customer>::extract(&req).await {
+            Ok(id) => Ok(id),
+            Err(e) => {
+                let response = e.error_response();
+                let status = response.status();
+                let body = to_bytes(response.into_body()).await.unwrap();
+                Err((status, serde_json::from_slice(&body).unwrap()))
+            }
+        }
+    }
+
+    #[test]
+    fn test_extractor() {
+        async_std::task::block_on(async {
+            let id = extract(TestRequest::default().param("id", format!("Cust_{ID}")))
+                .await
+                .unwrap();
+            assert_eq!(id.db_id(), ID);
+            let id = extract(
+                TestRequest::default()
+                    .param("contract", "Cont_x")
+                    .param("id", format!("Cust_{ID}")),
+            )
+            .await
+            .unwrap();
+            assert_eq!(id.db_id(), ID);
+
+            let (status, body) = extract(TestRequest::default().param("c", format!("Cont_{ID}")))
+                .await
+                .unwrap_err();
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(
+                body,
+                json!({
+                    "error": "wrong_class",
+                    "message": "wrong object class (expected Cust_ prefix)",
+                    "expected_prefix": "Cust",
+                })
+            );
+            let (status, _) = extract(TestRequest::default()).await.unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+        });
+    }
+
+    #[test]
+    fn test_config() {
+        async_std::task::block_on(async {
+            let config = IdErrorConfig {
+                wrong_class: StatusCode::BAD_REQUEST,
+                ..Default::default()
+            };
+            let request = || TestRequest::default().app_data(config);
+            let (status, _) = extract(request().param("id", format!("Cont_{ID}")))
+                .await
+                .unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            let (status, body) = extract(request().param("id", "Cust_123"))
+                .await
+                .unwrap_err();
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(body["error"], "invalid_format");
+        });
+    }
+
+    #[test]
+    fn test_id_error_response() {
+        assert_eq!(
+            IdError::new(IdErrorKind::WrongClass).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            IdError::new(IdErrorKind::InvalidFormat).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use {
+    super::*,
+    std::{fmt, str::FromStr},
+    uuid::Uuid,
+};
+
+/// An id of any registered class, when the class is only known at
+/// runtime, eg the subject of an audit log or the target of a comment
+///
+/// It's parsed from the public id, the prefix giving the class, which
+/// is found in the [`registry`] in constant time:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let any_id: AnyId = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// assert_eq!(any_id.class().prefix(), "Cust");
+/// let id: Id<Customer> = any_id.downcast().unwrap();
+/// assert_eq!(AnyId::from(id), any_id);
+///
+/// let error = AnyId::from_public_id("Nope_371c35ec-34d9-4315-ab31-7ea8889a419a").unwrap_err();
+/// assert_eq!(error.kind(), IdErrorKind::UnknownPrefix);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnyId {
+    class: IdClass,
+    uuid: Uuid,
+}
+
+impl AnyId {
+    /// Return the class of the id
+    pub fn class(&self) -> IdClass {
+        self.class
+    }
+    /// Return the internal UUID
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+    /// Return the registered class of the id, with the name of its type
+    ///
+    /// It's `None` when the id was converted from the `Id` of a type whose
+    /// `Identifiable` implementation isn't registered, ie not made by the
+    /// derive.
+    pub fn registered_class(&self) -> Option<&'static RegisteredClass> {
+        registry::find(self.class.prefix())
+    }
+    /// Return the public representation as a string
+    pub fn public_id(&self) -> String {
+        self.class.public_id(&self.uuid)
+    }
+    /// Parse a public id of any registered class
+    pub fn from_public_id(public_id: &str) -> Result<Self, IdError> {
+        // the class is the registered one of the text before the first
+        // underscore, then the id is parsed with the grammar of the class
+        let Some(prefix_len) = public_id.find('_') else {
+            return Err(IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(public_id)
+                .with_position(public_id.len()));
+        };
+        let Some(registered) = registry::find(&public_id[..prefix_len]) else {
+            return Err(IdError::new(IdErrorKind::UnknownPrefix)
+                .with_input(public_id)
+                .with_position(0));
+        };
+        let class = registered.class();
+        let db_id = class.strip_prefix(public_id)?;
+        let uuid = class.parse_db_id(db_id).map_err(|e| {
+            e.with_input(public_id)
+                .with_position(public_id.len() - db_id.len())
+        })?;
+        Ok(Self { class, uuid })
+    }
+    /// Return the typed id, if the id is of the class of `O`
+    pub fn downcast<O: Identifiable>(self) -> Result<Id<O>, IdError> {
+        if self.class == O::class() {
+            Ok(Id::unchecked(self.uuid))
+        } else {
+            Err(IdError::new(IdErrorKind::WrongClass)
+                .with_input(&self.public_id())
+                .with_expected(O::class())
+                .with_position(0))
+        }
+    }
+    /// Tell whether the id is of the class of `O`
+    pub fn is<O: Identifiable>(&self) -> bool {
+        self.class == O::class()
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for AnyId {
+    fn from(id: Id<O>) -> Self {
+        Self {
+            class: O::class(),
+            uuid: id.uuid,
+        }
+    }
+}
+
+/// An AnyId is displayed as its public id
+impl fmt::Display for AnyId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.class.fmt_public_id(&self.uuid, f)
+    }
+}
+
+impl fmt::Debug for AnyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.class.fmt_debug(&self.uuid, f)
+    }
+}
+
+impl FromStr for AnyId {
+    type Err = IdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_public_id(s)
+    }
+}
+
+/// An AnyId is serialized as its public id
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnyId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// An AnyId is deserialized from a public id of a registered class
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AnyId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AnyIdVisitor;
+        impl serde::de::Visitor<'_> for AnyIdVisitor {
+            type Value = AnyId;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a public id of a registered class")
+            }
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                AnyId::from_public_id(v).map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(AnyIdVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "AnyCust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "AnyCont")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_unregistered_class() {
+        pub struct Manual {}
+        impl Identifiable for Manual {
+            fn class() -> IdClass {
+                IdClass::new("AnyManual")
+            }
+        }
+        let any_id = AnyId::from(Id::<Manual>::from_db_id(ID).unwrap());
+        assert!(any_id.registered_class().is_none());
+        assert_eq!(any_id.to_string(), format!("AnyManual_{ID}"));
+    }
+
+    #[test]
+    fn test_any_id() {
+        let any_id = AnyId::from_public_id(&format!("ANYCUST_{ID}")).unwrap();
+        assert_eq!(any_id.class(), Customer::class());
+        assert_eq!(any_id.to_string(), format!("AnyCust_{ID}"));
+        assert_eq!(
+            any_id.registered_class().map(RegisteredClass::type_name),
+            Some("Customer")
+        );
+        assert!(any_id.is::<Customer>());
+        let id: Id<Customer> = any_id.downcast().unwrap();
+        assert_eq!(id.db_id(), ID);
+        assert_eq!(
+            any_id.downcast::<Contract>().unwrap_err().kind(),
+            IdErrorKind::WrongClass
+        );
+        for (public_id, kind) in [
+            (format!("Nope_{ID}"), IdErrorKind::UnknownPrefix),
+            (ID.to_string(), IdErrorKind::InvalidFormat),
+            ("AnyCont_371c35ec".to_string(), IdErrorKind::InvalidFormat),
+        ] {
+            assert_eq!(AnyId::from_public_id(&public_id).unwrap_err().kind(), kind);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_any_id_serde() {
+        let ids = vec![
+            AnyId::from(Id::<Customer>::random_v4()),
+            AnyId::from(Id::<Contract>::random_v4()),
+        ];
+        let json = serde_json::to_string(&ids).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<AnyId>>(&json).unwrap(), ids);
+        assert!(serde_json::from_str::<AnyId>(&format!("\"Nope_{ID}\"")).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Columns of ids in Arrow arrays, eg for Parquet exports.
+//!
+//! Ids are stored as `FixedSizeBinary(16)` values, the bytes of their
+//! uuids, in fields tagged with the canonical `arrow.uuid` extension type
+//! and with the prefix of their class in the `kind:class` metadata, which
+//! is checked when reading the column back.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let ids = vec![Some(Id::<Customer>::random_v4()), None];
+//! let field = kind::arrow::id_field::<Customer>("customer_id", true);
+//! let array = kind::arrow::ids_to_array(&ids);
+//! assert_eq!(kind::arrow::ids_from_array::<Customer>(&field, &array).unwrap(), ids);
+//! ```
+
+use {
+    crate::*,
+    arrow_array::{builder::FixedSizeBinaryBuilder, Array, FixedSizeBinaryArray},
+    arrow_schema::{ArrowError, DataType, Field},
+    std::collections::HashMap,
+    uuid::Uuid,
+};
+
+/// The key of the field metadata holding the prefix of the class
+pub const CLASS_METADATA_KEY: &str = "kind:class";
+
+/// The key of the field metadata naming the extension type
+const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+
+/// The canonical extension type of uuids
+const UUID_EXTENSION_NAME: &str = "arrow.uuid";
+
+/// Return the field of a column of ids of the class of `O`
+pub fn id_field<O: Identifiable>(name: &str, nullable: bool) -> Field {
+    Field::new(name, DataType::FixedSizeBinary(16), nullable).with_metadata(HashMap::from([
+        (
+            EXTENSION_NAME_KEY.to_string(),
+            UUID_EXTENSION_NAME.to_string(),
+        ),
+        (
+            CLASS_METADATA_KEY.to_string(),
+            O::class().prefix().to_string(),
+        ),
+    ]))
+}
+
+/// Build the array of a column of ids, `None` being stored as null
+pub fn ids_to_array<O: Identifiable>(ids: &[Option<Id<O>>]) -> FixedSizeBinaryArray {
+    let mut builder = FixedSizeBinaryBuilder::with_capacity(ids.len(), 16);
+    for id in ids {
+        match id {
+            Some(id) => builder
+                .append_value(id.uuid().as_bytes())
+                .expect("uuids have 16 bytes"),
+            None => builder.append_null(),
+        }
+    }
+    builder.finish()
+}
+
+/// Read a column of ids, checking its type and, when the field has a
+/// `kind:class` metadata, the class
+pub fn ids_from_array<O: Identifiable>(
+    field: &Field,
+    array: &dyn Array,
+) -> Result<Vec<Option<Id<O>>>, ArrowError> {
+    check_field::<O>(field)?;
+    let array = array
+        .as_any()
+        .downcast_ref::<FixedSizeBinaryArray>()
+        .filter(|array| array.value_length() == 16)
+        .ok_or_else(|| {
+            ArrowError::InvalidArgumentError(format!(
+                "expected a FixedSizeBinary(16) array of ids, got {}",
+                array.data_type()
+            ))
+        })?;
+    Ok((0..array.len())
+        .map(|i| {
+            array.is_valid(i).then(|| {
+                let bytes = array.value(i).try_into().expect("values have 16 bytes");
+                Id::unchecked(Uuid::from_bytes(bytes))
+            })
+        })
+        .collect())
+}
+
+/// Check a field can hold ids of the class of `O`
+///
+/// As in public ids, the case of the class in the metadata is ignored.
+pub fn check_field<O: Identifiable>(field: &Field) -> Result<(), ArrowError> {
+    if field.data_type() != &DataType::FixedSizeBinary(16) {
+        return Err(ArrowError::SchemaError(format!(
+            "field {:?} of type {} can't hold ids",
+            field.name(),
+            field.data_type(),
+        )));
+    }
+    match field.metadata().get(CLASS_METADATA_KEY) {
+        Some(prefix) if !prefix.eq_ignore_ascii_case(O::class().prefix()) => {
+            Err(ArrowError::SchemaError(format!(
+                "field {:?} holds {prefix} ids, not {} ones",
+                field.name(),
+                O::class().prefix(),
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        arrow_array::{Array, Int32Array},
+        arrow_schema::Field,
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[test]
+    fn test_arrow_column() {
+        let ids: Vec<Option<Id<Customer>>> =
+            (0..10).map(|i| (i % 3 != 0).then(Id::random_v4)).collect();
+        let field = arrow::id_field::<Customer>("customer_id", true);
+        assert_eq!(field.metadata()[arrow::CLASS_METADATA_KEY], "Cust");
+        let array = arrow::ids_to_array(&ids);
+        assert_eq!(array.null_count(), 4);
+        assert_eq!(array.value(1), ids[1].unwrap().uuid().as_bytes());
+        assert_eq!(arrow::ids_from_array(&field, &array).unwrap(), ids);
+
+        let untagged = Field::new("customer_id", field.data_type().clone(), true);
+        assert_eq!(arrow::ids_from_array(&untagged, &array).unwrap(), ids);
+        let lowercase = untagged
+            .clone()
+            .with_metadata([(arrow::CLASS_METADATA_KEY.to_string(), "cust".to_string())].into());
+        assert_eq!(arrow::ids_from_array(&lowercase, &array).unwrap(), ids);
+        assert!(arrow::ids_from_array::<Contract>(&field, &array).is_err());
+        let numbers = Int32Array::from(vec![1, 2]);
+        assert!(arrow::ids_from_array::<Customer>(&untagged, &numbers).is_err());
+        assert!(arrow::ids_to_array::<Customer>(&[]).is_empty());
+    }
+}
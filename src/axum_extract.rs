@@ -0,0 +1,220 @@
+use {
+    super::*,
+    axum::{
+        extract::{FromRequestParts, Query, RawPathParams},
+        http::{request::Parts, StatusCode},
+        response::{IntoResponse, Response},
+        Json,
+    },
+};
+
+/// The rejection is rendered as a JSON body, with the default status
+impl IntoResponse for IdRejection {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status_code()).unwrap_or(StatusCode::BAD_REQUEST);
+        (status, Json(self.body())).into_response()
+    }
+}
+
+/// An IdError returned by a handler is rendered as a JSON body, with
+/// the default status of [`IdError::http_status`]
+impl IntoResponse for IdError {
+    fn into_response(self) -> Response {
+        let status =
+            StatusCode::from_u16(self.http_status()).unwrap_or(StatusCode::UNPROCESSABLE_ENTITY);
+        (status, Json(rejection::error_body(&self))).into_response()
+    }
+}
+
+/// The status of the ids of another class, configured for the router
+/// with an `Extension`
+fn wrong_class(parts: &Parts) -> WrongClassStatus {
+    parts
+        .extensions
+        .get::<WrongClassStatus>()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// An Id is extracted from the path parameter named like the id field
+/// of the class (`id` by default), or from the only path parameter:
+///
+/// ```ignore
+/// async fn get_customer(id: Id<Customer>) -> Json<Ided<Customer>> {
+///     // ...
+/// }
+/// let app = Router::new().route("/customers/{id}", get(get_customer));
+/// ```
+///
+/// Ids of another class are rejected with a 404, or with the
+/// [`WrongClassStatus`] of the router when it's given as an extension.
+impl<O: Identifiable, S: Send + Sync> FromRequestParts<S> for Id<O> {
+    type Rejection = IdRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let missing = || IdRejection::Missing {
+            parameter: O::ID_FIELD,
+        };
+        let params = RawPathParams::from_request_parts(parts, state)
+            .await
+            .map_err(|_| missing())?;
+        let mut params: Vec<(&str, &str)> = params.iter().collect();
+        let value = match params.iter().find(|(name, _)| *name == O::ID_FIELD) {
+            Some((_, value)) => value,
+            None if params.len() == 1 => {
+                params.pop().map(|(_, value)| value).ok_or_else(missing)?
+            }
+            None => return Err(missing()),
+        };
+        IdRejection::parse(value, wrong_class(parts))
+    }
+}
+
+/// Extract an Id from the query parameter named like the id field
+/// of the class (`id` by default), eg `/invoices?id=Inv_...`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryId<O: Identifiable>(pub Id<O>);
+
+impl<O: Identifiable, S: Send + Sync> FromRequestParts<S> for QueryId<O> {
+    type Rejection = IdRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let missing = || IdRejection::Missing {
+            parameter: O::ID_FIELD,
+        };
+        let Query(params) = Query::<Vec<(String, String)>>::from_request_parts(parts, state)
+            .await
+            .map_err(|_| missing())?;
+        let (_, value) = params
+            .iter()
+            .find(|(name, _)| name == O::ID_FIELD)
+            .ok_or_else(missing)?;
+        IdRejection::parse(value, wrong_class(parts)).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        axum::{
+            body::Body,
+            http::{Request, StatusCode},
+            response::IntoResponse,
+            routing::get,
+            Extension, Router,
+        },
+        http_body_util::BodyExt,
+        serde_json::{json, Value},
+        tower::ServiceExt,
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont", id_field = "contract")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/customers/{id}",
+                get(|id: Id<Customer>| async move { id.db_id() }),
+            )
+            .route(
+                "/customers/{customer}/contracts/{contract}",
+                get(|id: Id<Contract>| async move { id.db_id() }),
+            )
+            .route(
+                "/contracts",
+                get(|QueryId(id): QueryId<Contract>| async move { id.db_id() }),
+            )
+    }
+
+    async fn call(uri: &str) -> (StatusCode, String) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_extractors() {
+        async_std::task::block_on(async {
+            assert_eq!(
+                call(&format!("/customers/Cust_{ID}")).await,
+                (StatusCode::OK, ID.to_string())
+            );
+            assert_eq!(
+                call(&format!("/customers/Cust_x/contracts/Cont_{ID}")).await,
+                (StatusCode::OK, ID.to_string())
+            );
+            assert_eq!(
+                call(&format!("/contracts?other=1&contract=Cont_{ID}")).await,
+                (StatusCode::OK, ID.to_string())
+            );
+        });
+    }
+
+    #[test]
+    fn test_rejections() {
+        async_std::task::block_on(async {
+            let (status, body) = call(&format!("/customers/Cont_{ID}")).await;
+            assert_eq!(status, StatusCode::NOT_FOUND);
+            assert_eq!(
+                serde_json::from_str::<Value>(&body).unwrap(),
+                json!({
+                    "error": "wrong_class",
+                    "message": "wrong object class (expected Cust_ prefix)",
+                    "expected_prefix": "Cust",
+                })
+            );
+            let (status, body) = call("/customers/Cust_123").await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(body.contains(r#""error":"invalid_format""#));
+            let (status, body) = call(&format!("/contracts?id=Cont_{ID}")).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body.contains(r#""parameter":"contract""#));
+        });
+    }
+
+    #[test]
+    fn test_wrong_class_status() {
+        async_std::task::block_on(async {
+            let app = app().layer(Extension(WrongClassStatus::UnprocessableEntity));
+            for (uri, status) in [
+                (
+                    format!("/customers/Cont_{ID}"),
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                ),
+                (
+                    format!("/contracts?contract=Cust_{ID}"),
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                ),
+                (format!("/customers/Cust_{ID}"), StatusCode::OK),
+            ] {
+                let request = Request::get(&uri).body(Body::empty()).unwrap();
+                let response = app.clone().oneshot(request).await.unwrap();
+                assert_eq!(response.status(), status, "{uri}");
+            }
+            assert_eq!(
+                call(&format!("/customers/Cont_{ID}")).await.0,
+                StatusCode::NOT_FOUND
+            );
+        });
+    }
+
+    #[test]
+    fn test_id_error_response() {
+        let error = "Cust_123".parse::<Id<Customer>>().unwrap_err();
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let error = format!("Cont_{ID}").parse::<Id<Customer>>().unwrap_err();
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,138 @@
+use super::*;
+
+/// The result of the validation of a batch of public ids, with the ids
+/// which are valid and the errors of the others, with their index in
+/// the batch
+#[derive(Debug, Clone)]
+pub struct BatchReport<O: Identifiable> {
+    ids: Vec<(usize, Id<O>)>,
+    errors: Vec<(usize, IdError)>,
+}
+
+/// Parse all the public ids of a batch, collecting all the failures
+/// instead of stopping at the first one:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let report = validate_batch::<Customer, _>([
+///     "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a",
+///     "Cont_371c35ec-34d9-4315-ab31-7ea8889a419a",
+///     "Cust_371c35ec",
+/// ]);
+/// assert!(!report.is_ok());
+/// assert_eq!(report.ids().len(), 1);
+/// let indices: Vec<usize> = report.errors().iter().map(|(i, _)| *i).collect();
+/// assert_eq!(indices, [1, 2]);
+/// ```
+pub fn validate_batch<'s, O, I>(public_ids: I) -> BatchReport<O>
+where
+    O: Identifiable,
+    I: IntoIterator<Item = &'s str>,
+{
+    let mut report = BatchReport {
+        ids: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (index, public_id) in public_ids.into_iter().enumerate() {
+        match Id::from_public_id(public_id) {
+            Ok(id) => report.ids.push((index, id)),
+            Err(e) => report.errors.push((index, e)),
+        }
+    }
+    report
+}
+
+impl<O: Identifiable> BatchReport<O> {
+    /// Tell whether all the ids of the batch are valid
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Return the valid ids, with their index in the batch
+    pub fn ids(&self) -> &[(usize, Id<O>)] {
+        &self.ids
+    }
+    /// Return the errors, with the index of the invalid id in the batch
+    pub fn errors(&self) -> &[(usize, IdError)] {
+        &self.errors
+    }
+    /// Return all the ids, in the order of the batch, if they're all
+    /// valid, or the report
+    pub fn into_result(self) -> Result<Vec<Id<O>>, Self> {
+        if self.is_ok() {
+            Ok(self.ids.into_iter().map(|(_, id)| id).collect())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// A report is serialized as the list of its errors, each one with the
+/// index of the row and the fields of the serialized [`IdError`]
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for BatchReport<O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct Row<'e> {
+            index: usize,
+            #[serde(flatten)]
+            error: &'e IdError,
+        }
+        serializer.collect_seq(self.errors.iter().map(|(index, error)| Row {
+            index: *index,
+            error,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_validate_batch() {
+        let rows = [format!("Cust_{ID}"), format!("cust_{ID}")];
+        let ids = validate_batch::<Customer, _>(rows.iter().map(String::as_str))
+            .into_result()
+            .unwrap();
+        assert_eq!(ids, [ids[0], ids[0]]);
+
+        let report = validate_batch::<Customer, _>(["", "Cust_x", &rows[0], "Inv_x"]);
+        assert_eq!(report.ids()[0].0, 2);
+        let errors: Vec<_> = report
+            .errors()
+            .iter()
+            .map(|(index, error)| (*index, error.kind()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (0, IdErrorKind::WrongClass),
+                (1, IdErrorKind::InvalidFormat),
+                (3, IdErrorKind::WrongClass),
+            ]
+        );
+        let report = report.into_result().unwrap_err();
+        assert_eq!(report.errors().len(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_report() {
+        let report = validate_batch::<Customer, _>([format!("Cust_{ID}").as_str(), "Cust_x"]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json[0]["index"], 1);
+        assert_eq!(json[0]["code"], "KIND_BAD_UUID");
+        assert_eq!(json.as_array().unwrap().len(), 1);
+    }
+}
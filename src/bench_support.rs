@@ -0,0 +1,112 @@
+//! Corpora of ids for benchmarks, behind the `bench-support` feature.
+//!
+//! The ids are pseudo random but deterministic: the same seed gives the
+//! same corpus, so that two runs of a benchmark, eg before and after a
+//! change, work on the same inputs.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let public_ids = bench_support::public_ids::<Customer>(42, 1000);
+//! assert_eq!(public_ids.len(), 1000);
+//! assert_eq!(public_ids, bench_support::public_ids::<Customer>(42, 1000));
+//! ```
+//!
+//! The corpora should be passed through `std::hint::black_box` in the
+//! measured closure, so that the compiler can't specialize the code for
+//! them.
+
+use {super::*, crate::split_mix::SplitMix};
+
+/// Return `n` v4 ids
+pub fn ids<O: Identifiable>(seed: u64, n: usize) -> Vec<Id<O>> {
+    let mut rng = SplitMix(seed);
+    (0..n).map(|_| Id::from(rng.uuid())).collect()
+}
+
+/// Return the public ids of `n` ids, as written by kind
+pub fn public_ids<O: Identifiable>(seed: u64, n: usize) -> Vec<String> {
+    ids::<O>(seed, n).iter().map(Id::public_id).collect()
+}
+
+/// Return the database ids of `n` ids
+pub fn db_ids<O: Identifiable>(seed: u64, n: usize) -> Vec<String> {
+    ids::<O>(seed, n).iter().map(Id::db_id).collect()
+}
+
+/// Return `n` valid public ids, whose characters have a random case,
+/// as they may come from other systems
+pub fn mixed_case_public_ids<O: Identifiable>(seed: u64, n: usize) -> Vec<String> {
+    let mut rng = SplitMix(seed);
+    public_ids::<O>(seed, n)
+        .into_iter()
+        .map(|public_id| {
+            let mask = rng.next();
+            public_id
+                .chars()
+                .enumerate()
+                .map(|(i, c)| {
+                    if mask >> (i % 64) & 1 == 1 {
+                        c.to_ascii_uppercase()
+                    } else {
+                        c.to_ascii_lowercase()
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Return `n` invalid public ids of the class: ids of another class,
+/// truncated ids, ids without underscore, and ids with a bad character
+pub fn invalid_public_ids<O: Identifiable>(seed: u64, n: usize) -> Vec<String> {
+    let mut rng = SplitMix(seed);
+    public_ids::<O>(seed, n)
+        .into_iter()
+        .map(|public_id| {
+            let prefix_len = O::class().prefix().len();
+            let db_id = &public_id[prefix_len + 1..];
+            match rng.next() % 4 {
+                0 => format!("{}X_{db_id}", O::class().prefix()),
+                1 => public_id[..public_id.len() - 1 - (rng.next() % 30) as usize].to_string(),
+                2 => public_id.replacen('_', "", 1),
+                _ => {
+                    let mut public_id = public_id.into_bytes();
+                    let position = prefix_len + 1 + (rng.next() % DB_ID_LEN as u64) as usize;
+                    public_id[position] = b'g';
+                    String::from_utf8(public_id).unwrap()
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_corpora() {
+        let ids = bench_support::ids::<Customer>(1, 100);
+        assert_eq!(ids, bench_support::ids::<Customer>(1, 100));
+        assert_ne!(ids, bench_support::ids::<Customer>(2, 100));
+        assert!(ids.iter().all(|id| id.uuid().get_version_num() == 4));
+        for public_id in bench_support::mixed_case_public_ids::<Customer>(1, 100) {
+            assert!(ids.contains(&public_id.parse().unwrap()));
+        }
+        for db_id in bench_support::db_ids::<Customer>(1, 100) {
+            assert!(ids.contains(&Id::from_db_id(&db_id).unwrap()));
+        }
+        for public_id in bench_support::invalid_public_ids::<Customer>(1, 100) {
+            assert!(Id::<Customer>::from_public_id(&public_id).is_err());
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Inspect, generate and find ids from the command line, with the
+//! parsing rules of the crate:
+//!
+//! ```text
+//! kind-cli new Cust --count 3 --v7
+//! kind-cli inspect Cust_018e0f9c-6a4b-7cc2-9d3a-5f6b7c8d9e0f
+//! kind-cli convert --to db Cust_371c35ec-34d9-4315-ab31-7ea8889a419a
+//! kind-cli convert --to public --class Cust 371c35ec-34d9-4315-ab31-7ea8889a419a
+//! kind-cli grep Cust app.log
+//! ```
+//!
+//! Ids are read from the standard input, one per line, when none is
+//! given as argument.
+
+use {
+    clap::{Parser, Subcommand, ValueEnum},
+    kind::{IdClass, IdError},
+    std::{
+        fs::File,
+        io::{self, BufRead, BufReader, Write},
+        process::ExitCode,
+    },
+    uuid::Uuid,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "kind-cli",
+    version,
+    about = "Inspect, generate and find kind ids"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate new ids of a class
+    New {
+        /// The prefix of the class, eg Cust
+        class: String,
+        /// The number of ids to generate
+        #[arg(short = 'n', long, default_value_t = 1)]
+        count: usize,
+        /// Generate time ordered (v7) uuids instead of random (v4) ones
+        #[arg(long)]
+        v7: bool,
+    },
+    /// Show the class, uuid, version and time (for time based uuids) of
+    /// public ids
+    Inspect { ids: Vec<String> },
+    /// Convert ids between representations
+    Convert {
+        #[arg(long, value_enum)]
+        to: Format,
+        /// The class of db ids, or the expected class of public ids
+        #[arg(long)]
+        class: Option<String>,
+        ids: Vec<String>,
+    },
+    /// Print the ids of a class found in files (or the standard input),
+    /// with their location
+    Grep { class: String, files: Vec<String> },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// The public id, eg Cust_371c35ec-34d9-4315-ab31-7ea8889a419a
+    Public,
+    /// The hyphenated uuid, as stored in database
+    Db,
+    /// The uuid without hyphens
+    Simple,
+    /// The uuid as an URN
+    Urn,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("kind-cli: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let mut out = io::stdout().lock();
+    match args.command {
+        Command::New { class, count, v7 } => {
+            let class = class_of(&class)?;
+            for _ in 0..count {
+                let uuid = if v7 { Uuid::now_v7() } else { Uuid::new_v4() };
+                writeln!(out, "{}_{uuid}", class.prefix()).map_err(io_error)?;
+            }
+        }
+        Command::Inspect { ids } => {
+            for id in inputs(ids)? {
+                let (class, uuid) = parse_public_id(&id, None)?;
+                writeln!(out, "class:   {}", class.prefix()).map_err(io_error)?;
+                writeln!(out, "uuid:    {uuid}").map_err(io_error)?;
+                writeln!(out, "version: {}", uuid.get_version_num()).map_err(io_error)?;
+                if let Some(timestamp) = uuid.get_timestamp() {
+                    let (seconds, nanos) = timestamp.to_unix();
+                    writeln!(out, "time:    {}", format_time(seconds, nanos)).map_err(io_error)?;
+                }
+                writeln!(out).map_err(io_error)?;
+            }
+        }
+        Command::Convert { to, class, ids } => {
+            let class = class.as_deref().map(class_of).transpose()?;
+            for id in inputs(ids)? {
+                let (class, uuid) = if id.contains('_') {
+                    parse_public_id(&id, class)?
+                } else {
+                    let class = class.ok_or("--class is needed to convert db ids")?;
+                    (class, class.parse_db_id(id.trim()).map_err(id_error)?)
+                };
+                let converted = match to {
+                    Format::Public => format!("{}_{uuid}", class.prefix()),
+                    Format::Db => uuid.hyphenated().to_string(),
+                    Format::Simple => uuid.simple().to_string(),
+                    Format::Urn => uuid.urn().to_string(),
+                };
+                writeln!(out, "{converted}").map_err(io_error)?;
+            }
+        }
+        Command::Grep { class, files } => {
+            let class = class_of(&class)?;
+            if files.is_empty() {
+                grep(class, None, io::stdin().lock(), &mut out)?;
+            }
+            for file in &files {
+                let reader = BufReader::new(File::open(file).map_err(|e| format!("{file}: {e}"))?);
+                grep(class, Some(file), reader, &mut out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build the class of a prefix given on the command line
+fn class_of(prefix: &str) -> Result<IdClass, String> {
+    if prefix.is_empty() || !prefix.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(format!("invalid class {prefix:?}"));
+    }
+    Ok(IdClass::new(Box::leak(prefix.into())))
+}
+
+/// Parse a public id, of the class when given, or of the class of its
+/// prefix
+fn parse_public_id(id: &str, class: Option<IdClass>) -> Result<(IdClass, Uuid), String> {
+    let id = id.trim();
+    let class = match class {
+        Some(class) => class,
+        None => class_of(id.split('_').next().unwrap_or_default())?,
+    };
+    let uuid = class.parse_public_id(id).map_err(id_error)?;
+    Ok((class, uuid))
+}
+
+/// The ids given as arguments, or read from the standard input
+fn inputs(ids: Vec<String>) -> Result<Vec<String>, String> {
+    if !ids.is_empty() {
+        return Ok(ids);
+    }
+    io::stdin()
+        .lock()
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .collect::<Result<_, _>>()
+        .map_err(io_error)
+}
+
+/// Print the ids of the class found in the lines of the reader
+fn grep(
+    class: IdClass,
+    file: Option<&str>,
+    reader: impl BufRead,
+    out: &mut impl Write,
+) -> Result<(), String> {
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        for id in find_ids(class, &line) {
+            match file {
+                Some(file) => writeln!(out, "{file}:{}: {id}", number + 1),
+                None => writeln!(out, "{}: {id}", number + 1),
+            }
+            .map_err(io_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Find the public ids of the class in a line, whatever their case
+fn find_ids(class: IdClass, line: &str) -> Vec<&str> {
+    let len = class.public_id_len();
+    let bytes = line.as_bytes();
+    let mut ids = Vec::new();
+    let mut start = 0;
+    while start + len <= bytes.len() {
+        let at_word_start = start == 0 || !bytes[start - 1].is_ascii_alphanumeric();
+        let at_word_end = bytes
+            .get(start + len)
+            .map_or(true, |b| !b.is_ascii_alphanumeric());
+        if at_word_start
+            && at_word_end
+            && line.is_char_boundary(start)
+            && line.is_char_boundary(start + len)
+        {
+            let candidate = &line[start..start + len];
+            if class.parse_public_id(candidate).is_ok() {
+                ids.push(candidate);
+                start += len;
+                continue;
+            }
+        }
+        start += 1;
+    }
+    ids
+}
+
+/// Format a unix time as RFC 3339, in UTC with milliseconds
+fn format_time(seconds: u64, nanos: u32) -> String {
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+    // the civil date of a number of days since 1970-01-01, from
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        nanos / 1_000_000
+    )
+}
+
+fn id_error(e: IdError) -> String {
+    e.to_string()
+}
+
+fn io_error(e: io::Error) -> String {
+    e.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_ids() {
+        let class = class_of("Cust").unwrap();
+        let line = "GET /customers/CUST_371c35ec-34d9-4315-ab31-7ea8889a419a (from Cust_371c35ec \
+            XCust_371c35ec-34d9-4315-ab31-7ea8889a419a) Cust_d6a1e4c2-8b1f-4c2e-9d3a-5f6b7c8d9e0f";
+        assert_eq!(
+            find_ids(class, line),
+            [
+                "CUST_371c35ec-34d9-4315-ab31-7ea8889a419a",
+                "Cust_d6a1e4c2-8b1f-4c2e-9d3a-5f6b7c8d9e0f"
+            ]
+        );
+        assert!(find_ids(class, "é Cust_ é").is_empty());
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_time(1_709_294_400, 123_456_789),
+            "2024-03-01T12:00:00.123Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_public_id() {
+        let (class, uuid) =
+            parse_public_id("Cust_371c35ec-34d9-4315-ab31-7ea8889a419a", None).unwrap();
+        assert_eq!(class.prefix(), "Cust");
+        assert_eq!(uuid.to_string(), "371c35ec-34d9-4315-ab31-7ea8889a419a");
+        let cont = class_of("Cont").unwrap();
+        assert_eq!(
+            parse_public_id("Cust_371c35ec-34d9-4315-ab31-7ea8889a419a", Some(cont)).unwrap_err(),
+            "wrong object class"
+        );
+        assert!(class_of("Cu-st").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use {
+    super::*,
+    ::borsh::{
+        io::{Read, Result, Write},
+        BorshDeserialize, BorshSerialize,
+    },
+    uuid::Uuid,
+};
+
+/// An Id is written as the 16 bytes of its UUID
+impl<O: Identifiable> BorshSerialize for Id<O> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.uuid().as_bytes())
+    }
+}
+
+impl<O: Identifiable> BorshDeserialize for Id<O> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let bytes = <[u8; 16]>::deserialize_reader(reader)?;
+        Ok(Id::unchecked(Uuid::from_bytes(bytes)))
+    }
+}
+
+/// An Ided is written as its id followed by its entity
+impl<T: Identifiable, E: BorshSerialize> BorshSerialize for Ided<T, E> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.id().serialize(writer)?;
+        self.entity().serialize(writer)
+    }
+}
+
+impl<T: Identifiable, E: BorshDeserialize> BorshDeserialize for Ided<T, E> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let id = Id::deserialize_reader(reader)?;
+        let entity = E::deserialize_reader(reader)?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Id, IdClass, Ided, Identifiable};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Test")]
+    struct TestStruct {}
+
+    #[test]
+    fn test_borsh_round_trip() {
+        let id = Id::<TestStruct>::random_v4();
+        let bytes = borsh::to_vec(&id).unwrap();
+        assert_eq!(bytes, id.uuid().as_bytes());
+        assert_eq!(borsh::from_slice::<Id<TestStruct>>(&bytes).unwrap(), id);
+        assert!(borsh::from_slice::<Id<TestStruct>>(&bytes[..15]).is_err());
+
+        let ided = Ided::<TestStruct, (String, u32)>::new(id, ("answer".to_string(), 42));
+        let bytes = borsh::to_vec(&ided).unwrap();
+        assert_eq!(&bytes[..16], id.uuid().as_bytes());
+        let back: Ided<TestStruct, (String, u32)> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(back.id(), id);
+        assert_eq!(back.entity(), ided.entity());
+    }
+}
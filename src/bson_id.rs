@@ -0,0 +1,95 @@
+use {
+    super::*,
+    bson::{Binary, Bson},
+};
+
+/// An Id converts into a `bson::Uuid`, stored as a binary UUID
+/// (subtype 4) in MongoDB. The class isn't stored.
+impl<O: Identifiable> From<Id<O>> for bson::Uuid {
+    fn from(id: Id<O>) -> Self {
+        bson::Uuid::from(id.uuid())
+    }
+}
+
+impl<O: Identifiable> From<bson::Uuid> for Id<O> {
+    fn from(uuid: bson::Uuid) -> Self {
+        Id::from(uuid::Uuid::from(uuid))
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for Binary {
+    fn from(id: Id<O>) -> Self {
+        Binary::from_uuid(id.into())
+    }
+}
+
+/// Only binaries with the UUID subtype (4) are accepted
+impl<O: Identifiable> TryFrom<Binary> for Id<O> {
+    type Error = bson::uuid::Error;
+    fn try_from(binary: Binary) -> Result<Self, Self::Error> {
+        binary.to_uuid().map(Id::from)
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for Bson {
+    fn from(id: Id<O>) -> Self {
+        Bson::Binary(id.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        bson::{doc, spec::BinarySubtype, Binary, Bson},
+        serde::{Deserialize, Serialize},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        #[serde(with = "crate::with::bson_uuid")]
+        customer: Id<Customer>,
+        #[serde(with = "crate::with::option_bson_uuid")]
+        referrer: Option<Id<Customer>>,
+        public: Id<Customer>,
+    }
+
+    #[test]
+    fn test_conversions() {
+        let id = Id::<Customer>::random_v4();
+        let binary = Binary::from(id);
+        assert_eq!(binary.subtype, BinarySubtype::Uuid);
+        assert_eq!(binary.bytes, id.uuid().as_bytes());
+        assert_eq!(Id::<Customer>::try_from(binary).unwrap(), id);
+        assert_eq!(Id::<Customer>::from(bson::Uuid::from(id)), id);
+        let generic = Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: id.uuid().as_bytes().to_vec(),
+        };
+        assert!(Id::<Customer>::try_from(generic).is_err());
+    }
+
+    #[test]
+    fn test_bson_uuid() {
+        let id = Id::<Customer>::random_v4();
+        let event = Event {
+            customer: id,
+            referrer: Some(id),
+            public: id,
+        };
+        let document = bson::to_document(&event).unwrap();
+        assert_eq!(
+            document,
+            doc! {
+                "customer": Bson::from(id),
+                "referrer": Bson::from(id),
+                "public": id.public_id(),
+            }
+        );
+        assert_eq!(bson::from_document::<Event>(document).unwrap(), event);
+    }
+}
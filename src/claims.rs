@@ -0,0 +1,174 @@
+//! Serde adapters for the ids of the claims of JSON Web Tokens.
+//!
+//! An `Id<O>` in a custom claims struct, eg the one given to
+//! jsonwebtoken's `decode::<Claims>`, is already written as its public id
+//! and checked against its class when the token is decoded, so that the
+//! token of an API key isn't taken for the token of a user. The adapters
+//! below add the JWT conventions:
+//!
+//! * [`subject`] names the `sub` claim in the error of an invalid id,
+//! * [`audience`] reads the `aud` claim, either a single string or an
+//!   array of strings, and writes a single string when there's only one
+//!   audience.
+//!
+//! Both work for `Id<O>`, for `AnyId` (with the registry feature) and for
+//! the enumerations of [`id_enum!`](crate::id_enum), when a claim can hold
+//! ids of several classes.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="User")]
+//! pub struct User {}
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Org")]
+//! pub struct Organization {}
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! pub struct Claims {
+//!     #[serde(with = "kind::claims::subject")]
+//!     sub: Id<User>,
+//!     #[serde(with = "kind::claims::audience")]
+//!     aud: Vec<Id<Organization>>,
+//!     exp: u64,
+//! }
+//!
+//! let json = r#"{
+//!     "sub": "User_371c35ec-34d9-4315-ab31-7ea8889a419a",
+//!     "aud": "Org_4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a",
+//!     "exp": 1700000000
+//! }"#;
+//! let claims: Claims = serde_json::from_str(json).unwrap();
+//! assert_eq!(claims.aud.len(), 1);
+//!
+//! let json = json.replace("User_", "Org_");
+//! let error = serde_json::from_str::<Claims>(&json).err().unwrap();
+//! assert!(error.to_string().starts_with("invalid sub claim"));
+//! ```
+//!
+//! To only accept the tokens of a given subject, set the `sub` of the
+//! jsonwebtoken `Validation` to `Some(id.public_id())`.
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Use for the `sub` claim, naming it in errors
+pub mod subject {
+    use super::*;
+
+    pub fn serialize<T, S>(sub: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        sub.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+            .map_err(|e| de::Error::custom(format!("invalid sub claim: {e}")))
+    }
+}
+
+/// Use for the `aud` claim, a single value or an array of values
+pub mod audience {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    pub fn serialize<T, S>(aud: &[T], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        match aud {
+            [one] => one.serialize(serializer),
+            many => many.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        match OneOrMany::deserialize(deserializer) {
+            Ok(OneOrMany::One(one)) => Ok(vec![one]),
+            Ok(OneOrMany::Many(many)) => Ok(many),
+            Err(_) => Err(de::Error::custom(
+                "invalid aud claim: expected an id or an array of ids of the audience class",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "User")]
+    pub struct User {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Org")]
+    pub struct Organization {}
+
+    id_enum! {Principal: User, Organization}
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Claims {
+        #[serde(with = "crate::claims::subject")]
+        sub: Principal,
+        #[serde(with = "crate::claims::audience")]
+        aud: Vec<Id<Organization>>,
+    }
+
+    const A: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+    const B: &str = "4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a";
+
+    #[test]
+    fn test_claims() {
+        let claims = Claims {
+            sub: Principal::User(Id::from_db_id(A).unwrap()),
+            aud: vec![Id::from_db_id(B).unwrap()],
+        };
+        let json = serde_json::to_string(&claims).unwrap();
+        assert_eq!(json, format!(r#"{{"sub":"User_{A}","aud":"Org_{B}"}}"#));
+        assert_eq!(serde_json::from_str::<Claims>(&json).unwrap(), claims);
+
+        let json = format!(r#"{{"sub":"Org_{A}","aud":["Org_{A}","Org_{B}"]}}"#);
+        let claims: Claims = serde_json::from_str(&json).unwrap();
+        assert!(matches!(claims.sub, Principal::Organization(_)));
+        assert_eq!(claims.aud.len(), 2);
+        assert_eq!(serde_json::to_string(&claims).unwrap(), json);
+
+        for (json, error) in [
+            (
+                format!(r#"{{"sub":"Cust_{A}","aud":[]}}"#),
+                "invalid sub claim",
+            ),
+            (
+                format!(r#"{{"sub":"User_{A}","aud":"User_{B}"}}"#),
+                "invalid aud claim",
+            ),
+            (
+                format!(r#"{{"sub":"User_{A}","aud":[1]}}"#),
+                "invalid aud claim",
+            ),
+        ] {
+            let e = serde_json::from_str::<Claims>(&json).unwrap_err();
+            assert!(e.to_string().starts_with(error), "{json}: {e}");
+        }
+    }
+}
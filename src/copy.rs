@@ -0,0 +1,335 @@
+//! Writing of ids in the binary format of the PostgreSQL `COPY` command,
+//! to bulk load millions of rows without per row INSERTs nor text escaping.
+//!
+//! A [`CopyWriter`] writes to any `io::Write`, eg a buffer sent with the
+//! sqlx `PgCopyIn`:
+//!
+//! ```
+//! use kind::{*, copy::*};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {
+//!     name: String,
+//!     referrer: Option<Id<Customer>>,
+//! }
+//!
+//! impl CopyFields for Customer {
+//!     fn write_copy_fields(&self, row: &mut CopyRow) {
+//!         row.field(&self.name);
+//!         row.field(&self.referrer);
+//!     }
+//! }
+//!
+//! let customers: Vec<Ided<Customer>> = Vec::new();
+//! let mut writer = CopyWriter::new(Vec::new()).unwrap();
+//! for customer in &customers {
+//!     writer.write_ided(customer).unwrap();
+//! }
+//! let data = writer.finish().unwrap();
+//! // then, with sqlx:
+//! // let mut copy = conn.copy_in_raw("COPY customer (id, name, referrer) FROM STDIN (FORMAT binary)").await?;
+//! // copy.send(data).await?;
+//! // copy.finish().await?;
+//! ```
+//!
+//! With tokio-postgres, ids can also be given to its `BinaryCopyInWriter`
+//! when the tokio-postgres feature is enabled.
+
+use {
+    crate::*,
+    std::io::{self, Write},
+    uuid::Uuid,
+};
+
+/// The signature, flags and header extension length starting the data
+const HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+
+/// The field count marking the end of the data
+const TRAILER: &[u8] = &(-1i16).to_be_bytes();
+
+/// A value which can be written as a field of a binary COPY row
+///
+/// The field is its length as a big endian `i32` followed by the value in
+/// the binary format of its PostgreSQL type, or -1 for a null.
+pub trait CopyValue {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Write a field, failing if it doesn't fit in the 2 GB of its length
+fn write_sized(buf: &mut Vec<u8>, bytes: &[u8]) -> io::Result<()> {
+    let len = i32::try_from(bytes.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("too long COPY field: {} bytes", bytes.len()),
+        )
+    })?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// An id is written as a `uuid`
+impl<O: Identifiable> CopyValue for Id<O> {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        write_sized(buf, self.uuid.as_bytes())
+    }
+}
+
+impl CopyValue for Uuid {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        write_sized(buf, self.as_bytes())
+    }
+}
+
+/// `None` is written as null
+impl<V: CopyValue> CopyValue for Option<V> {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Some(value) => value.write_copy_value(buf),
+            None => {
+                buf.extend_from_slice(&(-1i32).to_be_bytes());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<V: CopyValue + ?Sized> CopyValue for &V {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        (**self).write_copy_value(buf)
+    }
+}
+
+/// A string is written as a `text`, `varchar` or `char`
+impl CopyValue for str {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        write_sized(buf, self.as_bytes())
+    }
+}
+
+impl CopyValue for String {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        self.as_str().write_copy_value(buf)
+    }
+}
+
+impl CopyValue for bool {
+    fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+        write_sized(buf, &[u8::from(*self)])
+    }
+}
+
+macro_rules! copy_value_be_bytes {
+    ($($ty:ty: $pg:literal),*) => {
+        $(
+            #[doc = concat!("Written as a `", $pg, "`")]
+            impl CopyValue for $ty {
+                fn write_copy_value(&self, buf: &mut Vec<u8>) -> io::Result<()> {
+                    write_sized(buf, &self.to_be_bytes())
+                }
+            }
+        )*
+    };
+}
+
+copy_value_be_bytes!(i16: "smallint", i32: "integer", i64: "bigint", f32: "real", f64: "double precision");
+
+/// An entity whose fields can be written after its id in a binary COPY
+/// row, in the order of the columns of the COPY command
+pub trait CopyFields {
+    fn write_copy_fields(&self, row: &mut CopyRow);
+}
+
+/// The fields of a row being written
+///
+/// The first error of the fields is returned by the write of the row.
+pub struct CopyRow<'b> {
+    buf: &'b mut Vec<u8>,
+    count: usize,
+    error: Option<io::Error>,
+}
+
+impl CopyRow<'_> {
+    /// Write the next field of the row
+    pub fn field<V: CopyValue + ?Sized>(&mut self, value: &V) -> &mut Self {
+        if self.error.is_none() {
+            if let Err(e) = value.write_copy_value(self.buf) {
+                self.error = Some(e);
+            }
+        }
+        self.count += 1;
+        self
+    }
+}
+
+/// A writer of the binary format of COPY, see the [module](self) doc
+pub struct CopyWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> CopyWriter<W> {
+    /// Start the data, writing its header
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(HEADER)?;
+        Ok(Self {
+            inner,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Write a row whose fields are written by the given function
+    ///
+    /// Fails if a field can't be written or if the function writes more
+    /// than 1600 fields, the maximal number of columns of a table.
+    pub fn write_row<F: FnOnce(&mut CopyRow)>(&mut self, write_fields: F) -> io::Result<()> {
+        self.buf.clear();
+        self.buf.extend_from_slice(&[0, 0]);
+        let mut row = CopyRow {
+            buf: &mut self.buf,
+            count: 0,
+            error: None,
+        };
+        write_fields(&mut row);
+        if let Some(e) = row.error {
+            return Err(e);
+        }
+        let count = row.count;
+        if count > 1600 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("too many fields in a COPY row: {count}"),
+            ));
+        }
+        self.buf[..2].copy_from_slice(&(count as i16).to_be_bytes());
+        self.inner.write_all(&self.buf)
+    }
+
+    /// Write a row made of an id
+    pub fn write_id<O: Identifiable>(&mut self, id: Id<O>) -> io::Result<()> {
+        self.write_row(|row| {
+            row.field(&id);
+        })
+    }
+
+    /// Write rows made of an id each
+    pub fn write_ids<O: Identifiable>(&mut self, ids: &[Id<O>]) -> io::Result<()> {
+        ids.iter().try_for_each(|&id| self.write_id(id))
+    }
+
+    /// Write a row made of the id then the fields of the entity
+    pub fn write_ided<T: Identifiable, E: CopyFields>(
+        &mut self,
+        ided: &Ided<T, E>,
+    ) -> io::Result<()> {
+        self.write_row(|row| {
+            row.field(&ided.id());
+            ided.entity().write_copy_fields(row);
+        })
+    }
+
+    /// End the data, returning the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.write_all(TRAILER)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::{
+            copy::{CopyFields, CopyRow, CopyValue, CopyWriter, HEADER},
+            *,
+        },
+        std::io,
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+        referrer: Option<Id<Customer>>,
+        credits: i32,
+    }
+
+    impl CopyFields for Customer {
+        fn write_copy_fields(&self, row: &mut CopyRow) {
+            row.field(&self.name)
+                .field(&self.referrer)
+                .field(&self.credits);
+        }
+    }
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_copy_ids() {
+        let id = Id::<Customer>::from_db_id(ID).unwrap();
+        let mut writer = CopyWriter::new(Vec::new()).unwrap();
+        writer.write_ids(&[id, id]).unwrap();
+        let data = writer.finish().unwrap();
+        let mut row = vec![0, 1, 0, 0, 0, 16];
+        row.extend_from_slice(id.uuid().as_bytes());
+        assert_eq!(data, [HEADER, &row, &row, &[0xff, 0xff]].concat());
+    }
+
+    #[test]
+    fn test_copy_ided() {
+        let id = Id::<Customer>::from_db_id(ID).unwrap();
+        let customer = Ided::new(
+            id,
+            Customer {
+                name: "Zoé".to_string(),
+                referrer: None,
+                credits: -2,
+            },
+        );
+        let mut writer = CopyWriter::new(Vec::new()).unwrap();
+        writer.write_ided(&customer).unwrap();
+        let data = writer.finish().unwrap();
+        let mut row = vec![0, 4, 0, 0, 0, 16];
+        row.extend_from_slice(id.uuid().as_bytes());
+        row.extend_from_slice(&[0, 0, 0, 4, b'Z', b'o', 0xc3, 0xa9]);
+        row.extend_from_slice(&[0xff; 4]);
+        row.extend_from_slice(&[0, 0, 0, 4, 0xff, 0xff, 0xff, 0xfe]);
+        assert_eq!(&data[HEADER.len()..], [&row[..], &[0xff, 0xff]].concat());
+    }
+
+    #[test]
+    fn test_too_many_fields() {
+        let mut writer = CopyWriter::new(Vec::new()).unwrap();
+        let error = writer
+            .write_row(|row| {
+                for _ in 0..2000 {
+                    row.field(&true);
+                }
+            })
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    struct Failing;
+
+    impl CopyValue for Failing {
+        fn write_copy_value(&self, _: &mut Vec<u8>) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::InvalidInput, "failing"))
+        }
+    }
+
+    #[test]
+    fn test_field_error() {
+        let mut writer = CopyWriter::new(Vec::new()).unwrap();
+        let error = writer
+            .write_row(|row| {
+                row.field(&1i32).field(&Failing).field(&2i32);
+            })
+            .unwrap_err();
+        assert_eq!(error.to_string(), "failing");
+        writer
+            .write_ids(&[Id::<Customer>::from_db_id(ID).unwrap()])
+            .unwrap();
+    }
+}
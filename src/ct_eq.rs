@@ -0,0 +1,53 @@
+use {
+    super::*,
+    subtle::{Choice, ConstantTimeEq},
+};
+
+/// Ids compared in constant time, when they act as bearer capabilities,
+/// eg in unsubscribe links, so that the time of a comparison with a
+/// secret id doesn't tell how many bytes match
+impl<O: Identifiable> ConstantTimeEq for Id<O> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.uuid.as_bytes().ct_eq(other.uuid.as_bytes())
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Compare the ids in constant time
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Unsub")]
+    /// pub struct Unsubscription {}
+    ///
+    /// let secret = Id::<Unsubscription>::random_v4();
+    /// let presented: Id<Unsubscription> = secret.public_id().parse().unwrap();
+    /// assert!(presented.ct_eq(&secret));
+    /// assert!(!Id::random_v4().ct_eq(&secret));
+    /// ```
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, subtle::ConstantTimeEq};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Tok")]
+    pub struct Token {}
+
+    #[test]
+    fn test_ct_eq() {
+        let id = Id::<Token>::random_v4();
+        let mut bytes = *id.uuid().as_bytes();
+        assert!(id.ct_eq(&Id::from(uuid::Uuid::from_bytes(bytes))));
+        bytes[15] ^= 1;
+        let other = Id::from(uuid::Uuid::from_bytes(bytes));
+        assert!(!id.ct_eq(&other));
+        assert_eq!(ConstantTimeEq::ct_eq(&id, &other).unwrap_u8(), 0);
+    }
+}
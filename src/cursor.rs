@@ -0,0 +1,251 @@
+use {
+    super::*,
+    base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine},
+    hmac::{Hmac, Mac},
+    serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer},
+    sha2::Sha256,
+    std::fmt,
+};
+
+/// An opaque keyset pagination cursor, holding the id of the last
+/// returned object, and optionally the value of the sort key.
+///
+/// The token is URL safe base64 and, when decoded, the class of the id
+/// is checked, so that a cursor of invoices can't be used to page
+/// customers:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// let cursor = Cursor::new(id, "2024-03-01".to_string());
+/// let token = cursor.encode();
+///
+/// let cursor: Cursor<Customer, String> = Cursor::decode(&token).unwrap();
+/// assert_eq!(cursor.id(), id);
+/// assert_eq!(cursor.key(), "2024-03-01");
+/// ```
+///
+/// Plain tokens can be forged by the client. When the cursor must not
+/// be altered, use `encode_signed` and `decode_signed`, which append a
+/// HMAC-SHA256 of the payload.
+///
+/// When serialized, a cursor is its (unsigned) token.
+#[derive(Debug, Clone)]
+pub struct Cursor<T: Identifiable, K = ()> {
+    id: Id<T>,
+    key: K,
+}
+
+/// An error returned when decoding an invalid cursor
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CursorError {
+    /// The token isn't a cursor
+    Malformed,
+    /// The id of the cursor isn't valid, or of another class
+    Id(IdError),
+    /// The signature is missing or doesn't match the payload
+    Signature,
+}
+
+impl fmt::Display for CursorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed cursor"),
+            Self::Id(e) => write!(f, "invalid cursor id: {e}"),
+            Self::Signature => write!(f, "invalid cursor signature"),
+        }
+    }
+}
+
+impl std::error::Error for CursorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Id(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Identifiable> From<Id<T>> for Cursor<T> {
+    fn from(id: Id<T>) -> Self {
+        Self { id, key: () }
+    }
+}
+
+impl<T: Identifiable, K> Cursor<T, K> {
+    /// Create a cursor from the id and the sort key of the last object
+    pub fn new(id: Id<T>, key: K) -> Self {
+        Self { id, key }
+    }
+
+    /// Return the id of the last object
+    pub fn id(&self) -> Id<T> {
+        self.id
+    }
+
+    /// Return a reference to the sort key
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Destructure the cursor into its id and sort key
+    pub fn into_parts(self) -> (Id<T>, K) {
+        (self.id, self.key)
+    }
+}
+
+impl<T: Identifiable, K: Serialize> Cursor<T, K> {
+    fn payload(&self) -> String {
+        let payload = serde_json::to_vec(&(self.id.public_id(), &self.key))
+            .expect("sort keys should be serializable as JSON");
+        URL_SAFE_NO_PAD.encode(payload)
+    }
+
+    /// Return the token of the cursor
+    pub fn encode(&self) -> String {
+        self.payload()
+    }
+
+    /// Return the token of the cursor, signed with the secret
+    pub fn encode_signed(&self, secret: &[u8]) -> String {
+        let payload = self.payload();
+        let signature = mac(secret, &payload).finalize().into_bytes();
+        format!("{payload}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+}
+
+impl<T: Identifiable, K: DeserializeOwned> Cursor<T, K> {
+    /// Decode a token, checking the class of the id
+    pub fn decode(token: &str) -> Result<Self, CursorError> {
+        let payload = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| CursorError::Malformed)?;
+        let (public_id, key): (String, K) =
+            serde_json::from_slice(&payload).map_err(|_| CursorError::Malformed)?;
+        let id = Id::from_public_id(&public_id).map_err(CursorError::Id)?;
+        Ok(Self { id, key })
+    }
+
+    /// Decode a token produced by `encode_signed` with the same secret,
+    /// checking the signature then the class of the id
+    pub fn decode_signed(token: &str, secret: &[u8]) -> Result<Self, CursorError> {
+        let (payload, signature) = token.split_once('.').ok_or(CursorError::Signature)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| CursorError::Signature)?;
+        mac(secret, payload)
+            .verify_slice(&signature)
+            .map_err(|_| CursorError::Signature)?;
+        Self::decode(payload)
+    }
+}
+
+fn mac(secret: &[u8], payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+impl<T: Identifiable, K: Serialize> Serialize for Cursor<T, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de, T: Identifiable, K: DeserializeOwned> Deserialize<'de> for Cursor<T, K> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        Self::decode(&token).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A cursor is documented as an opaque string
+#[cfg(feature = "openapi")]
+impl<'s, T: Identifiable, K> utoipa::ToSchema<'s> for Cursor<T, K> {
+    fn schema() -> (
+        &'s str,
+        utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+    ) {
+        let schema = utoipa::openapi::ObjectBuilder::new()
+            .schema_type(utoipa::openapi::SchemaType::String)
+            .description(Some("Opaque pagination cursor"));
+        ("Cursor", schema.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Inv")]
+    pub struct Invoice {}
+
+    const SECRET: &[u8] = b"secret";
+
+    #[test]
+    fn test_round_trip() {
+        let id = Id::<Customer>::random_v4();
+        let token = Cursor::from(id).encode();
+        assert!(!token.contains(&id.db_id()));
+        let cursor: Cursor<Customer> = Cursor::decode(&token).unwrap();
+        assert_eq!(cursor.id(), id);
+
+        let cursor = Cursor::new(id, (42, "John".to_string()));
+        let decoded: Cursor<Customer, (i64, String)> = Cursor::decode(&cursor.encode()).unwrap();
+        assert_eq!(decoded.into_parts(), (id, (42, "John".to_string())));
+
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, format!("\"{}\"", cursor.encode()));
+        let decoded: Cursor<Customer, (i64, String)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.key(), cursor.key());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let token = Cursor::from(Id::<Customer>::random_v4()).encode();
+        assert!(matches!(
+            Cursor::<Invoice>::decode(&token).unwrap_err(),
+            CursorError::Id(e) if e.kind() == IdErrorKind::WrongClass
+        ));
+        assert_eq!(
+            Cursor::<Customer>::decode("not a cursor").unwrap_err(),
+            CursorError::Malformed
+        );
+        assert_eq!(
+            Cursor::<Customer, i64>::decode(&token).unwrap_err(),
+            CursorError::Malformed
+        );
+    }
+
+    #[test]
+    fn test_signed() {
+        let cursor = Cursor::new(Id::<Customer>::random_v4(), 42);
+        let token = cursor.encode_signed(SECRET);
+        let decoded: Cursor<Customer, i64> = Cursor::decode_signed(&token, SECRET).unwrap();
+        assert_eq!(decoded.id(), cursor.id());
+        assert_eq!(
+            Cursor::<Customer, i64>::decode_signed(&token, b"other").unwrap_err(),
+            CursorError::Signature
+        );
+        let (_, signature) = token.split_once('.').unwrap();
+        let forged = format!("{}.{signature}", Cursor::new(cursor.id(), 43).encode());
+        assert_eq!(
+            Cursor::<Customer, i64>::decode_signed(&forged, SECRET).unwrap_err(),
+            CursorError::Signature
+        );
+        assert_eq!(
+            Cursor::<Customer, i64>::decode_signed(&cursor.encode(), SECRET).unwrap_err(),
+            CursorError::Signature
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use {
+    super::*,
+    diesel::{
+        backend::Backend,
+        deserialize::{self, FromSql, FromStaticSqlRow, Queryable},
+        serialize::{self, Output, ToSql},
+        sql_types,
+    },
+    uuid::Uuid,
+};
+
+/// An Id is written as the uuid, with no check nor conversion.
+///
+/// It can be used in diesel queries just like an `Uuid`:
+///
+/// ```ignore
+/// customer::table.filter(customer::id.eq(id))
+/// ```
+impl<O, DB> ToSql<sql_types::Uuid, DB> for Id<O>
+where
+    O: Identifiable,
+    DB: Backend,
+    Uuid: ToSql<sql_types::Uuid, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        self.uuid.to_sql(out)
+    }
+}
+
+impl<O, DB> FromSql<sql_types::Uuid, DB> for Id<O>
+where
+    O: Identifiable,
+    DB: Backend,
+    Uuid: FromSql<sql_types::Uuid, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        Uuid::from_sql(bytes).map(Id::unchecked)
+    }
+}
+
+/// An Ided is built from a row made of the id and of the columns
+/// of the entity, which are given as a nested tuple:
+///
+/// ```ignore
+/// let customers: Vec<Ided<Customer>> = customer::table
+///     .select((customer::id, (customer::name, customer::email)))
+///     .load(&mut conn)?;
+/// ```
+impl<T, E, ST, DB> Queryable<(sql_types::Uuid, ST), DB> for Ided<T, E>
+where
+    T: Identifiable,
+    DB: Backend,
+    (Id<T>, E): FromStaticSqlRow<(sql_types::Uuid, ST), DB>,
+{
+    type Row = (Id<T>, E);
+
+    fn build((id, entity): Self::Row) -> deserialize::Result<Self> {
+        Ok(Ided::new(id, entity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        diesel::{
+            deserialize::FromSqlRow, pg::Pg, sql_types, ExpressionMethods,
+            NullableExpressionMethods, QueryDsl, Queryable,
+        },
+    };
+
+    diesel::table! {
+        customer (id) {
+            id -> Uuid,
+            name -> Text,
+        }
+    }
+
+    // only checks the implementations are valid
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, Queryable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        pub name: String,
+    }
+
+    fn is_loadable<U: FromSqlRow<ST, Pg>, ST>() {}
+
+    #[test]
+    fn test_bind() {
+        let id = Id::<Customer>::random_v4();
+        let by_id = customer::table.filter(customer::id.eq(id));
+        // the binds are written with the Debug impl of the id
+        #[cfg(not(feature = "redact"))]
+        let bind = id.db_id();
+        #[cfg(feature = "redact")]
+        let bind = id.redacted().to_string();
+        let sql = diesel::debug_query::<Pg, _>(&by_id).to_string();
+        assert!(sql.contains(r#"WHERE ("customer"."id" = $1)"#));
+        assert!(sql.contains(&bind));
+        let optional = customer::table.filter(customer::id.nullable().eq(Some(id)));
+        let sql = diesel::debug_query::<Pg, _>(&optional).to_string();
+        assert!(sql.contains(&bind));
+    }
+
+    #[test]
+    fn test_queryable() {
+        is_loadable::<Id<Customer>, sql_types::Uuid>();
+        is_loadable::<Ided<Customer>, (sql_types::Uuid, (sql_types::Text,))>();
+    }
+}
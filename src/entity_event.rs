@@ -0,0 +1,365 @@
+use {
+    super::*,
+    chrono::{DateTime, Utc},
+    std::{fmt, str::FromStr},
+};
+
+/// The action of an [`EntityEvent`]
+///
+/// Actions are written in snake case (`created`, `updated`,
+/// `deleted`), other actions being kept as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EntityAction {
+    Created,
+    Updated,
+    Deleted,
+    /// Any other action, eg `archived` or `invoice_sent`
+    Custom(String),
+}
+
+impl EntityAction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Created => "created",
+            Self::Updated => "updated",
+            Self::Deleted => "deleted",
+            Self::Custom(action) => action,
+        }
+    }
+}
+
+impl fmt::Display for EntityAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for EntityAction {
+    fn from(action: &str) -> Self {
+        match action {
+            "created" => Self::Created,
+            "updated" => Self::Updated,
+            "deleted" => Self::Deleted,
+            _ => Self::Custom(action.to_string()),
+        }
+    }
+}
+
+impl FromStr for EntityAction {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+/// The envelope of an audit event about an identified entity: who did
+/// what to which object and when, with a payload giving the details
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="User")]
+/// pub struct User {}
+///
+/// let user = Id::<User>::random_v4();
+/// let customer = Id::<Customer>::random_v4();
+/// let event = EntityEvent::new(customer, EntityAction::Deleted, user, ());
+/// assert_eq!(event.class().prefix(), "Cust");
+/// assert!(event.actor().is::<User>());
+/// ```
+///
+/// When serialized, the event is an object with the `id`, `class`,
+/// `action`, `actor`, `at` and `payload` keys, the class being checked
+/// when deserialized. With sqlx, it can be read from the same columns,
+/// `actor` being a TEXT column holding a public id and `payload` a JSON
+/// or JSONB column.
+pub struct EntityEvent<O: Identifiable, P = ()> {
+    id: Id<O>,
+    action: EntityAction,
+    actor: AnyId,
+    at: DateTime<Utc>,
+    payload: P,
+}
+
+impl<O: Identifiable, P> EntityEvent<O, P> {
+    /// Create an event happening now
+    pub fn new(id: Id<O>, action: EntityAction, actor: impl Into<AnyId>, payload: P) -> Self {
+        Self {
+            id,
+            action,
+            actor: actor.into(),
+            at: Utc::now(),
+            payload,
+        }
+    }
+    /// Set the time of the event
+    #[must_use]
+    pub fn with_at(mut self, at: DateTime<Utc>) -> Self {
+        self.at = at;
+        self
+    }
+    /// Return the id of the entity
+    pub fn id(&self) -> Id<O> {
+        self.id
+    }
+    /// Return the class of the entity
+    pub fn class(&self) -> IdClass {
+        O::class()
+    }
+    pub fn action(&self) -> &EntityAction {
+        &self.action
+    }
+    /// Return the id of whoever (user, API key, service...) did the action
+    pub fn actor(&self) -> AnyId {
+        self.actor
+    }
+    /// Return the time of the event
+    pub fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn into_payload(self) -> P {
+        self.payload
+    }
+}
+
+impl<O: Identifiable, P: fmt::Debug> fmt::Debug for EntityEvent<O, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntityEvent")
+            .field("id", &self.id)
+            .field("action", &self.action)
+            .field("actor", &self.actor)
+            .field("at", &self.at)
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+impl<O: Identifiable, P: Clone> Clone for EntityEvent<O, P> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            action: self.action.clone(),
+            actor: self.actor,
+            at: self.at,
+            payload: self.payload.clone(),
+        }
+    }
+}
+
+impl<O: Identifiable, P: PartialEq> PartialEq for EntityEvent<O, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.action == other.action
+            && self.actor == other.actor
+            && self.at == other.at
+            && self.payload == other.payload
+    }
+}
+
+/// The error of an event whose class isn't the class of its id type
+fn wrong_class<O: Identifiable>(class: &str) -> IdError {
+    IdError::new(IdErrorKind::WrongClass)
+        .with_input(class)
+        .with_expected(O::class())
+        .with_position(0)
+}
+
+mod serde_impls {
+    use {
+        super::*,
+        serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer},
+    };
+
+    impl Serialize for EntityAction {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(self.as_str())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for EntityAction {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let action = String::deserialize(deserializer)?;
+            Ok(Self::from(action.as_str()))
+        }
+    }
+
+    impl<O: Identifiable, P: Serialize> Serialize for EntityEvent<O, P> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut event = serializer.serialize_struct("EntityEvent", 6)?;
+            event.serialize_field("id", &self.id)?;
+            event.serialize_field("class", O::class().prefix())?;
+            event.serialize_field("action", &self.action)?;
+            event.serialize_field("actor", &self.actor)?;
+            event.serialize_field("at", &self.at)?;
+            event.serialize_field("payload", &self.payload)?;
+            event.end()
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(bound(deserialize = "P: Deserialize<'de>"))]
+    struct RawEvent<O: Identifiable, P> {
+        id: Id<O>,
+        class: String,
+        action: EntityAction,
+        actor: AnyId,
+        at: DateTime<Utc>,
+        payload: P,
+    }
+
+    impl<'de, O: Identifiable, P: Deserialize<'de>> Deserialize<'de> for EntityEvent<O, P> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = RawEvent::<O, P>::deserialize(deserializer)?;
+            if !raw.class.eq_ignore_ascii_case(O::class().prefix()) {
+                return Err(de::Error::custom(wrong_class::<O>(&raw.class)));
+            }
+            Ok(Self {
+                id: raw.id,
+                action: raw.action,
+                actor: raw.actor,
+                at: raw.at,
+                payload: raw.payload,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+mod sqlx_impls {
+    use {
+        super::*,
+        sqlx::{
+            encode::IsNull,
+            error::BoxDynError,
+            postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
+            Decode, Encode, Postgres, Type,
+        },
+    };
+
+    /// An action is stored in a TEXT column
+    impl Type<Postgres> for EntityAction {
+        fn type_info() -> PgTypeInfo {
+            <String as Type<Postgres>>::type_info()
+        }
+        fn compatible(ty: &PgTypeInfo) -> bool {
+            <String as Type<Postgres>>::compatible(ty)
+        }
+    }
+
+    impl PgHasArrayType for EntityAction {
+        fn array_type_info() -> PgTypeInfo {
+            <String as PgHasArrayType>::array_type_info()
+        }
+    }
+
+    impl Encode<'_, Postgres> for EntityAction {
+        fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+            <&str as Encode<'_, Postgres>>::encode(self.as_str(), buf)
+        }
+    }
+
+    impl Decode<'_, Postgres> for EntityAction {
+        fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+            Ok(Self::from(<&str as Decode<'_, Postgres>>::decode(value)?))
+        }
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, O, P> sqlx::FromRow<'r, sqlx::postgres::PgRow> for EntityEvent<O, P>
+where
+    O: Identifiable,
+    P: serde::Deserialize<'r> + 'r,
+{
+    fn from_row(row: &'r sqlx::postgres::PgRow) -> Result<Self, sqlx::Error> {
+        use sqlx::{types::Json, Row};
+        let class: &str = row.try_get("class")?;
+        if !class.eq_ignore_ascii_case(O::class().prefix()) {
+            return Err(sqlx::Error::ColumnDecode {
+                index: "class".to_string(),
+                source: Box::new(wrong_class::<O>(class)),
+            });
+        }
+        Ok(Self {
+            id: row.try_get("id")?,
+            action: row.try_get("action")?,
+            actor: row.try_get("actor")?,
+            at: row.try_get("at")?,
+            payload: row.try_get::<Json<P>, _>("payload")?.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "EvCust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "EvUser")]
+    pub struct User {}
+
+    #[test]
+    fn test_entity_event() {
+        let user = Id::<User>::random_v4();
+        let event = EntityEvent::new(
+            Id::<Customer>::random_v4(),
+            EntityAction::from("archived"),
+            user,
+            "payload",
+        );
+        assert_eq!(event.class(), Customer::class());
+        assert_eq!(
+            event.action(),
+            &EntityAction::Custom("archived".to_string())
+        );
+        assert_eq!(event.actor().downcast::<User>().unwrap(), user);
+        assert_eq!(
+            "deleted".parse::<EntityAction>().unwrap(),
+            EntityAction::Deleted
+        );
+    }
+
+    #[test]
+    fn test_entity_event_serde() {
+        const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+        let event = EntityEvent::new(
+            Id::<Customer>::from_db_id(ID).unwrap(),
+            EntityAction::Updated,
+            Id::<User>::from_db_id(ID).unwrap(),
+            serde_json::json!({ "name": "John" }),
+        )
+        .with_at("2024-03-01T12:00:00Z".parse().unwrap());
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": format!("EvCust_{ID}"),
+                "class": "EvCust",
+                "action": "updated",
+                "actor": format!("EvUser_{ID}"),
+                "at": "2024-03-01T12:00:00Z",
+                "payload": { "name": "John" },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<EntityEvent<Customer, serde_json::Value>>(json.clone())
+                .unwrap(),
+            event
+        );
+        let mut json = json;
+        json["class"] = "EvUser".into();
+        assert!(serde_json::from_value::<EntityEvent<Customer, serde_json::Value>>(json).is_err());
+    }
+}
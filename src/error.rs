@@ -1,21 +1,269 @@
-use std::fmt;
+use {crate::IdClass, std::fmt};
 
-/// An error which can be returned by the kind library
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-pub enum IdError {
+/// The maximal length, in bytes, of the input kept in an [`IdError`]
+const MAX_INPUT_LEN: usize = 64;
+
+/// The kind of an [`IdError`]
+///
+/// New kinds may be added in minor versions. Downstream code matching
+/// the kinds exhaustively can rely on this table, which also gives the
+/// mapping of the kinds to the codes and statuses of kind:
+///
+/// | Kind            | Since      | `as_str()`       | [`IdError::code`]                         | HTTP status |
+/// |-----------------|------------|------------------|-------------------------------------------|-------------|
+/// | `WrongClass`    | 1.0        | `wrong_class`    | `KIND_WRONG_CLASS`                        | 404 or 422 ([`WrongClassStatus`]) |
+/// | `InvalidFormat` | 1.0        | `invalid_format` | `KIND_BAD_UUID` or `KIND_INVALID_FORMAT`  | 422 |
+/// | `EmptyDbId`     | 1.0, deprecated, never produced | `invalid_format` | `KIND_EMPTY_DB_ID` | 422 |
+/// | `TooLong`       | unreleased | `too_long`       | `KIND_TOO_LONG`                           | 422 |
+/// | `UnknownPrefix` | unreleased | `unknown_prefix` | `KIND_UNKNOWN_PREFIX`                     | 404 or 422 ([`WrongClassStatus`]) |
+///
+/// Public ids having no checksum, there's no kind for bad checksums.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum IdErrorKind {
+    /// The prefix isn't the one of the expected class
     WrongClass,
+    /// The input isn't a valid id
     InvalidFormat,
+    #[deprecated(note = "never produced by kind")]
     EmptyDbId,
+    /// The input is longer than any valid id, when parsing untrusted
+    /// input with [`Id::parse_any_untrusted`](crate::Id::parse_any_untrusted)
+    TooLong,
+    /// The prefix isn't the one of any of the expected classes, eg
+    /// when parsing an enumeration declared with `id_enum!`
+    UnknownPrefix,
+}
+
+#[allow(deprecated)]
+impl IdErrorKind {
+    /// All the kinds
+    pub const ALL: &'static [Self] = &[
+        Self::WrongClass,
+        Self::InvalidFormat,
+        Self::EmptyDbId,
+        Self::TooLong,
+        Self::UnknownPrefix,
+    ];
+    /// A short snake case name of the kind, used in the JSON bodies of
+    /// the web framework integrations, eg `wrong_class`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WrongClass => "wrong_class",
+            Self::InvalidFormat | Self::EmptyDbId => "invalid_format",
+            Self::TooLong => "too_long",
+            Self::UnknownPrefix => "unknown_prefix",
+        }
+    }
 }
 
-impl fmt::Display for IdError {
+#[allow(deprecated)]
+impl fmt::Display for IdErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::WrongClass => write!(f, "wrong object class"),
             Self::InvalidFormat => write!(f, "invalid format for id"),
             Self::EmptyDbId => write!(f, "empty db id"),
+            Self::TooLong => write!(f, "id too long"),
+            Self::UnknownPrefix => write!(f, "unknown id prefix"),
+        }
+    }
+}
+
+/// An error which can be returned by the kind library
+///
+/// Besides its [kind](IdErrorKind), the error holds, when known, the
+/// offending input (truncated to 64 bytes), the expected class, and the
+/// position in the input, in bytes, where the problem was detected:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let error = Id::<Customer>::from_public_id("Cont_371c35ec").unwrap_err();
+/// assert_eq!(error.kind(), IdErrorKind::WrongClass);
+/// assert_eq!(error.input(), Some("Cont_371c35ec"));
+/// assert_eq!(error.expected().unwrap().prefix(), "Cust");
+/// assert_eq!(error.position(), Some(1));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+#[error("{kind}")]
+pub struct IdError {
+    kind: IdErrorKind,
+    pub(crate) input: Option<String>,
+    expected: Option<IdClass>,
+    position: Option<usize>,
+    // boxed, as most errors have no source, to keep the error small
+    #[source]
+    source: Option<Box<uuid::Error>>,
+}
+
+impl IdError {
+    /// Create an error without context
+    pub fn new(kind: IdErrorKind) -> Self {
+        Self {
+            kind,
+            input: None,
+            expected: None,
+            position: None,
+            source: None,
+        }
+    }
+    /// Set the offending input, truncated to 64 bytes
+    #[must_use]
+    pub fn with_input(mut self, input: &str) -> Self {
+        let mut len = input.len().min(MAX_INPUT_LEN);
+        while !input.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.input = Some(input[..len].to_string());
+        self
+    }
+    /// Set the expected class
+    #[must_use]
+    pub fn with_expected(mut self, class: IdClass) -> Self {
+        self.expected = Some(class);
+        self
+    }
+    /// Set the position, in bytes, of the error in the input
+    #[must_use]
+    pub fn with_position(mut self, position: usize) -> Self {
+        self.position = Some(position);
+        self
+    }
+    #[must_use]
+    pub(crate) fn with_source(mut self, source: uuid::Error) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+    pub fn kind(&self) -> IdErrorKind {
+        self.kind
+    }
+    /// The offending input, truncated to 64 bytes
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+    /// The class of the id which was expected
+    pub fn expected(&self) -> Option<IdClass> {
+        self.expected
+    }
+    /// The position, in bytes, of the error in the input
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+    /// A stable code of the error, for machine readable payloads, eg
+    /// `KIND_WRONG_CLASS`, or `KIND_BAD_UUID` when the prefix is right
+    /// but not the UUID (see [`IdErrorKind`] for all of them)
+    #[allow(deprecated)]
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            IdErrorKind::WrongClass => "KIND_WRONG_CLASS",
+            IdErrorKind::InvalidFormat if self.source.is_some() => "KIND_BAD_UUID",
+            IdErrorKind::InvalidFormat => "KIND_INVALID_FORMAT",
+            IdErrorKind::EmptyDbId => "KIND_EMPTY_DB_ID",
+            IdErrorKind::TooLong => "KIND_TOO_LONG",
+            IdErrorKind::UnknownPrefix => "KIND_UNKNOWN_PREFIX",
+        }
+    }
+    /// The default status code of the HTTP responses to this error: 404
+    /// for an id of another class and 422 for an invalid id
+    pub fn http_status(&self) -> u16 {
+        self.http_status_with(WrongClassStatus::default())
+    }
+    /// The status code of the HTTP responses to this error, with the given
+    /// status for an id of another class
+    pub fn http_status_with(&self, wrong_class: WrongClassStatus) -> u16 {
+        match self.kind {
+            IdErrorKind::WrongClass | IdErrorKind::UnknownPrefix => wrong_class.code(),
+            _ => 422,
+        }
+    }
+}
+
+/// The status of the HTTP responses to ids of another class than the
+/// expected one
+///
+/// It's 404 by default, as no object of the expected class can have
+/// this id, which also avoids telling clients that the id is the one of
+/// an existing object of another class. Routers preferring to reject
+/// such ids like other invalid ids can change it for their extractors,
+/// eg with axum:
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/customers/{id}", get(get_customer))
+///     .layer(Extension(WrongClassStatus::UnprocessableEntity));
+/// ```
+///
+/// and with actix, with the `wrong_class` of an `IdErrorConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WrongClassStatus {
+    /// 404 Not Found
+    #[default]
+    NotFound,
+    /// 422 Unprocessable Entity, like other invalid ids
+    UnprocessableEntity,
+}
+
+impl WrongClassStatus {
+    pub fn code(self) -> u16 {
+        match self {
+            Self::NotFound => 404,
+            Self::UnprocessableEntity => 422,
         }
     }
 }
 
-impl std::error::Error for IdError {}
+impl From<IdErrorKind> for IdError {
+    fn from(kind: IdErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+#[test]
+fn test_truncated_input() {
+    let input = "é".repeat(40);
+    let error = IdError::new(IdErrorKind::InvalidFormat).with_input(&input);
+    assert_eq!(error.input().unwrap().len(), 64);
+    assert_eq!(error.to_string(), "invalid format for id");
+}
+
+#[test]
+fn test_error_context() {
+    use {crate::*, std::error::Error};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    let error = Id::<Customer>::from_public_id("Cust_371c35ec-zz").unwrap_err();
+    assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+    assert_eq!(error.position(), Some(5));
+    assert!(error.source().is_some());
+    let error = Id::<Customer>::from_public_id("Cust371c35ec").unwrap_err();
+    assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+    assert_eq!(error.position(), Some(4));
+    assert!(error.source().is_none());
+    assert_eq!(error.http_status(), 422);
+    let error = Id::<Customer>::from_public_id("Cont_371c35ec").unwrap_err();
+    assert_eq!(error.http_status(), 404);
+    assert_eq!(
+        error.http_status_with(WrongClassStatus::UnprocessableEntity),
+        422
+    );
+}
+
+#[test]
+fn test_error_kinds() {
+    for kind in IdErrorKind::ALL {
+        let error = IdError::new(*kind);
+        assert!(error.code().starts_with("KIND_"));
+        assert!(!kind.as_str().is_empty());
+        assert!([404, 422].contains(&error.http_status()));
+    }
+    assert_eq!(IdErrorKind::UnknownPrefix.as_str(), "unknown_prefix");
+    assert_eq!(IdError::new(IdErrorKind::TooLong).code(), "KIND_TOO_LONG");
+}
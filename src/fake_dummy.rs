@@ -0,0 +1,58 @@
+use {
+    super::*,
+    fake::{Dummy, Fake, Faker, Rng},
+    uuid::Builder,
+};
+
+/// Fake ids are random (v4) ids
+///
+/// ```
+/// use {fake::{Fake, Faker}, kind::*};
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let id: Id<Customer> = Faker.fake();
+/// assert_eq!(id.uuid().get_version_num(), 4);
+/// ```
+impl<O: Identifiable> Dummy<Faker> for Id<O> {
+    fn dummy_with_rng<R: Rng + ?Sized>(_: &Faker, rng: &mut R) -> Self {
+        let mut bytes = [0; 16];
+        rng.fill_bytes(&mut bytes);
+        Id::unchecked(Builder::from_random_bytes(bytes).into_uuid())
+    }
+}
+
+/// A fake Ided is a fake entity with a fake id, eg
+/// `let customer: Ided<Customer> = Faker.fake();` when `Customer`
+/// derives `Dummy`
+impl<T: Identifiable, E: Dummy<Faker>> Dummy<Faker> for Ided<T, E> {
+    fn dummy_with_rng<R: Rng + ?Sized>(config: &Faker, rng: &mut R) -> Self {
+        Ided::new(config.fake_with_rng(rng), config.fake_with_rng(rng))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        fake::{Fake, Faker},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_dummy() {
+        let a: Id<Customer> = Faker.fake();
+        let b: Id<Customer> = Faker.fake();
+        assert_ne!(a, b);
+        assert_eq!(a.uuid().get_version_num(), 4);
+        let ided: Ided<Customer, (String, u8)> = Faker.fake();
+        assert_eq!(ided.id().class().prefix(), "Cust");
+        let ideds: Vec<Ided<Customer, String>> = (Faker, 3..4).fake();
+        assert_eq!(ideds.len(), 3);
+    }
+}
@@ -0,0 +1,238 @@
+//! A C interface to the parsing and formatting of public ids, behind the
+//! `ffi` feature, eg for gateways written in C which must validate ids
+//! before forwarding requests.
+//!
+//! The declarations are in `include/kind.h`, generated with cbindgen
+//! (`cbindgen --config cbindgen.toml --output include/kind.h`):
+//!
+//! ```c
+//! uint8_t uuid[16];
+//! KindError error = kind_parse_public_id(input, "Cust", uuid);
+//! if (error != KIND_ERROR_OK) {
+//!     return reject(kind_error_code(error));
+//! }
+//! ```
+//!
+//! Classes are given by their prefix. Inputs are parsed as untrusted, so
+//! that long inputs are rejected without being read entirely.
+
+use {
+    super::*,
+    std::{
+        ffi::{c_char, CStr},
+        slice,
+    },
+    uuid::Uuid,
+};
+
+/// The result of the functions of the C interface
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KindError {
+    Ok = 0,
+    /// The prefix isn't the one of the expected class
+    WrongClass,
+    /// The input isn't a valid id
+    InvalidFormat,
+    /// The prefix is valid, but not the UUID
+    BadUuid,
+    /// The input is longer than any valid id
+    TooLong,
+    /// A pointer argument is null
+    NullPointer,
+    /// A string argument isn't valid UTF-8
+    InvalidUtf8,
+    /// The given prefix isn't a valid class prefix (`[a-zA-Z0-9]+`)
+    InvalidClass,
+    /// The output buffer is too small
+    BufferTooSmall,
+}
+
+impl From<IdError> for KindError {
+    #[allow(deprecated)]
+    fn from(error: IdError) -> Self {
+        match error.kind() {
+            IdErrorKind::WrongClass | IdErrorKind::UnknownPrefix => Self::WrongClass,
+            IdErrorKind::TooLong => Self::TooLong,
+            IdErrorKind::InvalidFormat if error.code() == "KIND_BAD_UUID" => Self::BadUuid,
+            _ => Self::InvalidFormat,
+        }
+    }
+}
+
+impl KindError {
+    /// The code of the error, eg `KIND_WRONG_CLASS`, as given by
+    /// [`IdError::code`]
+    pub fn code(self) -> &'static CStr {
+        let code: &'static [u8] = match self {
+            Self::Ok => b"KIND_OK\0",
+            Self::WrongClass => b"KIND_WRONG_CLASS\0",
+            Self::InvalidFormat => b"KIND_INVALID_FORMAT\0",
+            Self::BadUuid => b"KIND_BAD_UUID\0",
+            Self::TooLong => b"KIND_TOO_LONG\0",
+            Self::NullPointer => b"KIND_NULL_POINTER\0",
+            Self::InvalidUtf8 => b"KIND_INVALID_UTF8\0",
+            Self::InvalidClass => b"KIND_INVALID_CLASS\0",
+            Self::BufferTooSmall => b"KIND_BUFFER_TOO_SMALL\0",
+        };
+        CStr::from_bytes_with_nul(code).expect("codes are NUL terminated")
+    }
+}
+
+/// Read a string argument
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL terminated string
+unsafe fn str_arg<'a>(s: *const c_char) -> Result<&'a str, KindError> {
+    if s.is_null() {
+        return Err(KindError::NullPointer);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| KindError::InvalidUtf8)
+}
+
+/// Read a class argument
+///
+/// # Safety
+///
+/// The pointer must be null or point to a NUL terminated string
+unsafe fn class_arg(class: *const c_char) -> Result<IdClass, KindError> {
+    IdClass::interned(str_arg(class)?).map_err(|_| KindError::InvalidClass)
+}
+
+fn status(result: Result<(), KindError>) -> KindError {
+    result.err().unwrap_or(KindError::Ok)
+}
+
+/// Parse a public id of the class with the given prefix, writing the 16
+/// bytes of its UUID in `uuid_out`
+///
+/// # Safety
+///
+/// `public_id` and `class` must be null or point to NUL terminated
+/// strings, and `uuid_out` must be null or point to 16 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kind_parse_public_id(
+    public_id: *const c_char,
+    class: *const c_char,
+    uuid_out: *mut u8,
+) -> KindError {
+    status((|| {
+        let class = class_arg(class)?;
+        let public_id = str_arg(public_id)?;
+        if uuid_out.is_null() {
+            return Err(KindError::NullPointer);
+        }
+        let uuid = class.parse_untrusted(public_id)?;
+        slice::from_raw_parts_mut(uuid_out, 16).copy_from_slice(uuid.as_bytes());
+        Ok(())
+    })())
+}
+
+/// Write the NUL terminated public id of the class with the given prefix
+/// and of the 16 bytes UUID in `buffer`, which must hold at least the
+/// length of the prefix plus 38 bytes
+///
+/// # Safety
+///
+/// `class` must be null or point to a NUL terminated string, `uuid`
+/// must be null or point to 16 bytes, and `buffer` must be null or point
+/// to `buffer_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kind_format_public_id(
+    class: *const c_char,
+    uuid: *const u8,
+    buffer: *mut c_char,
+    buffer_len: usize,
+) -> KindError {
+    status((|| {
+        let class = class_arg(class)?;
+        if uuid.is_null() || buffer.is_null() {
+            return Err(KindError::NullPointer);
+        }
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(slice::from_raw_parts(uuid, 16));
+        let len = class.public_id_len();
+        if buffer_len <= len {
+            return Err(KindError::BufferTooSmall);
+        }
+        let buffer = slice::from_raw_parts_mut(buffer.cast::<u8>(), buffer_len);
+        class.public_id_buf(&Uuid::from_bytes(bytes), buffer);
+        buffer[len] = 0;
+        Ok(())
+    })())
+}
+
+/// Return the NUL terminated code of an error, eg `KIND_WRONG_CLASS`
+#[no_mangle]
+pub extern "C" fn kind_error_code(error: KindError) -> *const c_char {
+    error.code().as_ptr()
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::ffi::*,
+        std::ffi::{CStr, CString},
+    };
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    fn parse(public_id: &str, class: &str) -> Result<[u8; 16], KindError> {
+        let public_id = CString::new(public_id).unwrap();
+        let class = CString::new(class).unwrap();
+        let mut uuid = [0; 16];
+        match unsafe { kind_parse_public_id(public_id.as_ptr(), class.as_ptr(), uuid.as_mut_ptr()) }
+        {
+            KindError::Ok => Ok(uuid),
+            error => Err(error),
+        }
+    }
+
+    #[test]
+    fn test_parse_public_id() {
+        let uuid = parse(&format!("cust_{ID}"), "Cust").unwrap();
+        assert_eq!(uuid::Uuid::from_bytes(uuid).to_string(), ID);
+        assert_eq!(
+            parse(&format!("Cont_{ID}"), "Cust"),
+            Err(KindError::WrongClass)
+        );
+        assert_eq!(parse("Cust_371c35ec", "Cust"), Err(KindError::BadUuid));
+        assert_eq!(parse(ID, "Cust"), Err(KindError::WrongClass));
+        assert_eq!(parse(&"Cust_".repeat(100), "Cust"), Err(KindError::TooLong));
+        assert_eq!(
+            parse(&format!("Cust_{ID}"), "Cu st"),
+            Err(KindError::InvalidClass)
+        );
+        let class = CString::new("Cust").unwrap();
+        let error =
+            unsafe { kind_parse_public_id(std::ptr::null(), class.as_ptr(), [0; 16].as_mut_ptr()) };
+        assert_eq!(error, KindError::NullPointer);
+        let code = unsafe { CStr::from_ptr(kind_error_code(error)) };
+        assert_eq!(code.to_str().unwrap(), "KIND_NULL_POINTER");
+    }
+
+    #[test]
+    fn test_format_public_id() {
+        let class = CString::new("Cust").unwrap();
+        let uuid = *uuid::Uuid::parse_str(ID).unwrap().as_bytes();
+        let mut buffer = [1 as std::ffi::c_char; 42];
+        let error = unsafe {
+            kind_format_public_id(
+                class.as_ptr(),
+                uuid.as_ptr(),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            )
+        };
+        assert_eq!(error, KindError::Ok);
+        let public_id = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+        assert_eq!(public_id.to_str().unwrap(), format!("Cust_{ID}"));
+        let error = unsafe {
+            kind_format_public_id(class.as_ptr(), uuid.as_ptr(), buffer.as_mut_ptr(), 41)
+        };
+        assert_eq!(error, KindError::BufferTooSmall);
+    }
+}
@@ -0,0 +1,254 @@
+use {
+    super::*,
+    async_graphql::{
+        parser::types::Field,
+        registry::{Deprecation, MetaField, MetaType, Registry},
+        resolver_utils::resolve_container,
+        ContainerType, Context, ContextSelectionSet, InputType, InputValueError, InputValueResult,
+        ObjectType, OutputType, Positioned, ScalarType, ServerResult, Value, ID,
+    },
+    std::borrow::Cow,
+};
+
+/// An Id is the GraphQL `ID` scalar, holding the public id.
+///
+/// Input values are checked: an id of another class, or an invalid id,
+/// is a field error, so resolvers can take `Id<Customer>` arguments
+/// directly:
+///
+/// ```ignore
+/// #[Object]
+/// impl Query {
+///     async fn customer(&self, id: Id<Customer>) -> Option<Ided<Customer>> {
+///         // ...
+///     }
+/// }
+/// ```
+impl<O: Identifiable + Send + Sync> ScalarType for Id<O> {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match value {
+            Value::String(s) => Id::from_public_id(&s).map_err(InputValueError::custom),
+            _ => Err(InputValueError::expected_type(value)),
+        }
+    }
+
+    fn is_valid(value: &Value) -> bool {
+        matches!(value, Value::String(_))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.public_id())
+    }
+}
+
+impl<O: Identifiable + Send + Sync> InputType for Id<O> {
+    type RawValueType = Self;
+
+    fn type_name() -> Cow<'static, str> {
+        <ID as InputType>::type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        <ID as InputType>::create_type_info(registry)
+    }
+
+    fn parse(value: Option<Value>) -> InputValueResult<Self> {
+        <Self as ScalarType>::parse(value.unwrap_or_default())
+    }
+
+    fn to_value(&self) -> Value {
+        <Self as ScalarType>::to_value(self)
+    }
+
+    fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+        Some(self)
+    }
+}
+
+impl<O: Identifiable + Send + Sync> OutputType for Id<O> {
+    fn type_name() -> Cow<'static, str> {
+        <ID as OutputType>::type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        <ID as OutputType>::create_type_info(registry)
+    }
+
+    async fn resolve(
+        &self,
+        _: &ContextSelectionSet<'_>,
+        _: &Positioned<Field>,
+    ) -> ServerResult<Value> {
+        Ok(<Self as ScalarType>::to_value(self))
+    }
+}
+
+/// An Ided is the object type of its entity, with an additional `ID!`
+/// field named like the id field of the class (`id` by default).
+///
+/// The entity type is thus expected to be exposed only wrapped in Ided,
+/// as the schema has a single object type for both.
+impl<T, E> ContainerType for Ided<T, E>
+where
+    T: Identifiable + Send + Sync,
+    E: ObjectType,
+{
+    async fn resolve_field(&self, ctx: &Context<'_>) -> ServerResult<Option<Value>> {
+        if ctx.item.node.name.node == T::ID_FIELD {
+            return Ok(Some(<Id<T> as ScalarType>::to_value(&self.id())));
+        }
+        self.entity().resolve_field(ctx).await
+    }
+}
+
+impl<T, E> OutputType for Ided<T, E>
+where
+    T: Identifiable + Send + Sync,
+    E: ObjectType,
+{
+    fn type_name() -> Cow<'static, str> {
+        E::type_name()
+    }
+
+    fn create_type_info(registry: &mut Registry) -> String {
+        let ty = <ID as OutputType>::create_type_info(registry);
+        let name = E::create_type_info(registry);
+        if let Some(MetaType::Object { fields, .. }) = registry.types.get_mut(&*E::type_name()) {
+            if !fields.contains_key(T::ID_FIELD) {
+                fields.shift_insert(0, T::ID_FIELD.to_string(), id_field(T::ID_FIELD, ty));
+            }
+        }
+        name
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ContextSelectionSet<'_>,
+        _: &Positioned<Field>,
+    ) -> ServerResult<Value> {
+        resolve_container(ctx, self).await
+    }
+}
+
+impl<T, E> ObjectType for Ided<T, E>
+where
+    T: Identifiable + Send + Sync,
+    E: ObjectType,
+{
+}
+
+fn id_field(name: &str, ty: String) -> MetaField {
+    MetaField {
+        name: name.to_string(),
+        description: None,
+        args: Default::default(),
+        ty,
+        deprecation: Deprecation::NoDeprecated,
+        cache_control: Default::default(),
+        external: false,
+        requires: None,
+        provides: None,
+        visible: None,
+        shareable: false,
+        inaccessible: false,
+        tags: Vec::new(),
+        override_from: None,
+        compute_complexity: None,
+        directive_invocations: Vec::new(),
+        requires_scopes: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject},
+        serde_json::json,
+    };
+
+    #[derive(Debug, Identifiable, SimpleObject)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[cfg(feature = "serde")]
+    id_enum! { PartyId: Customer, Contract }
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    struct Query;
+
+    #[Object]
+    impl Query {
+        async fn customer(&self, id: Id<Customer>) -> Ided<Customer> {
+            Ided::new(
+                id,
+                Customer {
+                    name: "John".to_string(),
+                },
+            )
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    struct PartyQuery;
+
+    #[cfg(feature = "serde")]
+    #[Object]
+    impl PartyQuery {
+        async fn party(&self, id: PartyId) -> PartyId {
+            id
+        }
+    }
+
+    fn execute(query: &str) -> async_graphql::Response {
+        let schema = Schema::new(Query, EmptyMutation, EmptySubscription);
+        async_std::task::block_on(schema.execute(query))
+    }
+
+    #[test]
+    fn test_id_scalar() {
+        let response = execute(&format!(r#"{{ customer(id: "Cust_{ID}") {{ id name }} }}"#));
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({
+                "customer": { "id": format!("Cust_{ID}"), "name": "John" },
+            })
+        );
+
+        let response = execute(&format!(r#"{{ customer(id: "Cont_{ID}") {{ name }} }}"#));
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("wrong object class"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_id_enum_scalar() {
+        let schema = Schema::new(PartyQuery, EmptyMutation, EmptySubscription);
+        let execute = |query: &str| async_std::task::block_on(schema.execute(query));
+        let response = execute(&format!(r#"{{ party(id: "Cont_{ID}") }}"#));
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({ "party": format!("Cont_{ID}") })
+        );
+        let response = execute(r#"{ party(id: "Inv_123") }"#);
+        assert_eq!(response.errors.len(), 1);
+        assert!(schema.sdl().contains("party(id: ID!): ID!"));
+    }
+
+    #[test]
+    fn test_sdl() {
+        let sdl = Schema::new(Query, EmptyMutation, EmptySubscription).sdl();
+        assert!(sdl.contains("customer(id: ID!): Customer!"));
+        assert!(sdl.contains("type Customer {\n\tid: ID!\n\tname: String!\n}"));
+    }
+}
@@ -19,8 +19,24 @@ use {
 /// The Display implementation provides the public id, which
 /// should be generally used, while the db id should be used
 /// only for communication with the database.
+///
+/// An Id has the same layout than its Uuid, so that slices of ids can be
+/// seen as slices of uuids, see [`ids_as_uuids`].
+///
+/// There's deliberately no `Default` implementation: use [`OrNil`] for
+/// schemas where the nil uuid means "unset".
+#[repr(transparent)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[cfg_attr(
+    feature = "diesel",
+    derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow),
+    diesel(sql_type = diesel::sql_types::Uuid)
+)]
 pub struct Id<O: Identifiable> {
-    uuid: Uuid,
+    pub(crate) uuid: Uuid,
     phantom: PhantomData<O>,
 }
 
@@ -33,20 +49,35 @@ impl<O: Identifiable> Eq for Id<O> {}
 
 /// The Display implementation produces a publicly usable
 /// id with the prefix preventing any ambiguity
+///
+/// The output is always the canonical public id, see
+/// [`Id::canonical_public_id`].
 impl<O: Identifiable> fmt::Display for Id<O> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}_{}", self.class().prefix(), &self.uuid.hyphenated())
+        self.class().fmt_public_id(&self.uuid, f)
     }
 }
 
+/// Ids are debugged as a struct, eg
+/// `Id { uuid: 371c35ec-34d9-4315-ab31-7ea8889a419a, class: "Cust" }`,
+/// and, with the alternate flag (`{:#?}`, as in snapshots of pretty
+/// printed values), as the public id on a single line, eg
+/// `Id(Cust_371c35ec-34d9-4315-ab31-7ea8889a419a)`.
+#[cfg(not(feature = "redact"))]
+impl<O: Identifiable> fmt::Debug for Id<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.class().fmt_debug(&self.uuid, f)
+    }
+}
+/// With the "redact" feature, ids are debugged in their redacted form,
+/// eg `Cust_371c…[redacted]`
+#[cfg(feature = "redact")]
 impl<O: Identifiable> fmt::Debug for Id<O> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Id")
-            .field("uuid", &self.uuid)
-            .field("class", &self.class().prefix())
-            .finish()
+        fmt::Display::fmt(&self.redacted(), f)
     }
 }
+
 impl<O: Identifiable> Clone for Id<O> {
     fn clone(&self) -> Self {
         *self
@@ -66,6 +97,23 @@ impl<O: Identifiable> Ord for Id<O> {
 }
 
 impl<O: Identifiable> Id<O> {
+    /// The length, in bytes, of the public ids of this class
+    ///
+    /// It's exact for the types using the derive, and an upper bound for
+    /// the implementations of `Identifiable` not setting `PUBLIC_ID_LEN`.
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let mut buffer = [0; Id::<Customer>::PUBLIC_LEN];
+    /// let id = Id::<Customer>::random_v4();
+    /// assert_eq!(id.public_id_buf(&mut buffer), id.public_id());
+    /// ```
+    pub const PUBLIC_LEN: usize = O::PUBLIC_ID_LEN;
     /// Return the class of an id, usually mapped to a specific struct
     pub fn class(&self) -> IdClass {
         <O as Identifiable>::class()
@@ -80,24 +128,110 @@ impl<O: Identifiable> Id<O> {
     /// be written in database using sqlx without going through
     /// a string representation.
     pub fn db_id(&self) -> String {
-        self.uuid.hyphenated().to_string()
+        let mut buffer = Uuid::encode_buffer();
+        self.uuid.hyphenated().encode_lower(&mut buffer).to_string()
+    }
+    /// Write the database identifier in the buffer, without allocating,
+    /// and return it
+    pub fn db_id_buf<'b>(&self, buffer: &'b mut [u8; DB_ID_LEN]) -> &'b str {
+        self.uuid.hyphenated().encode_lower(buffer)
     }
     /// Return the public representation as a string, which should
     /// be used in JSON, URL, or anywhere except the database.
     pub fn public_id(&self) -> String {
-        self.to_string()
+        self.class().public_id(&self.uuid)
+    }
+    /// Write the public representation in the buffer, without
+    /// allocating, and return it
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is shorter than the public id, that is the
+    /// prefix, the underscore and 36 bytes, which is checked against the
+    /// class whatever `Id::PUBLIC_LEN`
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+    /// let mut buffer = [0; 64];
+    /// assert_eq!(id.public_id_buf(&mut buffer), id.public_id());
+    /// ```
+    pub fn public_id_buf<'b>(&self, buffer: &'b mut [u8]) -> &'b str {
+        self.class().public_id_buf(&self.uuid, buffer)
+    }
+    /// Return the canonical public representation.
+    ///
+    /// While public ids are parsed in a case insensitive way, kind
+    /// always produces (in Display, public_id, Serialize, etc.) the
+    /// same canonical form:
+    /// - the class prefix, with the case used in its declaration
+    /// - an underscore
+    /// - the UUID in lowercase hyphenated form
+    ///
+    /// This form is guaranteed to be stable, so that it can be used
+    /// for signatures, hashes or cache keys.
+    pub fn canonical_public_id(&self) -> String {
+        self.public_id()
+    }
+    /// Parse a public id and return it in its canonical form, see
+    /// [`Id::canonical_public_id`]
+    pub fn canonicalize(public_id: &str) -> Result<String, IdError> {
+        Self::from_public_id(public_id).map(|id| id.canonical_public_id())
     }
     /// Parse an Id from its public representation, checking the class
     pub fn from_public_id(public_id: &str) -> Result<Self, IdError> {
-        let db_id = <O as Identifiable>::class().strip_prefix(public_id)?;
-        let uuid = Uuid::try_parse(db_id).map_err(|_| IdError::InvalidFormat)?;
-        Ok(Self::unchecked(uuid))
+        O::class().parse_public_id(public_id).map(Self::unchecked)
     }
     /// Parse the Id from its database string representation, *not* checking
     /// the class (as it's not embedded in this representation)
     pub fn from_db_id(db_id: &str) -> Result<Self, IdError> {
-        let uuid = Uuid::try_parse(db_id).map_err(|_| IdError::InvalidFormat)?;
-        Ok(Self::unchecked(uuid))
+        O::class().parse_db_id(db_id).map(Self::unchecked)
+    }
+    /// Parse a public id coming from an untrusted source, eg a request,
+    /// a webhook or a file, checking the class
+    ///
+    /// This accepts the same ids as [`Id::from_public_id`], but bounds
+    /// the work done on hostile inputs:
+    /// - inputs longer than the longest public id of the class (the
+    ///   prefix, the underscore and a 45 bytes URN UUID) are rejected
+    ///   without being read
+    /// - inputs with non ASCII characters are rejected before parsing
+    /// - it never panics, whatever the input, and the error holds at most
+    ///   64 bytes of the input
+    ///
+    /// So the time and memory it takes are bounded by the length of the
+    /// prefix of the class.
+    pub fn parse_any_untrusted(input: &str) -> Result<Self, IdError> {
+        O::class().parse_untrusted(input).map(Self::unchecked)
+    }
+    /// Build an Id from a uuid obtained elsewhere, eg from another
+    /// library, or an event already checked by its producer
+    ///
+    /// This is deliberately unchecked, a uuid not carrying its class: the
+    /// caller vouches that the uuid is the one of an object of the class.
+    /// It's the same conversion as `Id::from(uuid)`, with a name making
+    /// this trust visible in the code, and it can build constant ids:
+    ///
+    /// ```
+    /// use {kind::*, uuid::Uuid};
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// const SYSTEM: Id<Customer> = Id::from_uuid_trusted(Uuid::from_u128(1));
+    /// assert_eq!(SYSTEM.public_id(), "Cust_00000000-0000-0000-0000-000000000001");
+    /// ```
+    pub const fn from_uuid_trusted(uuid: Uuid) -> Self {
+        Self {
+            uuid,
+            phantom: PhantomData,
+        }
     }
     /// Build an Id without checking the class
     pub(crate) fn unchecked(uuid: Uuid) -> Self {
@@ -106,15 +240,48 @@ impl<O: Identifiable> Id<O> {
             phantom: PhantomData,
         }
     }
+    /// Build an Id without checking the class, from a database column
+    /// holding either the 16 bytes of the uuid or its textual form
+    #[cfg(any(feature = "sqlx-mysql", feature = "sqlx-sqlite"))]
+    pub(crate) fn from_uuid_column(bytes: &[u8]) -> Result<Self, uuid::Error> {
+        let uuid = if bytes.len() == 16 {
+            Uuid::from_slice(bytes)?
+        } else {
+            Uuid::try_parse_ascii(bytes)?
+        };
+        Ok(Self::unchecked(uuid))
+    }
     /// Build a random Id based on Uuid v4 (only random)
     ///
     /// See <https://www.rfc-editor.org/rfc/rfc4122#section-4.4>
+    ///
+    /// With the `testing` feature, the ids built in the closure of
+    /// [`testing::with_deterministic_ids`] come from a seeded sequence.
     pub fn random_v4() -> Self {
+        #[cfg(feature = "testing")]
+        if let Some(uuid) = testing::deterministic_uuid() {
+            return Self::unchecked(uuid);
+        }
         Self::unchecked(Uuid::new_v4())
     }
 }
 
-/// Make an Id from any valid Uuid
+/// The length of a database identifier, a hyphenated UUID
+pub const DB_ID_LEN: usize = 36;
+
+/// The length of the longest prefixes whose public ids fit in buffers of
+/// [`Id::PUBLIC_LEN`] bytes when `Identifiable::PUBLIC_ID_LEN` isn't set
+pub const MAX_PREFIX_LEN: usize = 64;
+
+/// The name of the span field, or telemetry attribute, holding the class
+/// prefix of an entity
+pub const ENTITY_CLASS_FIELD: &str = "entity.class";
+
+/// The name of the span field, or telemetry attribute, holding the id of
+/// an entity
+pub const ENTITY_ID_FIELD: &str = "entity.id";
+
+/// Make an Id from any valid Uuid, see [`Id::from_uuid_trusted`]
 impl<O: Identifiable> From<Uuid> for Id<O> {
     fn from(uuid: Uuid) -> Self {
         Self::unchecked(uuid)
@@ -129,12 +296,52 @@ impl<O: Identifiable> FromStr for Id<O> {
     }
 }
 
+/// Parse an Id from its public representation, eg a string field of
+/// a protobuf message, checking the class
+impl<O: Identifiable> TryFrom<&str> for Id<O> {
+    type Error = IdError;
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::from_public_id(s)
+    }
+}
+
+impl<O: Identifiable> TryFrom<String> for Id<O> {
+    type Error = IdError;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::from_public_id(&s)
+    }
+}
+
 impl<O: Identifiable> Hash for Id<O> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.uuid().hash(state);
     }
 }
 
+/// The archived id has the same 16 bytes layout than the Uuid,
+/// and can be used without deserialization
+#[cfg(feature = "rkyv")]
+impl<O: Identifiable> ArchivedId<O> {
+    /// Return the archived id
+    pub fn id(&self) -> Id<O> {
+        Id::unchecked(self.uuid)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<O: Identifiable> fmt::Debug for ArchivedId<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.id().fmt(f)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<O: Identifiable> PartialEq<Id<O>> for ArchivedId<O> {
+    fn eq(&self, other: &Id<O>) -> bool {
+        self.uuid == other.uuid
+    }
+}
+
 #[test]
 fn id_sorting() {
     #[derive(Debug, Identifiable)]
@@ -185,3 +392,213 @@ fn id_sorting() {
         }
     }
 }
+
+#[test]
+fn canonical_public_id() {
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "CuSt")]
+    pub struct C {}
+
+    let canonical = "CuSt_371c35ec-34d9-4315-ab31-7ea8889a419a";
+    for public_id in [
+        canonical,
+        "cust_371c35ec-34d9-4315-ab31-7ea8889a419a",
+        "CUST_371C35EC-34D9-4315-AB31-7EA8889A419A",
+    ] {
+        let id: Id<C> = public_id.parse().unwrap();
+        assert_eq!(id.canonical_public_id(), canonical);
+        assert_eq!(id.to_string(), canonical);
+        assert_eq!(Id::<C>::canonicalize(public_id).unwrap(), canonical);
+    }
+    assert!(Id::<C>::canonicalize("Cont_371c35ec-34d9-4315-ab31-7ea8889a419a").is_err());
+}
+
+#[test]
+fn strip_prefix() {
+    let class = IdClass::new("Cust");
+    assert_eq!(class.strip_prefix("cUST_abc"), Ok("abc"));
+    assert_eq!(class.strip_prefix("Cust_"), Ok(""));
+    for (public_id, kind, position) in [
+        ("", IdErrorKind::WrongClass, 0),
+        ("Cu", IdErrorKind::WrongClass, 2),
+        ("Cont_abc", IdErrorKind::WrongClass, 1),
+        ("Cust", IdErrorKind::InvalidFormat, 4),
+        ("Custabc", IdErrorKind::InvalidFormat, 4),
+        ("Customer_abc", IdErrorKind::WrongClass, 4),
+        ("Cusé_abc", IdErrorKind::WrongClass, 3),
+    ] {
+        let error = class.strip_prefix(public_id).unwrap_err();
+        assert_eq!((error.kind(), error.position()), (kind, Some(position)));
+    }
+    let class = IdClass::new("Clé");
+    assert_eq!(class.strip_prefix("clé_abc"), Ok("abc"));
+    let error = class.strip_prefix("clè_abc").unwrap_err();
+    assert_eq!(error.position(), Some(2));
+}
+
+#[test]
+fn id_buffers() {
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct C {}
+
+    let id: Id<C> = "CUST_371C35EC-34D9-4315-AB31-7EA8889A419A".parse().unwrap();
+    assert_eq!(
+        id.db_id_buf(&mut [0; DB_ID_LEN]),
+        "371c35ec-34d9-4315-ab31-7ea8889a419a"
+    );
+    let mut buffer = [b'x'; 64];
+    assert_eq!(
+        id.public_id_buf(&mut buffer),
+        "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a"
+    );
+    assert_eq!(buffer[41], b'x');
+    assert_eq!(id.public_id().capacity(), 41);
+    assert_eq!(Id::<C>::PUBLIC_LEN, 41);
+    assert_eq!(C::class().public_id_len(), 41);
+    assert_eq!(
+        format!("{id:>45}"),
+        "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a"
+    );
+    assert!(std::panic::catch_unwind(|| id.public_id_buf(&mut [0; 40]).len()).is_err());
+
+    // a manual implementation gets an upper bound
+    pub struct Manual {}
+    impl Identifiable for Manual {
+        fn class() -> IdClass {
+            IdClass::new("Manual")
+        }
+    }
+    let id = Id::<Manual>::from_db_id("371c35ec-34d9-4315-ab31-7ea8889a419a").unwrap();
+    assert_eq!(Id::<Manual>::PUBLIC_LEN, MAX_PREFIX_LEN + 37);
+    assert_eq!(
+        id.public_id_buf(&mut [0; Id::<Manual>::PUBLIC_LEN]),
+        id.public_id()
+    );
+
+    // a wrong length is caught by the check of the class
+    pub struct Wrong {}
+    impl Identifiable for Wrong {
+        fn class() -> IdClass {
+            IdClass::new("Wrong")
+        }
+        const PUBLIC_ID_LEN: usize = 10;
+    }
+    let id = Id::<Wrong>::random_v4();
+    let error =
+        std::panic::catch_unwind(|| id.public_id_buf(&mut [0; Id::<Wrong>::PUBLIC_LEN]).len())
+            .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<String>().map(String::as_str),
+        Some("buffer too short for the public id: 10 < 42")
+    );
+}
+
+#[test]
+fn untrusted_public_id() {
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct C {}
+
+    let id = Id::<C>::random_v4();
+    assert_eq!(Id::<C>::parse_any_untrusted(&id.public_id()), Ok(id));
+    let urn = format!("Cust_{}", id.uuid.urn());
+    assert_eq!(Id::<C>::parse_any_untrusted(&urn), Ok(id));
+
+    let long = format!("{urn}0");
+    let error = Id::<C>::parse_any_untrusted(&long).unwrap_err();
+    assert_eq!(error.kind(), IdErrorKind::TooLong);
+    assert_eq!(error.position(), Some(urn.len()));
+    let error = Id::<C>::parse_any_untrusted(&"x".repeat(1 << 20)).unwrap_err();
+    assert_eq!(error.input().unwrap().len(), 64);
+
+    // multibyte characters around the prefix and the separator
+    for input in [
+        "Cuſt_",
+        "Cus\u{2f8}_x",
+        "Cust\u{ff3f}x",
+        "é",
+        "Cust_é",
+        "ÇÜŞŢ_",
+    ] {
+        assert!(Id::<C>::parse_any_untrusted(input).is_err());
+        assert!(Id::<C>::from_public_id(input).is_err());
+    }
+    let error = Id::<C>::parse_any_untrusted("Cust_é").unwrap_err();
+    assert_eq!(error.position(), Some(5));
+}
+
+#[test]
+fn class_sensitivity() {
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust", sensitivity = "pii")]
+    pub struct C {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct D {}
+
+    assert_eq!(C::class().sensitivity(), Sensitivity::Pii);
+    assert_eq!(C::class().prefix(), "Cust");
+    assert_eq!(D::class().sensitivity(), Sensitivity::Public);
+    assert!(Sensitivity::Secret > Sensitivity::Pii);
+    assert!(Sensitivity::Internal < Sensitivity::Pii);
+    assert_eq!(Sensitivity::Pii.to_string(), "pii");
+    assert_eq!(
+        IdClass::new("Tok")
+            .with_sensitivity(Sensitivity::Secret)
+            .sensitivity(),
+        Sensitivity::Secret
+    );
+}
+
+#[cfg(not(feature = "redact"))]
+#[test]
+fn debug_formatting() {
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct C {}
+
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Invoice {
+        customer: Id<C>,
+    }
+
+    let id: Id<C> = "cust_371C35EC-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+    assert_eq!(
+        format!("{id:?}"),
+        r#"Id { uuid: 371c35ec-34d9-4315-ab31-7ea8889a419a, class: "Cust" }"#
+    );
+    assert_eq!(
+        format!("{:#?}", Invoice { customer: id }),
+        "Invoice {\n    customer: Id(Cust_371c35ec-34d9-4315-ab31-7ea8889a419a),\n}"
+    );
+}
+
+#[cfg(feature = "rkyv")]
+#[test]
+fn rkyv_archive() {
+    use rkyv::rancor::Error;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Ex")]
+    pub struct E {}
+
+    assert_eq!(std::mem::size_of::<ArchivedId<E>>(), 16);
+
+    let id = Id::<E>::random_v4();
+    let bytes = rkyv::to_bytes::<Error>(&id).unwrap();
+    let archived = rkyv::access::<ArchivedId<E>, Error>(&bytes).unwrap();
+    assert_eq!(*archived, id);
+    assert_eq!(archived.id(), id);
+    assert_eq!(rkyv::from_bytes::<Id<E>, Error>(&bytes).unwrap(), id);
+
+    let ided = Ided::new(id, "some entity".to_string());
+    let bytes = rkyv::to_bytes::<Error>(&ided).unwrap();
+    let archived = rkyv::access::<ArchivedIded<E, String>, Error>(&bytes).unwrap();
+    assert_eq!(archived.id(), id);
+    assert_eq!(archived.entity(), "some entity");
+    let ided_back = rkyv::from_bytes::<Ided<E, String>, Error>(&bytes).unwrap();
+    assert_eq!(ided_back.entity(), ided.entity());
+}
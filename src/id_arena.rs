@@ -0,0 +1,240 @@
+use {
+    super::*,
+    std::{collections::HashMap, ops::Index},
+    uuid::Uuid,
+};
+
+/// An arena of values keyed by id, to build in-memory graphs of
+/// objects: values are looked up by id in constant time, iterated in
+/// insertion order, and can be linked to the ids of any class.
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Plan")]
+/// pub struct Plan {}
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Feat")]
+/// pub struct Feature {}
+///
+/// let (basic, pro) = (Id::<Plan>::random_v4(), Id::<Plan>::random_v4());
+/// let export = Id::<Feature>::random_v4();
+///
+/// let mut plans: IdArena<Plan, &str> = IdArena::new();
+/// plans.insert(basic, "Basic");
+/// plans.insert(pro, "Pro");
+/// plans.link(pro, basic); // pro extends basic
+/// plans.link(pro, export);
+///
+/// assert_eq!(plans[pro], "Pro");
+/// assert_eq!(plans.values().collect::<Vec<_>>(), [&"Basic", &"Pro"]);
+/// assert_eq!(plans.links::<Feature>(pro).collect::<Vec<_>>(), [export]);
+/// assert_eq!(plans.linked(pro).map(|(_, name)| *name).collect::<Vec<_>>(), ["Basic"]);
+/// ```
+///
+/// Links are kept in the order they're added, a link added twice being
+/// returned twice.
+pub struct IdArena<O: Identifiable, V> {
+    nodes: Vec<Node<O, V>>,
+    index: HashMap<Id<O>, usize>,
+}
+
+struct Node<O: Identifiable, V> {
+    id: Id<O>,
+    value: V,
+    links: Vec<(IdClass, Uuid)>,
+}
+
+impl<O: Identifiable, V> IdArena<O, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, id: Id<O>) -> bool {
+        self.index.contains_key(&id)
+    }
+
+    /// Insert the value of an id, returning the previous value when the
+    /// id was already in the arena, in which case it keeps its position
+    /// and its links
+    pub fn insert(&mut self, id: Id<O>, value: V) -> Option<V> {
+        if let Some(&i) = self.index.get(&id) {
+            return Some(std::mem::replace(&mut self.nodes[i].value, value));
+        }
+        self.index.insert(id, self.nodes.len());
+        self.nodes.push(Node {
+            id,
+            value,
+            links: Vec::new(),
+        });
+        None
+    }
+
+    pub fn get(&self, id: Id<O>) -> Option<&V> {
+        self.node(id).map(|node| &node.value)
+    }
+
+    pub fn get_mut(&mut self, id: Id<O>) -> Option<&mut V> {
+        let i = *self.index.get(&id)?;
+        Some(&mut self.nodes[i].value)
+    }
+
+    fn node(&self, id: Id<O>) -> Option<&Node<O, V>> {
+        self.index.get(&id).map(|&i| &self.nodes[i])
+    }
+
+    /// Add a link from an id of the arena to an id of any class,
+    /// returning false, without adding it, when the source isn't in
+    /// the arena
+    pub fn link<B: Identifiable>(&mut self, from: Id<O>, to: Id<B>) -> bool {
+        match self.index.get(&from) {
+            Some(&i) => {
+                self.nodes[i].links.push((B::class(), to.uuid()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the ids of the class of `B` linked from an id
+    pub fn links<B: Identifiable>(&self, from: Id<O>) -> impl Iterator<Item = Id<B>> + '_ {
+        self.node(from)
+            .into_iter()
+            .flat_map(|node| &node.links)
+            .filter(|(class, _)| *class == B::class())
+            .map(|(_, uuid)| Id::unchecked(*uuid))
+    }
+
+    /// Return the ids and values of the arena linked from an id
+    pub fn linked(&self, from: Id<O>) -> impl Iterator<Item = (Id<O>, &V)> + '_ {
+        self.links::<O>(from)
+            .filter_map(move |id| self.get(id).map(|value| (id, value)))
+    }
+
+    /// Return the ids and values, in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (Id<O>, &V)> + '_ {
+        self.nodes.iter().map(|node| (node.id, &node.value))
+    }
+
+    /// Return the ids, in insertion order
+    pub fn ids(&self) -> impl Iterator<Item = Id<O>> + '_ {
+        self.nodes.iter().map(|node| node.id)
+    }
+
+    /// Return the values, in insertion order
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.nodes.iter().map(|node| &node.value)
+    }
+
+    /// Return the entries as `Ided` values, in insertion order, dropping
+    /// the links
+    pub fn into_ideds(self) -> impl Iterator<Item = Ided<O, V>> {
+        self.nodes
+            .into_iter()
+            .map(|node| Ided::new(node.id, node.value))
+    }
+}
+
+impl<O: Identifiable, V> Default for IdArena<O, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Panic when the id isn't in the arena
+impl<O: Identifiable, V> Index<Id<O>> for IdArena<O, V> {
+    type Output = V;
+    fn index(&self, id: Id<O>) -> &V {
+        match self.get(id) {
+            Some(value) => value,
+            None => panic!("{id} isn't in the arena"),
+        }
+    }
+}
+
+/// The values are inserted in the order of the iterator, the last one
+/// winning when an id is repeated
+impl<O: Identifiable, V> FromIterator<Ided<O, V>> for IdArena<O, V> {
+    fn from_iter<I: IntoIterator<Item = Ided<O, V>>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut arena = Self::with_capacity(iter.size_hint().0);
+        for ided in iter {
+            let (id, value) = ided.dismantle();
+            arena.insert(id, value);
+        }
+        arena
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Plan")]
+    pub struct Plan {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Feat")]
+    pub struct Feature {}
+
+    #[test]
+    fn test_arena() {
+        let ids: Vec<Id<Plan>> = (0..100).map(|_| Id::random_v4()).collect();
+        let mut arena: IdArena<Plan, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| Ided::new(id, i))
+            .collect();
+        assert_eq!(arena.len(), 100);
+        assert!(arena.ids().eq(ids.iter().copied()));
+        assert_eq!(arena.insert(ids[3], 1000), Some(3));
+        assert_eq!(arena.values().nth(3), Some(&1000));
+        *arena.get_mut(ids[4]).unwrap() += 1;
+        assert_eq!(arena[ids[4]], 5);
+        assert_eq!(arena.get(Id::random_v4()), None);
+    }
+
+    #[test]
+    fn test_links() {
+        let [a, b, c] = [(); 3].map(|_| Id::<Plan>::random_v4());
+        let feature = Id::<Feature>::random_v4();
+        let mut arena = IdArena::new();
+        arena.insert(a, "a");
+        arena.insert(b, "b");
+        assert!(arena.link(a, b));
+        assert!(arena.link(a, feature));
+        assert!(arena.link(a, c));
+        assert!(!arena.link(c, a));
+        assert_eq!(arena.links::<Plan>(a).collect::<Vec<_>>(), [b, c]);
+        assert_eq!(arena.links::<Feature>(a).collect::<Vec<_>>(), [feature]);
+        assert_eq!(arena.linked(a).collect::<Vec<_>>(), [(b, &"b")]);
+        assert_eq!(arena.links::<Plan>(b).count(), 0);
+        arena.insert(a, "a2");
+        assert_eq!(arena.links::<Feature>(a).count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't in the arena")]
+    fn test_index_missing() {
+        let arena = IdArena::<Plan, u32>::new();
+        let _ = arena[Id::random_v4()];
+    }
+}
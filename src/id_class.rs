@@ -1,4 +1,27 @@
-use {crate::IdError, std::fmt};
+use {
+    crate::{IdError, IdErrorKind, DB_ID_LEN},
+    std::fmt,
+    uuid::Uuid,
+};
+
+/// The prefixes of the classes created at runtime, by the bindings to
+/// other languages, leaked once each as classes need static prefixes
+#[cfg(any(feature = "wasm", feature = "ffi"))]
+static INTERNED_PREFIXES: std::sync::Mutex<std::collections::BTreeSet<&'static str>> =
+    std::sync::Mutex::new(std::collections::BTreeSet::new());
+
+/// The maximal length of the prefixes given at runtime
+#[cfg(any(feature = "wasm", feature = "ffi"))]
+const MAX_INTERNED_PREFIX_LEN: usize = 32;
+
+/// The maximal number of interned prefixes, bounding the leaked memory
+/// whatever the inputs of the bindings
+#[cfg(any(feature = "wasm", feature = "ffi"))]
+const MAX_INTERNED_PREFIXES: usize = 1024;
+
+/// The length of the longest textual form of a UUID accepted when
+/// parsing, the URN (`urn:uuid:` and a hyphenated UUID)
+const MAX_UUID_LEN: usize = 45;
 
 /// A category of identifiable objects
 ///
@@ -13,20 +36,117 @@ use {crate::IdError, std::fmt};
 /// Don't try to use the same class for two
 /// identifiable strucs, instead map the class
 /// to the most "natural" struct.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct IdClass {
     prefix: &'static str,
+    sensitivity: Sensitivity,
+}
+
+/// How sensitive the ids of a class are, so that logging and
+/// serialization layers can decide which ids to redact or encrypt
+///
+/// Levels are ordered, from the least to the most sensitive:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust", sensitivity="pii")]
+/// pub struct Customer {}
+///
+/// let id = Id::<Customer>::random_v4();
+/// assert_eq!(id.class().sensitivity(), Sensitivity::Pii);
+/// assert!(id.class().sensitivity() >= Sensitivity::Pii);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Sensitivity {
+    /// Ids which can be shown anywhere (the default)
+    #[default]
+    Public,
+    /// Ids which shouldn't leave the company
+    Internal,
+    /// Ids of personal data, eg customers or users
+    Pii,
+    /// Ids which give access to something, eg tokens
+    Secret,
+}
+
+impl Sensitivity {
+    /// The name of the level, as given to the derive
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Internal => "internal",
+            Self::Pii => "pii",
+            Self::Secret => "secret",
+        }
+    }
+}
+
+impl fmt::Display for Sensitivity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 impl IdClass {
     /// Create a new valid class.
     pub const fn new(prefix: &'static str) -> Self {
         assert!(!prefix.is_empty());
-        Self { prefix }
+        Self {
+            prefix,
+            sensitivity: Sensitivity::Public,
+        }
+    }
+    /// Set the sensitivity of the ids of the class, which is
+    /// `Public` by default
+    pub const fn with_sensitivity(mut self, sensitivity: Sensitivity) -> Self {
+        self.sensitivity = sensitivity;
+        self
     }
-    pub fn prefix(self) -> &'static str {
+    /// Return the relations declared on the type of the class, with the
+    /// `belongs_to` and `has_many` attributes of the derive, or none if
+    /// the class isn't registered
+    #[cfg(feature = "registry")]
+    pub fn relations(self) -> &'static [crate::Relation] {
+        crate::registry::find(self.prefix).map_or(&[], |c| c.relations())
+    }
+    /// Return the class of a prefix only known at runtime, eg given by
+    /// the caller of a binding, checking it's `[a-zA-Z0-9]+` like the
+    /// derive does, and at most 32 bytes long
+    ///
+    /// At most 1024 prefixes are interned, the other ones being
+    /// rejected as unknown.
+    #[cfg(any(feature = "wasm", feature = "ffi"))]
+    pub(crate) fn interned(prefix: &str) -> Result<Self, IdError> {
+        if prefix.is_empty()
+            || prefix.len() > MAX_INTERNED_PREFIX_LEN
+            || !prefix.bytes().all(|b| b.is_ascii_alphanumeric())
+        {
+            return Err(IdError::new(IdErrorKind::InvalidFormat).with_input(prefix));
+        }
+        let mut prefixes = INTERNED_PREFIXES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(prefix) = prefixes.get(prefix) {
+            return Ok(Self::new(prefix));
+        }
+        if prefixes.len() >= MAX_INTERNED_PREFIXES {
+            return Err(IdError::new(IdErrorKind::UnknownPrefix).with_input(prefix));
+        }
+        let leaked: &'static str = Box::leak(prefix.into());
+        prefixes.insert(leaked);
+        Ok(Self::new(leaked))
+    }
+    pub const fn prefix(self) -> &'static str {
         self.prefix
     }
+    pub const fn sensitivity(self) -> Sensitivity {
+        self.sensitivity
+    }
+    /// The length, in bytes, of the public ids of this class, eg to
+    /// allocate buffers of the exact size
+    pub const fn public_id_len(self) -> usize {
+        self.prefix.len() + 1 + DB_ID_LEN
+    }
     /// Remove the prefix and underscore from a public id to
     /// get the db_id.
     ///
@@ -35,26 +155,147 @@ impl IdClass {
     pub fn strip_prefix(self, public_id: &str) -> Result<&str, IdError> {
         // The implementation here doesn't assume anything about the
         // class as it can't be enforced in the const constructor.
-        let mut public_id_chars = public_id.chars();
-        let mut public_prefix_len = 0; // in bytes
-        for class_char in self.prefix.chars() {
-            let Some(public_id_char) = public_id_chars.next() else {
-                return Err(IdError::WrongClass);
-            };
-            if !public_id_char.eq_ignore_ascii_case(&class_char) {
-                return Err(IdError::WrongClass);
-            }
-            public_prefix_len += public_id_char.len_utf8();
+        let prefix = self.prefix_bytes();
+        let bytes = public_id.as_bytes();
+        if bytes.len() > prefix.len()
+            && bytes[prefix.len()] == b'_'
+            && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+        {
+            // non ASCII bytes are compared exactly, so the prefix of the
+            // public id is valid UTF-8 and ends on a char boundary
+            return Ok(&public_id[prefix.len() + 1..]);
         }
-        if public_id_chars.next() != Some('_') {
-            if public_id.contains('_') {
-                return Err(IdError::WrongClass);
-            } else {
-                return Err(IdError::InvalidFormat);
-            }
+        Err(self.prefix_error(public_id))
+    }
+    /// The bytes of the prefix, for comparisons without char iteration
+    pub const fn prefix_bytes(self) -> &'static [u8] {
+        self.prefix.as_bytes()
+    }
+    /// Build the error of a public id not starting with the prefix and
+    /// an underscore, out of the fast path
+    #[cold]
+    fn prefix_error(self, public_id: &str) -> IdError {
+        let prefix = self.prefix_bytes();
+        let bytes = public_id.as_bytes();
+        let mut position = bytes
+            .iter()
+            .zip(prefix)
+            .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count();
+        while !public_id.is_char_boundary(position) {
+            position -= 1;
         }
-        public_prefix_len += 1;
-        Ok(&public_id[public_prefix_len..])
+        // when the prefix matches but isn't followed by an underscore,
+        // the id is malformed, unless it's an id with a longer prefix
+        let kind = if position == prefix.len() && !public_id.contains('_') {
+            IdErrorKind::InvalidFormat
+        } else {
+            IdErrorKind::WrongClass
+        };
+        IdError::new(kind)
+            .with_input(public_id)
+            .with_expected(self)
+            .with_position(position)
+    }
+    // The formatting and parsing of ids is implemented here, on the class,
+    // and not in the generic methods of `Id`, so that it isn't compiled
+    // again for every identifiable type.
+
+    pub(crate) fn fmt_public_id(self, uuid: &Uuid, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Uuid::encode_buffer();
+        f.write_str(self.prefix)?;
+        f.write_str("_")?;
+        f.write_str(uuid.hyphenated().encode_lower(&mut buffer))
+    }
+    #[cfg(any(not(feature = "redact"), feature = "registry", feature = "wasm"))]
+    pub(crate) fn fmt_debug(self, uuid: &Uuid, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("Id(")?;
+            self.fmt_public_id(uuid, f)?;
+            return f.write_str(")");
+        }
+        f.debug_struct("Id")
+            .field("uuid", uuid)
+            .field("class", &self.prefix)
+            .finish()
+    }
+    pub(crate) fn public_id(self, uuid: &Uuid) -> String {
+        let mut public_id = String::with_capacity(self.public_id_len());
+        public_id.push_str(self.prefix);
+        public_id.push('_');
+        public_id.push_str(uuid.hyphenated().encode_lower(&mut [0; DB_ID_LEN]));
+        public_id
+    }
+    pub(crate) fn public_id_buf<'b>(self, uuid: &Uuid, buffer: &'b mut [u8]) -> &'b str {
+        let prefix = self.prefix_bytes();
+        let len = self.public_id_len();
+        assert!(
+            buffer.len() >= len,
+            "buffer too short for the public id: {} < {len}",
+            buffer.len()
+        );
+        let buffer = &mut buffer[..len];
+        buffer[..prefix.len()].copy_from_slice(prefix);
+        buffer[prefix.len()] = b'_';
+        uuid.hyphenated()
+            .encode_lower(&mut buffer[prefix.len() + 1..]);
+        // the prefix is a str, and the rest is ASCII
+        std::str::from_utf8(buffer).expect("public ids are UTF-8")
+    }
+    /// Parse a public id of the class, returning its uuid, eg when the
+    /// class is only known at runtime
+    pub fn parse_public_id(self, public_id: &str) -> Result<Uuid, IdError> {
+        let db_id = self.strip_prefix(public_id)?;
+        Uuid::try_parse_ascii(db_id.as_bytes()).map_err(|e| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(public_id)
+                .with_expected(self)
+                .with_position(public_id.len() - db_id.len())
+                .with_source(e)
+        })
+    }
+    /// Parse a db id (a textual uuid), with errors mentioning the class
+    pub fn parse_db_id(self, db_id: &str) -> Result<Uuid, IdError> {
+        Uuid::try_parse_ascii(db_id.as_bytes()).map_err(|e| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(db_id)
+                .with_expected(self)
+                .with_source(e)
+        })
+    }
+    pub(crate) fn parse_untrusted(self, input: &str) -> Result<Uuid, IdError> {
+        let max_len = self.prefix.len() + 1 + MAX_UUID_LEN;
+        let error = |kind, position| {
+            IdError::new(kind)
+                .with_input(input)
+                .with_expected(self)
+                .with_position(position)
+        };
+        if input.len() > max_len {
+            return Err(error(IdErrorKind::TooLong, max_len));
+        }
+        if let Some(position) = input.bytes().position(|b| !b.is_ascii()) {
+            return Err(error(IdErrorKind::InvalidFormat, position));
+        }
+        self.parse_public_id(input)
+    }
+    /// A regular expression matching the public ids of the class, as
+    /// written by kind (parsing is more lenient, being case insensitive)
+    pub fn public_id_pattern(self) -> String {
+        format!(
+            "^{}_[0-9a-fA-F]{{8}}-[0-9a-fA-F]{{4}}-[0-9a-fA-F]{{4}}-[0-9a-fA-F]{{4}}-[0-9a-fA-F]{{12}}$",
+            self.prefix
+        )
+    }
+    /// An example of public id, for the documentation of schemas
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-1",
+        all(feature = "openapi", feature = "serde"),
+        all(feature = "registry", feature = "serde")
+    ))]
+    pub(crate) fn example_public_id(self) -> String {
+        format!("{}_c40bea18-c0c9-44b1-bd0c-43f5283e1670", self.prefix)
     }
 }
 
@@ -29,8 +29,22 @@
 /// let a: PetId = s.parse().unwrap();
 /// assert!(matches!(a, PetId::Dog(_)));
 /// assert_eq!(s.to_string(), a.to_string());
+///
+/// let e = "Cow_453d6f99-ce09-4dd7-bde9-73c1d2dbc1d0".parse::<PetId>().unwrap_err();
+/// assert_eq!(e.kind(), IdErrorKind::UnknownPrefix);
 /// ```
 ///
+/// A prefix which is the one of none of the types gives an
+/// [`IdErrorKind::UnknownPrefix`] error. Up to version 1.0, it gave an
+/// `IdErrorKind::WrongClass` error, so code matching this kind must now
+/// also match `UnknownPrefix`. Other errors, eg an invalid uuid after a
+/// known prefix, are the ones of parsing the id of this type.
+///
+/// With the "async-graphql" feature, the enumeration is also the GraphQL
+/// `ID` scalar. With the "jsonschema-1" and "openapi" features, its
+/// schema is one of the schemas of the ids. With the "proptest" feature,
+/// it implements `Arbitrary`.
+///
 /// This macro needs the "serde" feature to be enabled.
 #[macro_export]
 macro_rules! id_enum {
@@ -45,7 +59,7 @@ macro_rules! id_enum {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 match self {
                     $(
-                        Self::$T(id) => id.fmt(f),
+                        Self::$T(id) => std::fmt::Display::fmt(id, f),
                     )*
                 }
             }
@@ -55,12 +69,14 @@ macro_rules! id_enum {
             fn from_str(s: &str) -> Result<Self, Self::Err> {
                 $(
                     match Id::<$T>::from_str(s) {
-                        Err(IdError::WrongClass) => {}
+                        Err(e) if e.kind() == $crate::IdErrorKind::WrongClass => {}
                         Ok(id) => { return Ok(Self::$T(id)); }
                         Err(e) => { return Err(e); }
                     }
                 )*
-                Err(IdError::WrongClass)
+                Err(IdError::new($crate::IdErrorKind::UnknownPrefix)
+                    .with_input(s)
+                    .with_position(0))
             }
         }
         impl serde::Serialize for $Enum {
@@ -81,5 +97,192 @@ macro_rules! id_enum {
                 Self::from_str(&s).map_err(serde::de::Error::custom)
             }
         }
+        $crate::__id_enum_graphql!($Enum);
+        $crate::__id_enum_jsonschema1!($Enum: $($T),*);
+        $crate::__id_enum_openapi!($Enum: $($T),*);
+        $crate::__id_enum_proptest!($Enum: $($T),*);
+    }
+}
+
+#[cfg(feature = "async-graphql")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_graphql {
+    ($Enum:ident) => {
+        impl $crate::__private::async_graphql::ScalarType for $Enum {
+            fn parse(
+                value: $crate::__private::async_graphql::Value,
+            ) -> $crate::__private::async_graphql::InputValueResult<Self> {
+                use $crate::__private::async_graphql::{InputValueError, Value};
+                match value {
+                    Value::String(s) => s.parse().map_err(InputValueError::custom),
+                    _ => Err(InputValueError::expected_type(value)),
+                }
+            }
+            fn is_valid(value: &$crate::__private::async_graphql::Value) -> bool {
+                matches!(value, $crate::__private::async_graphql::Value::String(_))
+            }
+            fn to_value(&self) -> $crate::__private::async_graphql::Value {
+                $crate::__private::async_graphql::Value::String(self.to_string())
+            }
+        }
+        impl $crate::__private::async_graphql::InputType for $Enum {
+            type RawValueType = Self;
+            fn type_name() -> std::borrow::Cow<'static, str> {
+                <$crate::__private::async_graphql::ID as $crate::__private::async_graphql::InputType>::type_name()
+            }
+            fn create_type_info(
+                registry: &mut $crate::__private::async_graphql::registry::Registry,
+            ) -> String {
+                <$crate::__private::async_graphql::ID as $crate::__private::async_graphql::InputType>::create_type_info(registry)
+            }
+            fn parse(
+                value: Option<$crate::__private::async_graphql::Value>,
+            ) -> $crate::__private::async_graphql::InputValueResult<Self> {
+                <Self as $crate::__private::async_graphql::ScalarType>::parse(value.unwrap_or_default())
+            }
+            fn to_value(&self) -> $crate::__private::async_graphql::Value {
+                <Self as $crate::__private::async_graphql::ScalarType>::to_value(self)
+            }
+            fn as_raw_value(&self) -> Option<&Self::RawValueType> {
+                Some(self)
+            }
+        }
+        impl $crate::__private::async_graphql::OutputType for $Enum {
+            fn type_name() -> std::borrow::Cow<'static, str> {
+                <$crate::__private::async_graphql::ID as $crate::__private::async_graphql::OutputType>::type_name()
+            }
+            fn create_type_info(
+                registry: &mut $crate::__private::async_graphql::registry::Registry,
+            ) -> String {
+                <$crate::__private::async_graphql::ID as $crate::__private::async_graphql::OutputType>::create_type_info(registry)
+            }
+            async fn resolve(
+                &self,
+                _: &$crate::__private::async_graphql::ContextSelectionSet<'_>,
+                _: &$crate::__private::async_graphql::Positioned<
+                    $crate::__private::async_graphql::parser::types::Field,
+                >,
+            ) -> $crate::__private::async_graphql::ServerResult<$crate::__private::async_graphql::Value> {
+                Ok(<Self as $crate::__private::async_graphql::ScalarType>::to_value(self))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "async-graphql"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_graphql {
+    ($Enum:ident) => {};
+}
+
+#[cfg(feature = "jsonschema-1")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_jsonschema1 {
+    ($Enum:ident: $($T:ident),*) => {
+        impl $crate::__private::schemars1::JsonSchema for $Enum {
+            fn schema_name() -> std::borrow::Cow<'static, str> {
+                stringify!($Enum).into()
+            }
+            fn json_schema(
+                generator: &mut $crate::__private::schemars1::SchemaGenerator,
+            ) -> $crate::__private::schemars1::Schema {
+                let schemas = [$(
+                    <Id<$T> as $crate::__private::schemars1::JsonSchema>::json_schema(generator),
+                )*];
+                $crate::__private::schemars1::json_schema!({ "oneOf": schemas })
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "jsonschema-1"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_jsonschema1 {
+    ($Enum:ident: $($T:ident),*) => {};
+}
+
+#[cfg(feature = "openapi")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_openapi {
+    ($Enum:ident: $($T:ident),*) => {
+        impl<'s> $crate::__private::utoipa::ToSchema<'s> for $Enum {
+            fn schema() -> (
+                &'s str,
+                $crate::__private::utoipa::openapi::RefOr<
+                    $crate::__private::utoipa::openapi::schema::Schema,
+                >,
+            ) {
+                let classes = [$(<$T as $crate::Identifiable>::class()),*];
+                (stringify!($Enum), $crate::openapi::__id_enum_schema(&classes))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "openapi"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_openapi {
+    ($Enum:ident: $($T:ident),*) => {};
+}
+
+#[cfg(feature = "proptest")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_proptest {
+    ($Enum:ident: $($T:ident),*) => {
+        impl $crate::__private::proptest::arbitrary::Arbitrary for $Enum {
+            type Parameters = ();
+            type Strategy = $crate::__private::proptest::strategy::BoxedStrategy<Self>;
+            fn arbitrary_with(_: ()) -> Self::Strategy {
+                use $crate::__private::proptest::{arbitrary::any, strategy::{Strategy, Union}};
+                Union::new([$(any::<Id<$T>>().prop_map(Self::$T).boxed()),*]).boxed()
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "proptest"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __id_enum_proptest {
+    ($Enum:ident: $($T:ident),*) => {};
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    id_enum! {Party: Customer, Contract}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_id_enum_errors() {
+        let party: Party = format!("cont_{ID}").parse().unwrap();
+        assert!(matches!(party, Party::Contract(_)));
+
+        let input = format!("Inv_{ID}");
+        let error = input.parse::<Party>().unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::UnknownPrefix);
+        assert_eq!(error.input(), Some(input.as_str()));
+        assert_eq!(error.position(), Some(0));
+
+        let error = "Cust_not-a-uuid".parse::<Party>().unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+        assert_eq!(error.expected(), Some(Customer::class()));
     }
 }
@@ -0,0 +1,121 @@
+use {
+    super::*,
+    std::{
+        collections::{HashMap, HashSet},
+        hash::{BuildHasherDefault, Hasher},
+    },
+};
+
+/// A hasher keeping the last 64 bits written, for maps keyed by ids
+///
+/// The UUIDs of ids are already uniformly random (at least their low 64
+/// bits, for v4 and v7 UUIDs), so there's no need to hash them again:
+/// as the bytes of the UUID are the last ones written by the `Hash` impl
+/// of an id, the hash is made of the low 64 bits of the UUID.
+///
+/// Those bits start with the 2 constant bits of the variant, so the two
+/// 32 bits halves are swapped, moving them to bits 30 and 31: hashbrown
+/// takes its control tags in the top 7 bits of the hash, and the index
+/// of the bucket in its low bits, which must both be random.
+///
+/// This isn't the `NoHashHasher` of nohash-hasher, with an
+/// `IsEnabled` implementation for ids, as this hasher only accepts
+/// integers and panics on the 16 bytes written by the `Hash` of ids,
+/// which is the one of their UUID.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IdHasher(u64);
+
+impl Hasher for IdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        match bytes.len().checked_sub(8) {
+            Some(start) => {
+                let mut last = [0; 8];
+                last.copy_from_slice(&bytes[start..]);
+                self.0 = u64::from_be_bytes(last).rotate_left(32);
+            }
+            None => {
+                for &b in bytes {
+                    self.0 = self.0 << 8 | b as u64;
+                }
+            }
+        }
+    }
+}
+
+/// The builder of [`IdHasher`], for the maps and sets of ids not
+/// declared with the aliases below
+pub type BuildIdHasher = BuildHasherDefault<IdHasher>;
+
+/// A `HashMap` keyed by ids, using their low 64 bits as hash instead of
+/// running SipHash
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let id = Id::<Customer>::random_v4();
+/// let mut names: IdHashMap<Customer, &str> = IdHashMap::default();
+/// names.insert(id, "John");
+/// assert_eq!(names[&id], "John");
+/// ```
+///
+/// The ids must not be chosen by an attacker, as colliding ids would
+/// be easy to build.
+pub type IdHashMap<O, V> = HashMap<Id<O>, V, BuildIdHasher>;
+
+/// A `HashSet` of ids, using their low 64 bits as hash instead of
+/// running SipHash
+pub type IdHashSet<O> = HashSet<Id<O>, BuildIdHasher>;
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        std::{
+            collections::hash_map::RandomState,
+            hash::{BuildHasher, Hash, Hasher},
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_id_hash() {
+        let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+        assert_eq!(BuildIdHasher::default().hash_one(id), 0x889a_419a_ab31_7ea8);
+        // the variant bits are neither in the top 7 bits nor in the low ones
+        let hashes: Vec<u64> = (0..1000)
+            .map(|_| BuildIdHasher::default().hash_one(Id::<Customer>::random_v4()))
+            .collect();
+        for bit in (0..16).chain(57..64) {
+            assert!(hashes.iter().any(|h| h >> bit & 1 == 1), "bit {bit}");
+            assert!(hashes.iter().any(|h| h >> bit & 1 == 0), "bit {bit}");
+        }
+        assert!(hashes.iter().all(|h| h >> 30 & 0b11 == 0b10));
+        // other hashers still hash the whole uuid
+        let state = RandomState::new();
+        assert_eq!(state.hash_one(id), state.hash_one(id.uuid()));
+
+        let mut hasher = IdHasher::default();
+        7_u16.hash(&mut hasher);
+        assert_eq!(
+            hasher.finish(),
+            7_u16
+                .to_ne_bytes()
+                .iter()
+                .fold(0, |h, &b| h << 8 | b as u64)
+        );
+
+        let ids: IdHashSet<Customer> = (0..1000).map(|_| Id::random_v4()).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| ids.contains(id)));
+    }
+}
@@ -0,0 +1,217 @@
+use {
+    super::*,
+    http::{
+        header::CONTENT_TYPE, request::Parts, Extensions, HeaderMap, HeaderValue, Request,
+        Response, StatusCode,
+    },
+    std::{
+        future::Future,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll},
+    },
+    tower::{Layer, Service},
+};
+
+/// Read the id of the given header, returning `None` when there's no
+/// such header
+///
+/// ```ignore
+/// let customer: Option<Id<Customer>> = header_id(request.headers(), "x-customer-id")?;
+/// ```
+pub fn header_id<O: Identifiable>(
+    headers: &HeaderMap,
+    name: &str,
+) -> Result<Option<Id<O>>, IdError> {
+    headers
+        .get(name)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| IdError::new(IdErrorKind::InvalidFormat).with_expected(O::class()))
+                .and_then(Id::from_public_id)
+        })
+        .transpose()
+}
+
+/// Write the public id in the given header, replacing any previous value
+pub fn set_header_id<O: Identifiable>(headers: &mut HeaderMap, name: &'static str, id: Id<O>) {
+    let value = HeaderValue::try_from(id.public_id()).expect("public ids are valid header values");
+    headers.insert(name, value);
+}
+
+type Insert = fn(&HeaderValue, &mut Extensions) -> Result<(), IdRejection>;
+
+fn insert<O: Identifiable + Send + Sync + 'static>(
+    value: &HeaderValue,
+    extensions: &mut Extensions,
+) -> Result<(), IdRejection> {
+    let value = value.to_str().map_err(|_| IdRejection::Invalid {
+        error: IdError::new(IdErrorKind::InvalidFormat).with_expected(O::class()),
+        expected: O::class(),
+        wrong_class: WrongClassStatus::default(),
+    })?;
+    extensions.insert(IdRejection::parse::<O>(value, WrongClassStatus::default())?);
+    Ok(())
+}
+
+/// A tower layer reading ids from request headers into the request
+/// extensions, as typed `Id<O>` values.
+///
+/// Requests with a malformed id, or an id of another class, in one of
+/// the configured headers, are rejected with a 400 JSON response, before
+/// reaching the inner service. Absent headers are ignored.
+///
+/// ```ignore
+/// let app = Router::new()
+///     .route("/invoices", get(list_invoices))
+///     .layer(IdHeaderLayer::new().header::<Customer>("x-customer-id"));
+///
+/// async fn list_invoices(Extension(customer): Extension<Id<Customer>>) {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IdHeaderLayer {
+    headers: Arc<Vec<(&'static str, Insert)>>,
+}
+
+impl IdHeaderLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the ids of the given class from the header with this name
+    pub fn header<O: Identifiable + Send + Sync + 'static>(mut self, name: &'static str) -> Self {
+        Arc::make_mut(&mut self.headers).push((name, insert::<O>));
+        self
+    }
+}
+
+impl<S> Layer<S> for IdHeaderLayer {
+    type Service = IdHeaderService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IdHeaderService {
+            inner,
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+/// The service of an [`IdHeaderLayer`]
+#[derive(Debug, Clone)]
+pub struct IdHeaderService<S> {
+    inner: S,
+    headers: Arc<Vec<(&'static str, Insert)>>,
+}
+
+impl<S> IdHeaderService<S> {
+    fn extract(&self, parts: &mut Parts) -> Result<(), IdRejection> {
+        for (name, insert) in self.headers.iter() {
+            if let Some(value) = parts.headers.get(*name) {
+                insert(value, &mut parts.extensions)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for IdHeaderService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    ResBody: From<String> + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let (mut parts, body) = request.into_parts();
+        if let Err(rejection) = self.extract(&mut parts) {
+            let mut response = Response::new(ResBody::from(rejection.body().to_string()));
+            *response.status_mut() = StatusCode::BAD_REQUEST;
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            return Box::pin(std::future::ready(Ok(response)));
+        }
+        Box::pin(self.inner.call(Request::from_parts(parts, body)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        http::{HeaderMap, Request, Response, StatusCode},
+        std::convert::Infallible,
+        tower::{service_fn, Layer, ServiceExt},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_header_id() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(header_id::<Customer>(&headers, "x-customer-id"), Ok(None));
+        let id = Id::<Customer>::random_v4();
+        set_header_id(&mut headers, "x-customer-id", id);
+        assert_eq!(header_id(&headers, "x-customer-id"), Ok(Some(id)));
+        assert_eq!(
+            header_id::<Contract>(&headers, "x-customer-id")
+                .unwrap_err()
+                .kind(),
+            IdErrorKind::WrongClass
+        );
+    }
+
+    fn call(request: Request<String>) -> Response<String> {
+        let service = IdHeaderLayer::new()
+            .header::<Customer>("x-customer-id")
+            .header::<Contract>("x-contract-id")
+            .layer(service_fn(|request: Request<String>| async move {
+                let extensions = request.extensions();
+                let customer = extensions.get::<Id<Customer>>().map(Id::public_id);
+                let contract = extensions.get::<Id<Contract>>().map(Id::public_id);
+                Ok::<_, Infallible>(Response::new(format!("{customer:?} {contract:?}")))
+            }));
+        async_std::task::block_on(service.oneshot(request)).unwrap()
+    }
+
+    #[test]
+    fn test_layer() {
+        let request = Request::builder()
+            .header("x-customer-id", format!("Cust_{ID}"))
+            .body(String::new())
+            .unwrap();
+        let response = call(request);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), &format!("Some(\"Cust_{ID}\") None"));
+
+        let request = Request::builder()
+            .header("x-customer-id", format!("Cust_{ID}"))
+            .header("x-contract-id", format!("Cust_{ID}"))
+            .body(String::new())
+            .unwrap();
+        let response = call(request);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_str(response.body()).unwrap();
+        assert_eq!(body["error"], "wrong_class");
+        assert_eq!(body["expected_prefix"], "Cont");
+    }
+}
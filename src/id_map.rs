@@ -0,0 +1,407 @@
+use {
+    super::*,
+    std::{
+        collections::{hash_map, hash_set, HashMap, HashSet},
+        fmt,
+        ops::{BitAnd, BitOr, BitXor, Deref, DerefMut, Sub},
+    },
+};
+
+/// A `HashMap` keyed by the ids of a class
+///
+/// It dereferences to the inner `HashMap`, and adds the conversions
+/// between ids and entities:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {
+///     name: String,
+/// }
+///
+/// let customers = vec![
+///     Ided::new(Id::random_v4(), Customer { name: "John".to_string() }),
+///     Ided::new(Id::random_v4(), Customer { name: "Anna".to_string() }),
+/// ];
+/// let id = customers[0].id();
+/// let customers: IdMap<Customer, Customer> = customers.into_iter().collect();
+/// assert_eq!(customers[&id].name, "John");
+/// ```
+///
+/// With the serde feature, a map is serialized as an object whose keys
+/// are the public ids, and binding a map in a sqlx query binds the array
+/// of its keys, eg for a `WHERE id = ANY($1)` clause.
+pub struct IdMap<O: Identifiable, V>(HashMap<Id<O>, V>);
+
+/// A `HashSet` of the ids of a class
+///
+/// It dereferences to the inner `HashSet`, and its set operations
+/// return id sets:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let (a, b, c) = (Id::<Customer>::random_v4(), Id::random_v4(), Id::random_v4());
+/// let left: IdSet<Customer> = [a, b].into_iter().collect();
+/// let right: IdSet<Customer> = [b, c].into_iter().collect();
+/// assert_eq!(&left & &right, [b].into_iter().collect());
+/// assert_eq!((&left | &right).len(), 3);
+/// assert_eq!(&left - &right, [a].into_iter().collect());
+/// ```
+///
+/// With the serde feature, a set is serialized as a sequence of public
+/// ids. It's bound in sqlx queries, and decoded, as a `UUID[]`.
+pub struct IdSet<O: Identifiable>(HashSet<Id<O>>);
+
+impl<O: Identifiable, V> IdMap<O, V> {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(HashMap::with_capacity(capacity))
+    }
+
+    /// Return the set of the keys
+    pub fn id_set(&self) -> IdSet<O> {
+        self.0.keys().copied().collect()
+    }
+
+    /// Return the entries as `Ided` values, in no particular order
+    pub fn into_ideds(self) -> impl Iterator<Item = Ided<O, V>> {
+        self.0.into_iter().map(|(id, value)| Ided::new(id, value))
+    }
+
+    /// Return the inner `HashMap`
+    pub fn into_inner(self) -> HashMap<Id<O>, V> {
+        self.0
+    }
+}
+
+impl<O: Identifiable> IdSet<O> {
+    pub fn new() -> Self {
+        Self(HashSet::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(HashSet::with_capacity(capacity))
+    }
+
+    /// Return the ids of both sets
+    pub fn union(&self, other: &Self) -> Self {
+        self.0.union(&other.0).copied().collect()
+    }
+
+    /// Return the ids in both sets
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.0.intersection(&other.0).copied().collect()
+    }
+
+    /// Return the ids of this set which aren't in the other one
+    pub fn difference(&self, other: &Self) -> Self {
+        self.0.difference(&other.0).copied().collect()
+    }
+
+    /// Return the ids in one of the sets but not in both
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.0.symmetric_difference(&other.0).copied().collect()
+    }
+
+    /// Return the ids as a vector, in no particular order, eg to bind
+    /// them in a query
+    pub fn to_vec(&self) -> Vec<Id<O>> {
+        self.0.iter().copied().collect()
+    }
+
+    /// Return the inner `HashSet`
+    pub fn into_inner(self) -> HashSet<Id<O>> {
+        self.0
+    }
+}
+
+impl<O: Identifiable, V> Default for IdMap<O, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Identifiable> Default for IdSet<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Identifiable, V: Clone> Clone for IdMap<O, V> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<O: Identifiable> Clone for IdSet<O> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<O: Identifiable, V: PartialEq> PartialEq for IdMap<O, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<O: Identifiable, V: Eq> Eq for IdMap<O, V> {}
+
+impl<O: Identifiable> PartialEq for IdSet<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<O: Identifiable> Eq for IdSet<O> {}
+
+impl<O: Identifiable, V: fmt::Debug> fmt::Debug for IdMap<O, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for IdSet<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<O: Identifiable, V> Deref for IdMap<O, V> {
+    type Target = HashMap<Id<O>, V>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<O: Identifiable, V> DerefMut for IdMap<O, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<O: Identifiable> Deref for IdSet<O> {
+    type Target = HashSet<Id<O>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<O: Identifiable> DerefMut for IdSet<O> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<O: Identifiable, V> From<HashMap<Id<O>, V>> for IdMap<O, V> {
+    fn from(map: HashMap<Id<O>, V>) -> Self {
+        Self(map)
+    }
+}
+
+impl<O: Identifiable> From<HashSet<Id<O>>> for IdSet<O> {
+    fn from(set: HashSet<Id<O>>) -> Self {
+        Self(set)
+    }
+}
+
+impl<O: Identifiable, V> FromIterator<(Id<O>, V)> for IdMap<O, V> {
+    fn from_iter<I: IntoIterator<Item = (Id<O>, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Entities are collected by id, the last one winning when an id is
+/// repeated
+impl<O: Identifiable, V> FromIterator<Ided<O, V>> for IdMap<O, V> {
+    fn from_iter<I: IntoIterator<Item = Ided<O, V>>>(iter: I) -> Self {
+        iter.into_iter().map(Ided::dismantle).collect()
+    }
+}
+
+impl<O: Identifiable> FromIterator<Id<O>> for IdSet<O> {
+    fn from_iter<I: IntoIterator<Item = Id<O>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<O: Identifiable, V> Extend<(Id<O>, V)> for IdMap<O, V> {
+    fn extend<I: IntoIterator<Item = (Id<O>, V)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<O: Identifiable, V> Extend<Ided<O, V>> for IdMap<O, V> {
+    fn extend<I: IntoIterator<Item = Ided<O, V>>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(Ided::dismantle))
+    }
+}
+
+impl<O: Identifiable> Extend<Id<O>> for IdSet<O> {
+    fn extend<I: IntoIterator<Item = Id<O>>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+impl<O: Identifiable, V> IntoIterator for IdMap<O, V> {
+    type Item = (Id<O>, V);
+    type IntoIter = hash_map::IntoIter<Id<O>, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'m, O: Identifiable, V> IntoIterator for &'m IdMap<O, V> {
+    type Item = (&'m Id<O>, &'m V);
+    type IntoIter = hash_map::Iter<'m, Id<O>, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'m, O: Identifiable, V> IntoIterator for &'m mut IdMap<O, V> {
+    type Item = (&'m Id<O>, &'m mut V);
+    type IntoIter = hash_map::IterMut<'m, Id<O>, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<O: Identifiable> IntoIterator for IdSet<O> {
+    type Item = Id<O>;
+    type IntoIter = hash_set::IntoIter<Id<O>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'s, O: Identifiable> IntoIterator for &'s IdSet<O> {
+    type Item = &'s Id<O>;
+    type IntoIter = hash_set::Iter<'s, Id<O>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<O: Identifiable> BitOr<&IdSet<O>> for &IdSet<O> {
+    type Output = IdSet<O>;
+    fn bitor(self, other: &IdSet<O>) -> IdSet<O> {
+        self.union(other)
+    }
+}
+
+impl<O: Identifiable> BitAnd<&IdSet<O>> for &IdSet<O> {
+    type Output = IdSet<O>;
+    fn bitand(self, other: &IdSet<O>) -> IdSet<O> {
+        self.intersection(other)
+    }
+}
+
+impl<O: Identifiable> Sub<&IdSet<O>> for &IdSet<O> {
+    type Output = IdSet<O>;
+    fn sub(self, other: &IdSet<O>) -> IdSet<O> {
+        self.difference(other)
+    }
+}
+
+impl<O: Identifiable> BitXor<&IdSet<O>> for &IdSet<O> {
+    type Output = IdSet<O>;
+    fn bitxor(self, other: &IdSet<O>) -> IdSet<O> {
+        self.symmetric_difference(other)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable, V: serde::Serialize> serde::Serialize for IdMap<O, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable, V: serde::Deserialize<'de>> serde::Deserialize<'de> for IdMap<O, V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashMap::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for IdSet<O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::Deserialize<'de> for IdSet<O> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HashSet::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_id_map() {
+        let ids = [(); 3].map(|_| Id::<Customer>::random_v4());
+        let map: IdMap<Customer, usize> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| Ided::new(id, i))
+            .collect();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[&ids[1]], 1);
+        assert_eq!(map.id_set(), ids.into_iter().collect());
+        let mut ideds: Vec<_> = map.clone().into_ideds().map(Ided::take_entity).collect();
+        ideds.sort();
+        assert_eq!(ideds, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_id_set_algebra() {
+        let [a, b, c] = [(); 3].map(|_| Id::<Customer>::random_v4());
+        let left: IdSet<Customer> = [a, b].into_iter().collect();
+        let right: IdSet<Customer> = [b, c].into_iter().collect();
+        assert_eq!(&left | &right, [a, b, c].into_iter().collect());
+        assert_eq!(&left & &right, [b].into_iter().collect());
+        assert_eq!(&left - &right, [a].into_iter().collect());
+        assert_eq!(&left ^ &right, [a, c].into_iter().collect());
+        assert!(left.contains(&a) && !left.contains(&c));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+        let map: IdMap<Customer, u32> = [(id, 42)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"Cust_371c35ec-34d9-4315-ab31-7ea8889a419a":42}"#);
+        assert_eq!(
+            serde_json::from_str::<IdMap<Customer, u32>>(&json).unwrap(),
+            map
+        );
+        let json = r#"{"Cont_371c35ec-34d9-4315-ab31-7ea8889a419a":42}"#;
+        assert!(serde_json::from_str::<IdMap<Customer, u32>>(json).is_err());
+
+        let set: IdSet<Customer> = [id].into_iter().collect();
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"["Cust_371c35ec-34d9-4315-ab31-7ea8889a419a"]"#);
+        assert_eq!(serde_json::from_str::<IdSet<Customer>>(&json).unwrap(), set);
+    }
+}
@@ -0,0 +1,264 @@
+use {
+    super::*,
+    std::{cmp::Ordering, fmt, hash::Hash, ops::Deref, str::FromStr},
+    uuid::Uuid,
+};
+
+/// A path of ids from a root, eg of a folder in a hierarchy of folders
+///
+/// Paths are ordered lexicographically, so that a sorted list of paths
+/// lists each path just before its descendants. Their public form is
+/// made of the public ids separated by slashes:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Fold")]
+/// pub struct Folder {}
+///
+/// let (root, docs) = (Id::<Folder>::random_v4(), Id::<Folder>::random_v4());
+/// let path: IdPath<Folder> = IdPath::from(vec![root, docs]);
+/// assert_eq!(path.to_string(), format!("{root}/{docs}"));
+/// assert_eq!(path.to_string().parse::<IdPath<Folder>>().unwrap(), path);
+/// assert_eq!(path.leaf(), Some(docs));
+/// assert!(path.starts_with(&path.parent().unwrap()));
+/// ```
+///
+/// With the serde feature, a path is serialized as its public form, and
+/// with the sqlx feature, it's stored in a PostgreSQL `ltree` column, as
+/// the uuids without hyphens separated by dots (see [`IdPath::ltree`]),
+/// whose ordering is the same.
+pub struct IdPath<O: Identifiable>(Vec<Id<O>>);
+
+impl<O: Identifiable> IdPath<O> {
+    /// Return the empty path
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Return the ids, from the root
+    pub fn ids(&self) -> &[Id<O>] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<Id<O>> {
+        self.0
+    }
+
+    /// Return the last id, if the path isn't empty
+    pub fn leaf(&self) -> Option<Id<O>> {
+        self.0.last().copied()
+    }
+
+    /// Return the path without its last id, if it isn't empty
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.0.split_last()?;
+        Some(Self(parent.to_vec()))
+    }
+
+    /// Return the path extended with a child id
+    pub fn child(&self, id: Id<O>) -> Self {
+        let mut path = self.clone();
+        path.push(id);
+        path
+    }
+
+    pub fn push(&mut self, id: Id<O>) {
+        self.0.push(id);
+    }
+
+    pub fn pop(&mut self) -> Option<Id<O>> {
+        self.0.pop()
+    }
+
+    /// Tell whether the given path is an ancestor of this one, or this
+    /// path itself
+    pub fn starts_with(&self, ancestor: &Self) -> bool {
+        self.0.starts_with(&ancestor.0)
+    }
+
+    /// Return the `ltree` form of the path, with the uuids without
+    /// hyphens as labels, eg `371c35ec34d94315ab317ea8889a419a.4a5c…`
+    pub fn ltree(&self) -> String {
+        let mut ltree = String::with_capacity(self.0.len() * 33);
+        let mut buffer = Uuid::encode_buffer();
+        for (i, id) in self.0.iter().enumerate() {
+            if i > 0 {
+                ltree.push('.');
+            }
+            ltree.push_str(id.uuid().simple().encode_lower(&mut buffer));
+        }
+        ltree
+    }
+
+    /// Parse the `ltree` form of a path, whose class can't be checked
+    pub fn from_ltree(ltree: &str) -> Result<Self, IdError> {
+        if ltree.is_empty() {
+            return Ok(Self::new());
+        }
+        ltree
+            .split('.')
+            .map(|label| O::class().parse_db_id(label).map(Id::unchecked))
+            .collect()
+    }
+}
+
+impl<O: Identifiable> Default for IdPath<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Identifiable> From<Vec<Id<O>>> for IdPath<O> {
+    fn from(ids: Vec<Id<O>>) -> Self {
+        Self(ids)
+    }
+}
+
+impl<O: Identifiable> FromIterator<Id<O>> for IdPath<O> {
+    fn from_iter<I: IntoIterator<Item = Id<O>>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<O: Identifiable> Deref for IdPath<O> {
+    type Target = [Id<O>];
+    fn deref(&self) -> &[Id<O>] {
+        &self.0
+    }
+}
+
+impl<O: Identifiable> Clone for IdPath<O> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<O: Identifiable> PartialEq for IdPath<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<O: Identifiable> Eq for IdPath<O> {}
+
+impl<O: Identifiable> Ord for IdPath<O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+impl<O: Identifiable> PartialOrd for IdPath<O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<O: Identifiable> Hash for IdPath<O> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<O: Identifiable> fmt::Display for IdPath<O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, id) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            fmt::Display::fmt(id, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for IdPath<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IdPath({self})")
+    }
+}
+
+/// Parse the public form of a path, the empty string being the empty path
+impl<O: Identifiable> FromStr for IdPath<O> {
+    type Err = IdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(Self::new());
+        }
+        s.split('/').map(str::parse).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for IdPath<O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::Deserialize<'de> for IdPath<O> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <std::borrow::Cow<str>>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Fold")]
+    pub struct Folder {}
+
+    #[test]
+    fn test_path() {
+        let ids: Vec<Id<Folder>> = (0..4).map(|_| Id::random_v4()).collect();
+        let path: IdPath<Folder> = ids.iter().copied().collect();
+        let text = path.to_string();
+        assert_eq!(text.split('/').count(), 4);
+        assert_eq!(text.parse::<IdPath<Folder>>().unwrap(), path);
+        assert_eq!(IdPath::<Folder>::from_ltree(&path.ltree()).unwrap(), path);
+        assert_eq!(path.ltree().len(), 4 * 33 - 1);
+        assert_eq!(path.parent().unwrap().child(ids[3]), path);
+        assert!(path.starts_with(&IdPath::new()));
+        assert!(!path.parent().unwrap().starts_with(&path));
+        assert_eq!("".parse::<IdPath<Folder>>().unwrap(), IdPath::new());
+        assert_eq!(IdPath::<Folder>::new().parent(), None);
+        assert!(format!("{}/", ids[0]).parse::<IdPath<Folder>>().is_err());
+        assert!(format!("{}/Cust_{}", ids[0], ids[1].uuid())
+            .parse::<IdPath<Folder>>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_ordering() {
+        let ids: Vec<Id<Folder>> = (0..20).map(|_| Id::random_v4()).collect();
+        let mut paths: Vec<IdPath<Folder>> = (0..40)
+            .map(|i| {
+                ids[i % 3..]
+                    .iter()
+                    .step_by(i % 5 + 1)
+                    .take(i % 4 + 1)
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        let mut by_text = paths.clone();
+        let mut by_ltree = paths.clone();
+        paths.sort();
+        by_text.sort_by_key(|path| path.to_string());
+        by_ltree.sort_by_key(|path| path.ltree());
+        assert_eq!(paths, by_text);
+        assert_eq!(paths, by_ltree);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let path: IdPath<Folder> = (0..2).map(|_| Id::random_v4()).collect();
+        let json = serde_json::to_string(&path).unwrap();
+        assert_eq!(json, format!("\"{path}\""));
+        assert_eq!(serde_json::from_str::<IdPath<Folder>>(&json).unwrap(), path);
+    }
+}
@@ -0,0 +1,94 @@
+//! Conversions between slices or vectors of ids and of uuids, without
+//! copy, eg to bind a large array of ids in a query of a library only
+//! knowing uuids.
+//!
+//! ```
+//! use {kind::*, uuid::Uuid};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let ids = vec![Id::<Customer>::random_v4(), Id::random_v4()];
+//! let uuids: &[Uuid] = ids_as_uuids(&ids);
+//! assert_eq!(uuids[1], ids[1].uuid());
+//! let uuids: Vec<Uuid> = ids_into_uuids(ids);
+//! let ids: Vec<Id<Customer>> = uuids_into_ids(uuids);
+//! ```
+//!
+//! Like `Id::from(uuid)`, the conversions from uuids don't check the
+//! class of the ids.
+
+use {
+    super::*,
+    std::mem::{self, ManuallyDrop},
+    uuid::Uuid,
+};
+
+/// Check that `Id<O>` and `Uuid` have the same layout, which
+/// `#[repr(transparent)]` guarantees (the check is optimized out)
+fn assert_same_layout<O: Identifiable>() {
+    assert_eq!(mem::size_of::<Id<O>>(), mem::size_of::<Uuid>());
+    assert_eq!(mem::align_of::<Id<O>>(), mem::align_of::<Uuid>());
+}
+
+/// See a slice of ids as the slice of their uuids
+pub fn ids_as_uuids<O: Identifiable>(ids: &[Id<O>]) -> &[Uuid] {
+    assert_same_layout::<O>();
+    // SAFETY: Id<O> is a repr(transparent) wrapper of Uuid
+    unsafe { std::slice::from_raw_parts(ids.as_ptr().cast(), ids.len()) }
+}
+
+/// See a slice of uuids as a slice of ids, without checking the class
+pub fn uuids_as_ids<O: Identifiable>(uuids: &[Uuid]) -> &[Id<O>] {
+    assert_same_layout::<O>();
+    // SAFETY: Id<O> is a repr(transparent) wrapper of Uuid
+    unsafe { std::slice::from_raw_parts(uuids.as_ptr().cast(), uuids.len()) }
+}
+
+/// Convert a vector of ids into the vector of their uuids, reusing
+/// its allocation
+pub fn ids_into_uuids<O: Identifiable>(ids: Vec<Id<O>>) -> Vec<Uuid> {
+    assert_same_layout::<O>();
+    let mut ids = ManuallyDrop::new(ids);
+    // SAFETY: Id<O> is a repr(transparent) wrapper of Uuid, so the
+    // allocation has the right size and alignment for uuids
+    unsafe { Vec::from_raw_parts(ids.as_mut_ptr().cast(), ids.len(), ids.capacity()) }
+}
+
+/// Convert a vector of uuids into a vector of ids, reusing its
+/// allocation, without checking the class
+pub fn uuids_into_ids<O: Identifiable>(uuids: Vec<Uuid>) -> Vec<Id<O>> {
+    assert_same_layout::<O>();
+    let mut uuids = ManuallyDrop::new(uuids);
+    // SAFETY: Id<O> is a repr(transparent) wrapper of Uuid, so the
+    // allocation has the right size and alignment for ids
+    unsafe { Vec::from_raw_parts(uuids.as_mut_ptr().cast(), uuids.len(), uuids.capacity()) }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_id_slices() {
+        let ids: Vec<Id<Customer>> = (0..100).map(|_| Id::random_v4()).collect();
+        let uuids = ids_as_uuids(&ids);
+        assert_eq!(uuids.len(), 100);
+        assert!(ids.iter().zip(uuids).all(|(id, uuid)| id.uuid() == *uuid));
+        assert_eq!(uuids_as_ids::<Customer>(uuids), &ids[..]);
+
+        let mut ids_with_capacity = Vec::with_capacity(200);
+        ids_with_capacity.extend_from_slice(&ids);
+        let pointer = ids_with_capacity.as_ptr() as usize;
+        let uuids = ids_into_uuids(ids_with_capacity);
+        assert_eq!((uuids.as_ptr() as usize, uuids.capacity()), (pointer, 200));
+        let ids_again: Vec<Id<Customer>> = uuids_into_ids(uuids);
+        assert_eq!(ids_again, ids);
+        assert!(ids_as_uuids::<Customer>(&[]).is_empty());
+    }
+}
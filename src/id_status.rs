@@ -0,0 +1,319 @@
+use {
+    super::*,
+    std::{collections::HashMap, convert::Infallible, fmt, time::SystemTime},
+};
+
+/// The status of an id, for APIs telling that an object was deleted or
+/// merged into another one, eg when duplicate customers are merged
+///
+/// ```
+/// use {kind::*, std::collections::HashMap};
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let (old, duplicate, kept) = (Id::random_v4(), Id::random_v4(), Id::random_v4());
+/// let statuses: HashMap<Id<Customer>, IdStatus<Customer>> = [
+///     (old, IdStatus::Merged { into: duplicate }),
+///     (duplicate, IdStatus::Merged { into: kept }),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(statuses.resolve(old), Ok(kept));
+/// ```
+///
+/// With the serde feature, it's serialized as an object tagged by its
+/// `status`, eg `{"status":"merged","into":"Cust_371c…"}`, the time of a
+/// deletion being in seconds since the Unix epoch.
+pub enum IdStatus<O: Identifiable> {
+    Active,
+    Deleted { at: SystemTime },
+    Merged { into: Id<O> },
+}
+
+impl<O: Identifiable> IdStatus<O> {
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Active)
+    }
+    /// Return the id the object was merged into, if it was merged
+    pub fn merged_into(&self) -> Option<Id<O>> {
+        match self {
+            Self::Merged { into } => Some(*into),
+            _ => None,
+        }
+    }
+}
+
+impl<O: Identifiable> Clone for IdStatus<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O: Identifiable> Copy for IdStatus<O> {}
+
+impl<O: Identifiable> PartialEq for IdStatus<O> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Active, Self::Active) => true,
+            (Self::Deleted { at }, Self::Deleted { at: other }) => at == other,
+            (Self::Merged { into }, Self::Merged { into: other }) => into == other,
+            _ => false,
+        }
+    }
+}
+impl<O: Identifiable> Eq for IdStatus<O> {}
+
+impl<O: Identifiable> fmt::Debug for IdStatus<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Active => f.write_str("Active"),
+            Self::Deleted { at } => f.debug_struct("Deleted").field("at", at).finish(),
+            Self::Merged { into } => f.debug_struct("Merged").field("into", into).finish(),
+        }
+    }
+}
+
+/// The maximal number of merges followed by [`Resolver::resolve`]
+pub const MAX_MERGE_REDIRECTS: usize = 32;
+
+/// An error returned when resolving an id
+pub enum ResolveError<O: Identifiable, E> {
+    /// The object, or the one it was merged into, was deleted
+    Deleted { id: Id<O>, at: SystemTime },
+    /// The merges loop back to this id
+    Loop { id: Id<O> },
+    /// There are more than [`MAX_MERGE_REDIRECTS`] merges
+    TooManyRedirects { id: Id<O> },
+    /// The resolver failed
+    Resolver(E),
+}
+
+impl<O: Identifiable, E: Clone> Clone for ResolveError<O, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Deleted { id, at } => Self::Deleted { id: *id, at: *at },
+            Self::Loop { id } => Self::Loop { id: *id },
+            Self::TooManyRedirects { id } => Self::TooManyRedirects { id: *id },
+            Self::Resolver(e) => Self::Resolver(e.clone()),
+        }
+    }
+}
+
+impl<O: Identifiable, E: PartialEq> PartialEq for ResolveError<O, E> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Deleted { id, at }, Self::Deleted { id: i, at: a }) => id == i && at == a,
+            (Self::Loop { id }, Self::Loop { id: other }) => id == other,
+            (Self::TooManyRedirects { id }, Self::TooManyRedirects { id: other }) => id == other,
+            (Self::Resolver(e), Self::Resolver(other)) => e == other,
+            _ => false,
+        }
+    }
+}
+impl<O: Identifiable, E: Eq> Eq for ResolveError<O, E> {}
+
+impl<O: Identifiable, E: fmt::Debug> fmt::Debug for ResolveError<O, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deleted { id, at } => f
+                .debug_struct("Deleted")
+                .field("id", id)
+                .field("at", at)
+                .finish(),
+            Self::Loop { id } => f.debug_struct("Loop").field("id", id).finish(),
+            Self::TooManyRedirects { id } => {
+                f.debug_struct("TooManyRedirects").field("id", id).finish()
+            }
+            Self::Resolver(e) => f.debug_tuple("Resolver").field(e).finish(),
+        }
+    }
+}
+
+impl<O: Identifiable, E: fmt::Display> fmt::Display for ResolveError<O, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deleted { id, .. } => write!(f, "{id} was deleted"),
+            Self::Loop { id } => write!(f, "the merges of {id} loop"),
+            Self::TooManyRedirects { id } => {
+                write!(f, "more than {MAX_MERGE_REDIRECTS} merges from {id}")
+            }
+            Self::Resolver(e) => write!(f, "resolver error: {e}"),
+        }
+    }
+}
+
+impl<O: Identifiable, E: std::error::Error + 'static> std::error::Error for ResolveError<O, E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Resolver(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A source of the statuses of ids, eg a table of merges
+pub trait Resolver<O: Identifiable> {
+    type Error;
+
+    /// Return the status of an id
+    fn status(&self, id: Id<O>) -> Result<IdStatus<O>, Self::Error>;
+
+    /// Follow the merges of an id up to the active id, failing when it
+    /// was deleted, or when the merges loop
+    fn resolve(&self, id: Id<O>) -> Result<Id<O>, ResolveError<O, Self::Error>> {
+        let mut visited = vec![id];
+        let mut current = id;
+        loop {
+            match self.status(current).map_err(ResolveError::Resolver)? {
+                IdStatus::Active => return Ok(current),
+                IdStatus::Deleted { at } => return Err(ResolveError::Deleted { id: current, at }),
+                IdStatus::Merged { into } => {
+                    if visited.contains(&into) {
+                        return Err(ResolveError::Loop { id: into });
+                    }
+                    if visited.len() > MAX_MERGE_REDIRECTS {
+                        return Err(ResolveError::TooManyRedirects { id });
+                    }
+                    visited.push(into);
+                    current = into;
+                }
+            }
+        }
+    }
+}
+
+/// The ids missing from the map are active
+impl<O: Identifiable, S: std::hash::BuildHasher> Resolver<O> for HashMap<Id<O>, IdStatus<O>, S> {
+    type Error = Infallible;
+    fn status(&self, id: Id<O>) -> Result<IdStatus<O>, Infallible> {
+        Ok(self.get(&id).copied().unwrap_or(IdStatus::Active))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase", bound = "")]
+enum IdStatusRepr<O: Identifiable> {
+    Active,
+    Deleted { at: u64 },
+    Merged { into: Id<O> },
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for IdStatus<O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = match *self {
+            Self::Active => IdStatusRepr::Active,
+            Self::Deleted { at } => IdStatusRepr::Deleted {
+                at: at
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs()),
+            },
+            Self::Merged { into } => IdStatusRepr::Merged { into },
+        };
+        repr.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::Deserialize<'de> for IdStatus<O> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match IdStatusRepr::deserialize(deserializer)? {
+            IdStatusRepr::Active => Self::Active,
+            IdStatusRepr::Deleted { at } => Self::Deleted {
+                at: std::time::UNIX_EPOCH
+                    .checked_add(std::time::Duration::from_secs(at))
+                    .ok_or_else(|| {
+                        serde::de::Error::custom(format!("deletion time out of range: {at}"))
+                    })?,
+            },
+            IdStatusRepr::Merged { into } => Self::Merged { into },
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        std::{
+            collections::HashMap,
+            time::{Duration, UNIX_EPOCH},
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_resolve() {
+        let [a, b, c, d] = [(); 4].map(|_| Id::<Customer>::random_v4());
+        let at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut statuses: HashMap<_, _> = [
+            (a, IdStatus::Merged { into: b }),
+            (b, IdStatus::Merged { into: c }),
+            (d, IdStatus::Deleted { at }),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(statuses.resolve(a), Ok(c));
+        assert_eq!(statuses.resolve(c), Ok(c));
+        assert_eq!(
+            statuses.resolve(d),
+            Err(ResolveError::Deleted { id: d, at })
+        );
+        statuses.insert(c, IdStatus::Merged { into: d });
+        assert_eq!(
+            statuses.resolve(a),
+            Err(ResolveError::Deleted { id: d, at })
+        );
+        statuses.insert(c, IdStatus::Merged { into: a });
+        assert_eq!(statuses.resolve(b), Err(ResolveError::Loop { id: b }));
+
+        let chain: Vec<Id<Customer>> = (0..=MAX_MERGE_REDIRECTS + 1)
+            .map(|_| Id::random_v4())
+            .collect();
+        let statuses: HashMap<_, _> = chain
+            .windows(2)
+            .map(|w| (w[0], IdStatus::Merged { into: w[1] }))
+            .collect();
+        assert_eq!(statuses.resolve(chain[1]), Ok(chain[chain.len() - 1]));
+        assert_eq!(
+            statuses.resolve(chain[0]),
+            Err(ResolveError::TooManyRedirects { id: chain[0] })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+        for (status, json) in [
+            (IdStatus::Active, r#"{"status":"active"}"#.to_string()),
+            (
+                IdStatus::Deleted {
+                    at: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                },
+                r#"{"status":"deleted","at":1700000000}"#.to_string(),
+            ),
+            (
+                IdStatus::Merged { into: id },
+                format!(r#"{{"status":"merged","into":"{id}"}}"#),
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&status).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<IdStatus<Customer>>(&json).unwrap(),
+                status
+            );
+        }
+        let error = serde_json::from_str::<IdStatus<Customer>>(&format!(
+            r#"{{"status":"deleted","at":{}}}"#,
+            u64::MAX
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("out of range"), "{error}");
+    }
+}
@@ -0,0 +1,82 @@
+use {super::*, std::borrow::Cow, validator::ValidationError};
+
+/// Check that an id isn't nil, for `#[validate(custom)]` attributes of
+/// the validator crate (the class is already checked by the type):
+///
+/// ```ignore
+/// #[derive(Deserialize, Validate)]
+/// pub struct NewInvoice {
+///     #[validate(custom(function = "kind::validate_id"))]
+///     customer: Id<Customer>,
+/// }
+/// ```
+pub fn validate_id<O: Identifiable>(id: &Id<O>) -> Result<(), ValidationError> {
+    if id.uuid.is_nil() {
+        return Err(ValidationError::new("nil_id").with_message(Cow::Borrowed("nil id")));
+    }
+    Ok(())
+}
+
+/// Check that a string is the public id of an object of the class, and
+/// not the nil id, for DTOs keeping ids as strings:
+///
+/// ```ignore
+/// #[derive(Deserialize, Validate)]
+/// pub struct NewInvoice {
+///     #[validate(custom(function = "kind::validate_public_id::<Customer>"))]
+///     customer_id: String,
+/// }
+/// ```
+pub fn validate_public_id<O: Identifiable>(public_id: &str) -> Result<(), ValidationError> {
+    let id = Id::<O>::from_public_id(public_id).map_err(|e| {
+        let code = e.kind().as_str();
+        let mut error = ValidationError::new(code).with_message(Cow::Owned(e.to_string()));
+        error.add_param(Cow::Borrowed("expected_prefix"), &O::class().prefix());
+        error
+    })?;
+    validate_id(&id)
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, validator::Validate};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Validate)]
+    struct NewInvoice {
+        #[validate(custom(function = "validate_id"))]
+        customer: Id<Customer>,
+        #[validate(custom(function = "validate_public_id::<Customer>"))]
+        payer_id: String,
+        #[validate(length(min = 1))]
+        label: String,
+    }
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_validate() {
+        let customer: Id<Customer> = format!("Cust_{ID}").parse().unwrap();
+        let invoice = NewInvoice {
+            customer,
+            payer_id: customer.public_id(),
+            label: "March".to_string(),
+        };
+        assert!(invoice.validate().is_ok());
+
+        let invoice = NewInvoice {
+            customer: Id::from_db_id("00000000-0000-0000-0000-000000000000").unwrap(),
+            payer_id: format!("Cont_{ID}"),
+            label: String::new(),
+        };
+        let errors = invoice.validate().unwrap_err();
+        let fields = errors.field_errors();
+        assert_eq!(fields["customer"][0].code, "nil_id");
+        assert_eq!(fields["payer_id"][0].code, "wrong_class");
+        assert_eq!(fields["payer_id"][0].params["expected_prefix"], "Cust");
+        assert_eq!(fields["label"][0].code, "length");
+    }
+}
@@ -15,11 +15,16 @@ use {
 ///
 /// Ordering implementation of the Ided is based on the
 /// ordering of the wrapped entity.
+///
+/// When serialized, the id is written next to the fields of the entity,
+/// with the `Identifiable::ID_FIELD` key ("id" by default).
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 pub struct Ided<T: Identifiable, E = T> {
     id: Id<T>,
-    #[cfg_attr(feature = "serde", serde(flatten))]
     entity: E,
 }
 
@@ -55,6 +60,19 @@ impl<T: Identifiable, E> Ided<T, E> {
     }
 }
 
+#[cfg(feature = "rkyv")]
+impl<T: Identifiable, E: rkyv::Archive> ArchivedIded<T, E> {
+    /// Return the archived id
+    pub fn id(&self) -> Id<T> {
+        self.id.id()
+    }
+
+    /// Return a reference to the archived entity
+    pub fn entity(&self) -> &rkyv::Archived<E> {
+        &self.entity
+    }
+}
+
 #[cfg(feature = "sqlx")]
 impl<'e, T, E> sqlx::FromRow<'e, sqlx::postgres::PgRow> for Ided<T, E>
 where
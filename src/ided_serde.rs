@@ -0,0 +1,860 @@
+//! Serialization and deserialization of `Ided`
+//!
+//! An `Ided` is serialized as the fields of the entity, with the id next
+//! to them, under the `Identifiable::ID_FIELD` key.
+//!
+//! Instead of buffering the whole map as `#[serde(flatten)]` would do,
+//! the entity is deserialized with a deserializer which removes the id
+//! entry (or one of the `Identifiable::ID_ALIASES`) on the fly. This way
+//! - errors on the id are reported with the path of the `id` field
+//! - the `deny_unknown_fields` attribute of the entity is honored
+//! - the entity can borrow from the input (eg `&'de str` fields)
+
+use {
+    super::*,
+    serde::{
+        de::{
+            self,
+            value::{BorrowedStrDeserializer, StringDeserializer},
+            DeserializeSeed, EnumAccess, IgnoredAny, MapAccess, VariantAccess, Visitor,
+        },
+        forward_to_deserialize_any,
+        ser::{self, Impossible, SerializeMap, SerializeStruct},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    std::{fmt, marker::PhantomData},
+};
+
+impl<T: Identifiable, E: Serialize> Serialize for Ided<T, E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Some formats, like MessagePack, need the length of the map
+        // to be known beforehand, so the entries are counted first
+        let mut counter = EntryCounter(1);
+        let len = self
+            .entity()
+            .serialize(FlatMapSerializer(&mut counter))
+            .ok()
+            .map(|_| counter.0);
+        let mut map = serializer.serialize_map(len)?;
+        map.serialize_entry(T::ID_FIELD, &self.id())?;
+        self.entity().serialize(FlatMapSerializer(&mut map))?;
+        map.end()
+    }
+}
+
+/// A map only counting its entries
+struct EntryCounter(usize);
+
+impl SerializeMap for EntryCounter {
+    type Ok = ();
+    type Error = de::value::Error;
+    fn serialize_key<K: Serialize + ?Sized>(&mut self, _: &K) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+    fn serialize_value<V: Serialize + ?Sized>(&mut self, _: &V) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    fn end(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Where the fields of a flattened entity are written
+trait FlatTarget {
+    type Error: ser::Error;
+    fn field<V: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &V,
+    ) -> Result<(), Self::Error>;
+    fn key<K: Serialize + ?Sized>(&mut self, key: &K) -> Result<(), Self::Error>;
+    fn value<V: Serialize + ?Sized>(&mut self, value: &V) -> Result<(), Self::Error>;
+}
+
+impl<M: SerializeMap> FlatTarget for M {
+    type Error = M::Error;
+    fn field<V: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &V,
+    ) -> Result<(), M::Error> {
+        self.serialize_entry(key, value)
+    }
+    fn key<K: Serialize + ?Sized>(&mut self, key: &K) -> Result<(), M::Error> {
+        self.serialize_key(key)
+    }
+    fn value<V: Serialize + ?Sized>(&mut self, value: &V) -> Result<(), M::Error> {
+        self.serialize_value(value)
+    }
+}
+
+/// An already started struct, whose field names must be static, so that
+/// the entries of a map can't be written in it
+struct StructTarget<'a, S>(&'a mut S);
+
+impl<S: SerializeStruct> FlatTarget for StructTarget<'_, S> {
+    type Error = S::Error;
+    fn field<V: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &V,
+    ) -> Result<(), S::Error> {
+        self.0.serialize_field(key, value)
+    }
+    fn key<K: Serialize + ?Sized>(&mut self, _: &K) -> Result<(), S::Error> {
+        Err(ser::Error::custom(
+            "the entity of an IdedFlat must be a struct",
+        ))
+    }
+    fn value<V: Serialize + ?Sized>(&mut self, _: &V) -> Result<(), S::Error> {
+        Err(ser::Error::custom(
+            "the entity of an IdedFlat must be a struct",
+        ))
+    }
+}
+
+/// Serializer writing the fields of a struct, or the entries of
+/// a map, into an already started map or struct
+struct FlatMapSerializer<'a, M>(&'a mut M);
+
+fn not_flattenable<E: ser::Error>() -> Result<(), E> {
+    Err(E::custom("the entity of an Ided must be a struct or a map"))
+}
+
+macro_rules! not_flattenable {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<(), M::Error> {
+                not_flattenable()
+            }
+        )*
+    };
+}
+
+impl<'a, M: FlatTarget> Serializer for FlatMapSerializer<'a, M> {
+    type Ok = ();
+    type Error = M::Error;
+    type SerializeSeq = Impossible<(), M::Error>;
+    type SerializeTuple = Impossible<(), M::Error>;
+    type SerializeTupleStruct = Impossible<(), M::Error>;
+    type SerializeTupleVariant = Impossible<(), M::Error>;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), M::Error>;
+
+    not_flattenable! {
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+    // an entity without any field
+    fn serialize_none(self) -> Result<(), M::Error> {
+        Ok(())
+    }
+    fn serialize_unit(self) -> Result<(), M::Error> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), M::Error> {
+        Ok(())
+    }
+    fn serialize_some<V: Serialize + ?Sized>(self, value: &V) -> Result<(), M::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_struct<V: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &V,
+    ) -> Result<(), M::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<V: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &V,
+    ) -> Result<(), M::Error> {
+        not_flattenable()
+    }
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, M::Error> {
+        not_flattenable().map(|_| unreachable!())
+    }
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, M::Error> {
+        not_flattenable().map(|_| unreachable!())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, M::Error> {
+        not_flattenable().map(|_| unreachable!())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, M::Error> {
+        not_flattenable().map(|_| unreachable!())
+    }
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, M::Error> {
+        Ok(self)
+    }
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, M::Error> {
+        Ok(self)
+    }
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, M::Error> {
+        not_flattenable().map(|_| unreachable!())
+    }
+}
+
+impl<'a, M: FlatTarget> SerializeStruct for FlatMapSerializer<'a, M> {
+    type Ok = ();
+    type Error = M::Error;
+    fn serialize_field<V: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &V,
+    ) -> Result<(), M::Error> {
+        self.0.field(key, value)
+    }
+    fn end(self) -> Result<(), M::Error> {
+        Ok(())
+    }
+}
+
+impl<'a, M: FlatTarget> SerializeMap for FlatMapSerializer<'a, M> {
+    type Ok = ();
+    type Error = M::Error;
+    fn serialize_key<K: Serialize + ?Sized>(&mut self, key: &K) -> Result<(), M::Error> {
+        self.0.key(key)
+    }
+    fn serialize_value<V: Serialize + ?Sized>(&mut self, value: &V) -> Result<(), M::Error> {
+        self.0.value(value)
+    }
+    fn end(self) -> Result<(), M::Error> {
+        Ok(())
+    }
+}
+
+/// An `Ided` serialized as a struct, whose fields are the id and the
+/// fields of the entity, for the formats not supporting maps, like CSV
+///
+/// The entity must be a struct. It's deserialized like an `Ided`.
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable, serde::Serialize, serde::Deserialize)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {
+///     name: String,
+/// }
+///
+/// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// let customer = Ided::new(id, Customer { name: "John".to_string() });
+/// let mut writer = csv::Writer::from_writer(vec![]);
+/// writer.serialize(IdedFlat(customer)).unwrap();
+/// assert_eq!(
+///     String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+///     "id,name\nCust_371c35ec-34d9-4315-ab31-7ea8889a419a,John\n",
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct IdedFlat<T: Identifiable, E = T>(pub Ided<T, E>);
+
+impl<T: Identifiable, E> IdedFlat<T, E> {
+    pub fn into_inner(self) -> Ided<T, E> {
+        self.0
+    }
+}
+
+impl<T: Identifiable, E> From<Ided<T, E>> for IdedFlat<T, E> {
+    fn from(ided: Ided<T, E>) -> Self {
+        Self(ided)
+    }
+}
+
+impl<T: Identifiable, E> std::ops::Deref for IdedFlat<T, E> {
+    type Target = Ided<T, E>;
+    fn deref(&self) -> &Ided<T, E> {
+        &self.0
+    }
+}
+
+impl<T: Identifiable, E: Serialize> Serialize for IdedFlat<T, E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut counter = EntryCounter(1);
+        self.0
+            .entity()
+            .serialize(FlatMapSerializer(&mut counter))
+            .ok();
+        let mut fields = serializer.serialize_struct("Ided", counter.0)?;
+        fields.serialize_field(T::ID_FIELD, &self.0.id())?;
+        self.0
+            .entity()
+            .serialize(FlatMapSerializer(&mut StructTarget(&mut fields)))?;
+        fields.end()
+    }
+}
+
+impl<'de, T: Identifiable, E: Deserialize<'de>> Deserialize<'de> for IdedFlat<T, E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ided::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<'de, T: Identifiable, E: Deserialize<'de>> Deserialize<'de> for Ided<T, E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut id = None;
+        let entity = E::deserialize(EntityDeserializer {
+            inner: deserializer,
+            id: &mut id,
+        })?;
+        let id = id.ok_or_else(|| de::Error::missing_field(T::ID_FIELD))?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+/// The deserializer given to the entity, reading a map
+///
+/// Like the deserializer of `#[serde(flatten)]` fields, it also accepts
+/// unit structs (ignoring the other entries), newtype structs (wrapping a
+/// map) and externally tagged enums (whose variant is an entry of the map).
+struct EntityDeserializer<'a, D, T: Identifiable> {
+    inner: D,
+    id: &'a mut Option<Id<T>>,
+}
+
+impl<'de, 'a, D, T> Deserializer<'de> for EntityDeserializer<'a, D, T>
+where
+    D: Deserializer<'de>,
+    T: Identifiable,
+{
+    type Error = D::Error;
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_map(EntityVisitor {
+            inner: visitor,
+            id: self.id,
+        })
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_map(UnitVisitor {
+            inner: visitor,
+            id: self.id,
+        })
+    }
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.deserialize_map(EnumVisitor {
+            inner: visitor,
+            name,
+            variants,
+            id: self.id,
+        })
+    }
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+/// Skip the remaining entries of the map
+fn drain<'de, A: MapAccess<'de>>(map: &mut A) -> Result<(), A::Error> {
+    while map.next_key::<IgnoredAny>()?.is_some() {
+        map.next_value::<IgnoredAny>()?;
+    }
+    Ok(())
+}
+
+/// Read the id of a unit struct, ignoring the other entries
+struct UnitVisitor<'a, V, T: Identifiable> {
+    inner: V,
+    id: &'a mut Option<Id<T>>,
+}
+
+impl<'de, 'a, V, T> Visitor<'de> for UnitVisitor<'a, V, T>
+where
+    V: Visitor<'de>,
+    T: Identifiable,
+{
+    type Value = V::Value;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        drain(&mut EntityMapAccess {
+            inner: map,
+            id: self.id,
+        })?;
+        self.inner.visit_unit()
+    }
+}
+
+/// Read an externally tagged enum from the first entry whose key is
+/// one of its variants, ignoring the other entries
+struct EnumVisitor<'a, V, T: Identifiable> {
+    inner: V,
+    name: &'static str,
+    variants: &'static [&'static str],
+    id: &'a mut Option<Id<T>>,
+}
+
+impl<'de, 'a, V, T> Visitor<'de> for EnumVisitor<'a, V, T>
+where
+    V: Visitor<'de>,
+    T: Identifiable,
+{
+    type Value = V::Value;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let mut map = EntityMapAccess {
+            inner: map,
+            id: self.id,
+        };
+        while let Some(key) = map.next_key::<Key<'de>>()? {
+            if self.variants.contains(&key.as_str()) {
+                let value = self.inner.visit_enum(VariantEntry { key, map: &mut map })?;
+                drain(&mut map)?;
+                return Ok(value);
+            }
+            map.next_value::<IgnoredAny>()?;
+        }
+        Err(de::Error::custom(format_args!(
+            "no variant of enum {} found in flattened data",
+            self.name
+        )))
+    }
+}
+
+/// The entry of the map holding the variant of an enum
+struct VariantEntry<'de, 'm, M> {
+    key: Key<'de>,
+    map: &'m mut M,
+}
+
+impl<'de, 'm, M: MapAccess<'de>> EnumAccess<'de> for VariantEntry<'de, 'm, M> {
+    type Error = M::Error;
+    type Variant = Self;
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self), Self::Error> {
+        let variant = match &self.key {
+            Key::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Key::Owned(key) => seed.deserialize(StringDeserializer::new(key.clone())),
+        }?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'm, M: MapAccess<'de>> VariantAccess<'de> for VariantEntry<'de, 'm, M> {
+    type Error = M::Error;
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.map.next_value()
+    }
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.map.next_value_seed(seed)
+    }
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.map.next_value_seed(TupleSeed(len, visitor))
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.map.next_value_seed(StructSeed(fields, visitor))
+    }
+}
+
+struct TupleSeed<V>(usize, V);
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for TupleSeed<V> {
+    type Value = V::Value;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        deserializer.deserialize_tuple(self.0, self.1)
+    }
+}
+
+struct StructSeed<V>(&'static [&'static str], V);
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for StructSeed<V> {
+    type Value = V::Value;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        deserializer.deserialize_struct("", self.0, self.1)
+    }
+}
+
+struct EntityVisitor<'a, V, T: Identifiable> {
+    inner: V,
+    id: &'a mut Option<Id<T>>,
+}
+
+impl<'de, 'a, V, T> Visitor<'de> for EntityVisitor<'a, V, T>
+where
+    V: Visitor<'de>,
+    T: Identifiable,
+{
+    type Value = V::Value;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_map(EntityMapAccess {
+            inner: map,
+            id: self.id,
+        })
+    }
+}
+
+/// The map seen by the entity, without the id entry
+struct EntityMapAccess<'a, A, T: Identifiable> {
+    inner: A,
+    id: &'a mut Option<Id<T>>,
+}
+
+impl<'de, 'a, A, T> MapAccess<'de> for EntityMapAccess<'a, A, T>
+where
+    A: MapAccess<'de>,
+    T: Identifiable,
+{
+    type Error = A::Error;
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        while let Some(key) = self.inner.next_key::<Key<'de>>()? {
+            let key_str = key.as_str();
+            if key_str == T::ID_FIELD || T::ID_ALIASES.contains(&key_str) {
+                if self.id.is_some() {
+                    return Err(de::Error::duplicate_field(T::ID_FIELD));
+                }
+                *self.id = Some(self.inner.next_value_seed(IdField(PhantomData))?);
+                continue;
+            }
+            let key = match key {
+                Key::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+                Key::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+            }?;
+            return Ok(Some(key));
+        }
+        Ok(None)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.next_value_seed(seed)
+    }
+}
+
+/// A map key, borrowed from the input when possible
+enum Key<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+impl Key<'_> {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Borrowed(s) => s,
+            Self::Owned(s) => s,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key<'de> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = Key<'de>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a field name")
+            }
+            fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+                Ok(Key::Borrowed(v))
+            }
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Key::Owned(v.to_string()))
+            }
+            fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(Key::Owned(v))
+            }
+            // eg the headers of a CSV file
+            fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+                std::str::from_utf8(v)
+                    .map(Key::Borrowed)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Bytes(v), &self))
+            }
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                std::str::from_utf8(v)
+                    .map(|v| Key::Owned(v.to_string()))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Bytes(v), &self))
+            }
+        }
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+/// The value of the id entry, whose errors mention the field
+struct IdField<T: Identifiable>(PhantomData<T>);
+
+impl<'de, T: Identifiable> DeserializeSeed<'de> for IdField<T> {
+    type Value = Id<T>;
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Id<T>, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, T: Identifiable> Visitor<'de> for IdField<T> {
+    type Value = Id<T>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a {} public id in field `{}`", T::class(), T::ID_FIELD)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Id::from_public_id(v).map_err(|e| match e.kind() {
+            IdErrorKind::WrongClass => {
+                let found = v.split('_').next().unwrap_or_default();
+                E::custom(format_args!(
+                    "{}: {e} (expected {}_, found {found}_)",
+                    T::ID_FIELD,
+                    T::class(),
+                ))
+            }
+            _ => E::custom(format_args!("{}: {e}", T::ID_FIELD)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{IdClass, Ided, Identifiable};
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    const ID: &str = "86261271-0fc7-46d3-81c6-0b0158628331";
+
+    #[derive(Debug, Identifiable, Deserialize)]
+    #[kind(class = "Cust")]
+    struct Customer {
+        name: String,
+    }
+
+    #[derive(Debug, Identifiable, Deserialize)]
+    #[kind(class = "Strict")]
+    #[serde(deny_unknown_fields)]
+    struct Strict {
+        name: String,
+    }
+
+    #[rstest]
+    pub fn test_id_errors() {
+        let json = format!(r#"{{"name": "John", "id": "Con_{ID}"}}"#);
+        let err = serde_json::from_str::<Ided<Customer>>(&json).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "id: wrong object class (expected Cust_, found Con_) at line 1 column 65",
+        );
+
+        let json = r#"{"name": "John", "id": "Cust_42"}"#;
+        let err = serde_json::from_str::<Ided<Customer>>(json).unwrap_err();
+        assert!(err.to_string().starts_with("id: invalid format for id"));
+
+        let json = r#"{"name": "John"}"#;
+        let err = serde_json::from_str::<Ided<Customer>>(json).unwrap_err();
+        assert!(err.to_string().starts_with("missing field `id`"));
+
+        let json = format!(r#"{{"id": "Cust_{ID}", "name": "John", "id": "Cust_{ID}"}}"#);
+        let err = serde_json::from_str::<Ided<Customer>>(&json).unwrap_err();
+        assert!(err.to_string().starts_with("duplicate field `id`"));
+    }
+
+    #[rstest]
+    pub fn test_unknown_fields() {
+        let json = format!(r#"{{"id": "Cust_{ID}", "name": "John", "age": 42}}"#);
+        let customer: Ided<Customer> = serde_json::from_str(&json).unwrap();
+        assert_eq!(customer.name, "John");
+
+        let json = format!(r#"{{"id": "Strict_{ID}", "name": "John"}}"#);
+        let strict: Ided<Strict> = serde_json::from_str(&json).unwrap();
+        assert_eq!(strict.name, "John");
+
+        let json = format!(r#"{{"id": "Strict_{ID}", "name": "John", "age": 42}}"#);
+        let err = serde_json::from_str::<Ided<Strict>>(&json).unwrap_err();
+        assert!(err.to_string().starts_with("unknown field `age`"));
+    }
+
+    #[rstest]
+    pub fn test_borrowed_entity() {
+        #[derive(Debug, Identifiable, Deserialize)]
+        #[kind(class = "Hook")]
+        struct Webhook<'a> {
+            event: &'a str,
+            #[serde(borrow)]
+            body: Cow<'a, str>,
+        }
+
+        let json = format!(r#"{{"id": "Hook_{ID}", "event": "paid", "body": "no escape"}}"#);
+        let hook: Ided<Webhook> = serde_json::from_str(&json).unwrap();
+        assert_eq!(hook.event, "paid");
+        assert!(matches!(hook.body, Cow::Borrowed("no escape")));
+    }
+
+    #[rstest]
+    pub fn test_id_field_and_aliases() {
+        #[derive(Debug, Identifiable, Serialize, Deserialize)]
+        #[kind(
+            class = "Leg",
+            id_field = "legacy_id",
+            id_alias = "id",
+            id_alias = "uuid"
+        )]
+        struct Legacy {
+            name: String,
+        }
+
+        for key in ["legacy_id", "id", "uuid"] {
+            let legacy: Ided<Legacy> =
+                serde_json::from_value(json!({ key: format!("Leg_{ID}"), "name": "John" }))
+                    .unwrap();
+            assert_eq!(
+                serde_json::to_value(&legacy).unwrap(),
+                json!({ "legacy_id": format!("Leg_{ID}"), "name": "John" }),
+            );
+        }
+
+        let err = serde_json::from_value::<Ided<Legacy>>(json!({
+            "id": format!("Leg_{ID}"),
+            "uuid": format!("Leg_{ID}"),
+            "name": "John",
+        }))
+        .unwrap_err();
+        assert_eq!(err.to_string(), "duplicate field `legacy_id`");
+
+        let err = serde_json::from_value::<Ided<Legacy>>(json!({ "name": "John" })).unwrap_err();
+        assert_eq!(err.to_string(), "missing field `legacy_id`");
+    }
+
+    #[rstest]
+    pub fn test_entity_shapes() {
+        #[derive(Debug, PartialEq, Identifiable, Serialize, Deserialize)]
+        #[kind(class = "Tag")]
+        struct Tag;
+
+        #[derive(Debug, PartialEq, Identifiable, Serialize, Deserialize)]
+        #[kind(class = "Wrap")]
+        struct Wrap(std::collections::BTreeMap<String, String>);
+
+        #[derive(Debug, PartialEq, Identifiable, Serialize, Deserialize)]
+        #[kind(class = "Shape")]
+        enum Shape {
+            Circle { radius: u32 },
+            Square(u32),
+            Point,
+        }
+
+        let json = format!(r#"{{"id": "Tag_{ID}", "other": [1]}}"#);
+        let tag: Ided<Tag> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tag.entity(), &Tag);
+        assert_eq!(tag.id().db_id(), ID);
+
+        let json = format!(r#"{{"name": "John", "id": "Wrap_{ID}"}}"#);
+        let wrap: Ided<Wrap> = serde_json::from_str(&json).unwrap();
+        assert_eq!(wrap.0["name"], "John");
+        assert_eq!(wrap.0.len(), 1);
+        assert_eq!(
+            serde_json::to_value(&wrap).unwrap(),
+            json!({ "id": format!("Wrap_{ID}"), "name": "John" }),
+        );
+
+        for (json, shape) in [
+            (
+                format!(r#"{{"id": "Shape_{ID}", "Circle": {{"radius": 2}}}}"#),
+                Shape::Circle { radius: 2 },
+            ),
+            (
+                format!(r#"{{"other": 1, "Square": 3, "id": "Shape_{ID}"}}"#),
+                Shape::Square(3),
+            ),
+            (
+                format!(r#"{{"Point": null, "id": "Shape_{ID}"}}"#),
+                Shape::Point,
+            ),
+        ] {
+            let ided: Ided<Shape> = serde_json::from_str(&json).unwrap();
+            assert_eq!(ided.entity(), &shape, "{json}");
+            assert_eq!(ided.id().db_id(), ID);
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let ided: Ided<Shape> = serde_json::from_value(value).unwrap();
+            assert_eq!(ided.entity(), &shape, "{json}");
+        }
+        let json = format!(r#"{{"id": "Shape_{ID}", "Triangle": 1}}"#);
+        let err = serde_json::from_str::<Ided<Shape>>(&json).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("no variant of enum Shape found in flattened data"));
+    }
+}
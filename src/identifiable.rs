@@ -1,4 +1,4 @@
-use super::IdClass;
+use super::{IdClass, DB_ID_LEN, MAX_PREFIX_LEN};
 
 /// An identifiable is an object which can be refered to with
 /// an id (the id may be part, or not, of the object).
@@ -7,4 +7,20 @@ use super::IdClass;
 /// use the `kind` derive attribute
 pub trait Identifiable {
     fn class() -> IdClass;
+    /// Key of the id in the serialized form of an `Ided` of this type
+    ///
+    /// Can be set with `#[kind(class="Cust", id_field="customer_id")]`
+    const ID_FIELD: &'static str = "id";
+    /// Other keys accepted for the id when deserializing an `Ided`
+    ///
+    /// Can be set with `#[kind(class="Cust", id_alias="uuid")]`,
+    /// the attribute being repeatable
+    const ID_ALIASES: &'static [&'static str] = &[];
+    /// Length of the public ids of this type, ie `class().public_id_len()`,
+    /// set by the derive
+    ///
+    /// Implementations not using the derive can set it with
+    /// `IdClass::new("Cust").public_id_len()`. By default, it's the length
+    /// of the public ids of the longest prefixes, [`MAX_PREFIX_LEN`] bytes.
+    const PUBLIC_ID_LEN: usize = MAX_PREFIX_LEN + 1 + DB_ID_LEN;
 }
@@ -2,21 +2,37 @@ use {
     super::*,
     ::schemars::{
         gen::SchemaGenerator,
-        schema::{ObjectValidation, SchemaObject},
+        schema::{InstanceType, Metadata, ObjectValidation, SchemaObject, StringValidation},
         JsonSchema,
     },
     std::borrow::Cow,
 };
 
+/// The schema of an Id is a string matching the public ids of the class
 impl<O: Identifiable> JsonSchema for Id<O> {
     fn schema_name() -> String {
         format!("{}_uuid", O::class().prefix())
     }
 
-    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
-        let mut schema: SchemaObject = <String>::json_schema(gen).into();
-        schema.format = Some("string".to_owned());
-        schema.into()
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let class = O::class();
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            metadata: Some(Box::new(Metadata {
+                description: Some(format!(
+                    "Unique identifier of a {0} object: the {0} prefix and a UUID",
+                    class.prefix()
+                )),
+                examples: vec![class.example_public_id().into()],
+                ..Default::default()
+            })),
+            string: Some(Box::new(StringValidation {
+                pattern: Some(class.public_id_pattern()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
     }
 }
 
@@ -31,24 +47,69 @@ impl<O: Identifiable + JsonSchema> JsonSchema for Ided<O> {
 
     fn json_schema(gen: &mut SchemaGenerator) -> schemars::schema::Schema {
         let subschema = O::json_schema(gen);
-        let mut properties = subschema
+        let ObjectValidation {
+            mut properties,
+            mut required,
+            ..
+        } = subschema
             .into_object()
             .object
-            .map(|obj| obj.properties)
+            .map(|obj| *obj)
             .unwrap_or_default();
-        properties.insert("id".to_string(), Id::<O>::json_schema(gen));
+        properties.insert(O::ID_FIELD.to_string(), Id::<O>::json_schema(gen));
+        required.insert(O::ID_FIELD.to_string());
 
         schemars::schema::Schema::Object(SchemaObject {
-            instance_type: Some(schemars::schema::InstanceType::Object.into()),
-            metadata: Some(Box::new(schemars::schema::Metadata {
+            instance_type: Some(InstanceType::Object.into()),
+            metadata: Some(Box::new(Metadata {
                 description: Some(format!("Identified version of {}", O::schema_name())),
                 ..Default::default()
             })),
             object: Some(Box::new(ObjectValidation {
                 properties,
+                required,
                 ..Default::default()
             })),
             ..Default::default()
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use {crate::*, schemars::JsonSchema, serde_json::json};
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, JsonSchema)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn test_id_schema() {
+        let schema = schemars::schema_for!(Id<Customer>);
+        let schema = serde_json::to_value(schema).unwrap();
+        assert_eq!(schema["type"], "string");
+        assert_eq!(
+            schema["pattern"],
+            "^Cust_[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
+        );
+        assert_eq!(
+            schema["examples"],
+            json!(["Cust_c40bea18-c0c9-44b1-bd0c-43f5283e1670"])
+        );
+        assert!(schema.get("format").is_none());
+    }
+
+    #[test]
+    fn test_ided_schema() {
+        let schema = schemars::schema_for!(Ided<Customer>);
+        let schema = serde_json::to_value(schema).unwrap();
+        assert_eq!(schema["title"], "Customer_ided");
+        assert_eq!(schema["required"], json!(["id", "name"]));
+        assert_eq!(schema["properties"]["id"]["type"], "string");
+        assert!(schema["properties"].get("nickname").is_some());
+    }
+}
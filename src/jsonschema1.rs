@@ -0,0 +1,118 @@
+use {
+    super::*,
+    schemars1::{json_schema, JsonSchema, Schema, SchemaGenerator},
+    serde_json::{Map, Value},
+    std::borrow::Cow,
+};
+
+/// The schema of an Id is a string matching the public ids of the class
+impl<O: Identifiable> JsonSchema for Id<O> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("{}_uuid", O::class().prefix()).into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let class = O::class();
+        json_schema!({
+            "type": "string",
+            "description": format!(
+                "Unique identifier of a {0} object: the {0} prefix and a UUID",
+                class.prefix()
+            ),
+            "pattern": class.public_id_pattern(),
+            "examples": [class.example_public_id()],
+        })
+    }
+}
+
+impl<O: Identifiable + JsonSchema> JsonSchema for Ided<O> {
+    fn schema_name() -> Cow<'static, str> {
+        format!("{}_ided", O::schema_name()).into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        format!("Ided<{}>", O::schema_id()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let mut subschema = O::json_schema(generator);
+        let object = subschema.ensure_object();
+        let mut properties = match object.remove("properties") {
+            Some(Value::Object(properties)) => properties,
+            _ => Map::new(),
+        };
+        let mut required = match object.remove("required") {
+            Some(Value::Array(required)) => required,
+            _ => Vec::new(),
+        };
+        properties.insert(
+            O::ID_FIELD.to_string(),
+            Id::<O>::json_schema(generator).to_value(),
+        );
+        required.insert(0, O::ID_FIELD.into());
+
+        json_schema!({
+            "type": "object",
+            "description": format!("Identified version of {}", O::schema_name()),
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, schemars1::JsonSchema, serde_json::json};
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, JsonSchema)]
+    #[schemars(crate = "schemars1")]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[cfg(feature = "serde")]
+    id_enum! { PartyId: Customer, Contract }
+
+    #[test]
+    fn test_id_schema() {
+        let schema = schemars1::schema_for!(Id<Customer>).to_value();
+        assert_eq!(schema["title"], "Cust_uuid");
+        assert_eq!(schema["type"], "string");
+        assert_eq!(
+            schema["pattern"],
+            "^Cust_[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
+        );
+        assert_eq!(
+            schema["examples"],
+            json!(["Cust_c40bea18-c0c9-44b1-bd0c-43f5283e1670"])
+        );
+    }
+
+    #[test]
+    fn test_ided_schema() {
+        let schema = schemars1::schema_for!(Ided<Customer>).to_value();
+        assert_eq!(schema["title"], "Customer_ided");
+        assert_eq!(schema["required"], json!(["id", "name"]));
+        assert_eq!(schema["properties"]["id"]["type"], "string");
+        assert!(schema["properties"].get("nickname").is_some());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_id_enum_schema() {
+        let schema = schemars1::schema_for!(PartyId).to_value();
+        assert_eq!(schema["title"], "PartyId");
+        assert_eq!(
+            schema["oneOf"][1]["pattern"].as_str().unwrap()[..6],
+            *"^Cont_"
+        );
+    }
+}
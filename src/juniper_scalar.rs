@@ -0,0 +1,157 @@
+use {
+    super::*,
+    juniper::{
+        macros::reflect::{BaseSubTypes, BaseType, Type, Types, WrappedType, WrappedValue},
+        marker::{IsInputType, IsOutputType},
+        meta::MetaType,
+        parser::ScalarToken,
+        BoxFuture, ExecutionResult, Executor, FromInputValue, GraphQLType, GraphQLValue,
+        GraphQLValueAsync, InputValue, ParseScalarResult, ParseScalarValue, Registry, ScalarValue,
+        Selection, ToInputValue, Value, ID,
+    },
+};
+
+/// An Id is the GraphQL `ID` scalar, holding the public id.
+///
+/// Input values are checked when converted: an id of another class,
+/// or an invalid id, is rejected as an invalid argument value.
+impl<O: Identifiable, S: ScalarValue> GraphQLType<S> for Id<O> {
+    fn name(info: &()) -> Option<&str> {
+        <ID as GraphQLType<S>>::name(info)
+    }
+
+    fn meta<'r>(info: &(), registry: &mut Registry<'r, S>) -> MetaType<'r, S>
+    where
+        S: 'r,
+    {
+        registry.build_scalar_type::<Self>(info).into_meta()
+    }
+}
+
+impl<O: Identifiable, S: ScalarValue> GraphQLValue<S> for Id<O> {
+    type Context = ();
+    type TypeInfo = ();
+
+    fn type_name<'i>(&self, info: &'i ()) -> Option<&'i str> {
+        <Self as GraphQLType<S>>::name(info)
+    }
+
+    fn resolve(
+        &self,
+        _: &(),
+        _: Option<&[Selection<S>]>,
+        _: &Executor<(), S>,
+    ) -> ExecutionResult<S> {
+        Ok(Value::scalar(self.public_id()))
+    }
+}
+
+impl<O: Identifiable + Sync, S: ScalarValue + Send + Sync> GraphQLValueAsync<S> for Id<O> {
+    fn resolve_async<'a>(
+        &'a self,
+        info: &'a (),
+        selection_set: Option<&'a [Selection<S>]>,
+        executor: &'a Executor<(), S>,
+    ) -> BoxFuture<'a, ExecutionResult<S>> {
+        Box::pin(std::future::ready(self.resolve(
+            info,
+            selection_set,
+            executor,
+        )))
+    }
+}
+
+impl<O: Identifiable, S: ScalarValue> ToInputValue<S> for Id<O> {
+    fn to_input_value(&self) -> InputValue<S> {
+        InputValue::scalar(self.public_id())
+    }
+}
+
+impl<O: Identifiable, S: ScalarValue> FromInputValue<S> for Id<O> {
+    type Error = String;
+
+    fn from_input_value(v: &InputValue<S>) -> Result<Self, String> {
+        let public_id = v
+            .as_string_value()
+            .ok_or_else(|| format!("Expected `String`, found: {v}"))?;
+        Id::from_public_id(public_id).map_err(|e| e.to_string())
+    }
+}
+
+impl<O: Identifiable, S: ScalarValue> ParseScalarValue<S> for Id<O> {
+    fn from_str(value: ScalarToken<'_>) -> ParseScalarResult<S> {
+        <String as ParseScalarValue<S>>::from_str(value)
+    }
+}
+
+impl<O: Identifiable, S: ScalarValue> IsInputType<S> for Id<O> {}
+
+impl<O: Identifiable, S: ScalarValue> IsOutputType<S> for Id<O> {}
+
+impl<O: Identifiable, S: ScalarValue> BaseType<S> for Id<O> {
+    const NAME: Type = <ID as BaseType<S>>::NAME;
+}
+
+impl<O: Identifiable, S: ScalarValue> BaseSubTypes<S> for Id<O> {
+    const NAMES: Types = <ID as BaseSubTypes<S>>::NAMES;
+}
+
+impl<O: Identifiable, S: ScalarValue> WrappedType<S> for Id<O> {
+    const VALUE: WrappedValue = <ID as WrappedType<S>>::VALUE;
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        juniper::{
+            graphql_object, graphql_value, EmptyMutation, EmptySubscription, RootNode, Value,
+            Variables,
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    struct Query;
+
+    #[graphql_object]
+    impl Query {
+        fn customer(id: Id<Customer>) -> Id<Customer> {
+            id
+        }
+    }
+
+    type Schema = RootNode<'static, Query, EmptyMutation, EmptySubscription>;
+
+    fn schema() -> Schema {
+        RootNode::new(Query, EmptyMutation::new(), EmptySubscription::new())
+    }
+
+    fn execute(query: &str) -> Result<Value, String> {
+        juniper::execute_sync(query, None, &schema(), &Variables::new(), &())
+            .map(|(value, _)| value)
+            .map_err(|e| format!("{e:?}"))
+    }
+
+    #[test]
+    fn test_id_scalar() {
+        let public_id = format!("Cust_{ID}");
+        let value = execute(&format!(r#"{{ customer(id: "{public_id}") }}"#)).unwrap();
+        assert_eq!(value, graphql_value!({ "customer": public_id }));
+        let error = execute(&format!(r#"{{ customer(id: "Cont_{ID}") }}"#)).unwrap_err();
+        assert!(error.contains(r#"argument \"id\""#), "{error}");
+        assert!(execute(r#"{ customer(id: "Cust_123") }"#).is_err());
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = schema();
+        let field = schema.schema.concrete_type_by_name("Query").unwrap();
+        let field = field.field_by_name("customer").unwrap();
+        assert_eq!(field.field_type.to_string(), "ID!");
+    }
+}
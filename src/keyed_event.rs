@@ -0,0 +1,199 @@
+use {super::*, std::fmt};
+
+impl<O: Identifiable> Id<O> {
+    /// Return the key of the messages about this object, eg in Kafka,
+    /// so that they all go to the same partition
+    ///
+    /// The key is the public id, whose bytes don't depend on the version
+    /// of the crate nor on the platform, and which tells the class of the
+    /// id to whoever reads the topic.
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+    /// assert_eq!(id.partition_key(), b"Cust_371c35ec-34d9-4315-ab31-7ea8889a419a");
+    /// assert_eq!(Id::<Customer>::from_partition_key(&id.partition_key()), Ok(id));
+    /// ```
+    pub fn partition_key(&self) -> Vec<u8> {
+        self.public_id().into_bytes()
+    }
+
+    /// Parse the key of a message, checking the class
+    pub fn from_partition_key(key: &[u8]) -> Result<Self, IdError> {
+        match std::str::from_utf8(key) {
+            Ok(public_id) => Self::from_public_id(public_id),
+            Err(e) => Err(IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(&String::from_utf8_lossy(key))
+                .with_expected(O::class())
+                .with_position(e.valid_up_to())),
+        }
+    }
+}
+
+/// A message about an identified object, keyed by its id, eg a Kafka
+/// record whose key is [`Id::partition_key`]
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// // producer
+/// let event = KeyedEvent::new(Id::<Customer>::random_v4(), "renamed");
+/// let (key, payload) = (event.partition_key(), *event.payload());
+///
+/// // consumer
+/// let received = KeyedEvent::<Customer, _>::from_key(&key, payload).unwrap();
+/// assert_eq!(received, event);
+/// ```
+///
+/// With the serde feature, the event is serialized as an object with the
+/// `key` (the public id) and `payload` keys, for transports without
+/// message keys.
+pub struct KeyedEvent<O: Identifiable, P> {
+    key: Id<O>,
+    payload: P,
+}
+
+impl<O: Identifiable, P> KeyedEvent<O, P> {
+    pub fn new(key: Id<O>, payload: P) -> Self {
+        Self { key, payload }
+    }
+    /// Build the event of a received message, checking the class of its
+    /// key
+    pub fn from_key(key: &[u8], payload: P) -> Result<Self, IdError> {
+        Ok(Self::new(Id::from_partition_key(key)?, payload))
+    }
+    /// Return the id of the object
+    pub fn key(&self) -> Id<O> {
+        self.key
+    }
+    /// Return the key of the message
+    pub fn partition_key(&self) -> Vec<u8> {
+        self.key.partition_key()
+    }
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+    pub fn into_payload(self) -> P {
+        self.payload
+    }
+    pub fn into_parts(self) -> (Id<O>, P) {
+        (self.key, self.payload)
+    }
+}
+
+impl<O: Identifiable, P: fmt::Debug> fmt::Debug for KeyedEvent<O, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedEvent")
+            .field("key", &self.key)
+            .field("payload", &self.payload)
+            .finish()
+    }
+}
+
+impl<O: Identifiable, P: Clone> Clone for KeyedEvent<O, P> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key,
+            payload: self.payload.clone(),
+        }
+    }
+}
+
+impl<O: Identifiable, P: PartialEq> PartialEq for KeyedEvent<O, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.payload == other.payload
+    }
+}
+impl<O: Identifiable, P: Eq> Eq for KeyedEvent<O, P> {}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use {
+        super::*,
+        serde::{ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer},
+    };
+
+    impl<O: Identifiable, P: Serialize> Serialize for KeyedEvent<O, P> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut event = serializer.serialize_struct("KeyedEvent", 2)?;
+            event.serialize_field("key", &self.key)?;
+            event.serialize_field("payload", &self.payload)?;
+            event.end()
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(bound(deserialize = "P: Deserialize<'de>"))]
+    struct RawEvent<O: Identifiable, P> {
+        key: Id<O>,
+        payload: P,
+    }
+
+    impl<'de, O: Identifiable, P: Deserialize<'de>> Deserialize<'de> for KeyedEvent<O, P> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let raw = RawEvent::<O, P>::deserialize(deserializer)?;
+            Ok(Self::new(raw.key, raw.payload))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_partition_key() {
+        let id = Id::<Customer>::from_db_id(ID).unwrap();
+        let key = id.partition_key();
+        assert_eq!(key, format!("Cust_{ID}").as_bytes());
+        assert_eq!(Id::from_partition_key(&key), Ok(id));
+        let error = Id::<Contract>::from_partition_key(&key).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::WrongClass);
+        let error = Id::<Customer>::from_partition_key(b"Cust_\xff").unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+        assert_eq!(error.position(), Some(5));
+    }
+
+    #[test]
+    fn test_keyed_event() {
+        let id = Id::<Customer>::random_v4();
+        let event = KeyedEvent::new(id, vec![1, 2]);
+        let received = KeyedEvent::<Customer, _>::from_key(&event.partition_key(), vec![1, 2]);
+        assert_eq!(received.as_ref(), Ok(&event));
+        assert_eq!(received.unwrap().into_parts(), (id, vec![1, 2]));
+        assert!(KeyedEvent::<Contract, ()>::from_key(&event.partition_key(), ()).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_keyed_event_serde() {
+        let event = KeyedEvent::new(Id::<Customer>::from_db_id(ID).unwrap(), "renamed");
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            format!(r#"{{"key":"Cust_{ID}","payload":"renamed"}}"#)
+        );
+        let parsed: KeyedEvent<Customer, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.into_parts(), (event.key(), "renamed".to_string()));
+        assert!(serde_json::from_str::<KeyedEvent<Contract, String>>(&json).is_err());
+    }
+}
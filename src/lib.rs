@@ -45,7 +45,7 @@
 //! // And let's check it's OK as a customer id:
 //! let customer_id: Result<Id<Customer>, IdError> = Id::from_public_id(&customer_public_id);
 //! assert!(customer_id.is_ok());
-//! assert_eq!(customer_id.unwrap().db_id(), "371c35ec-34d9-4315-ab31-7ea8889a419a");
+//! assert_eq!(customer_id.as_ref().unwrap().db_id(), "371c35ec-34d9-4315-ab31-7ea8889a419a");
 //!
 //! // The public id is parsed and checked in a case insensitive way
 //! assert_eq!(customer_id, "cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse());
@@ -53,35 +53,219 @@
 //!
 //! ```
 
+// The registrations generated by the derive refer to `::kind`
+#[cfg(feature = "registry")]
+extern crate self as kind;
+
+#[cfg(feature = "registry")]
+mod any_id;
+mod batch;
+#[cfg(feature = "bench-support")]
+pub mod bench_support;
+#[cfg(feature = "copy")]
+pub mod copy;
+#[cfg(feature = "events")]
+mod entity_event;
 mod error;
 mod id;
+mod id_arena;
 mod id_class;
+mod id_map;
+mod id_path;
+mod id_slice;
+mod id_status;
 mod ided;
 mod identifiable;
+mod keyed_event;
+mod merge_map;
+mod or_nil;
+mod pair_id;
+mod recovery;
+mod redact;
+mod reference;
+#[cfg(feature = "registry")]
+pub mod registry;
+mod scoped_id;
+#[cfg(any(feature = "bench-support", feature = "testing"))]
+mod split_mix;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+
+#[cfg(feature = "actix")]
+mod actix_extract;
+#[cfg(feature = "axum")]
+mod axum_extract;
+#[cfg(feature = "async-graphql")]
+mod graphql_scalar;
+#[cfg(feature = "tower")]
+mod id_headers;
+#[cfg(feature = "juniper")]
+mod juniper_scalar;
+#[cfg(any(feature = "axum", feature = "actix", feature = "tower"))]
+mod rejection;
+#[cfg(feature = "rocket")]
+mod rocket_param;
 
+#[cfg(feature = "sqlx")]
+pub mod migrate;
+#[cfg(feature = "sqlx-mysql")]
+mod mysql;
+#[cfg(feature = "tokio-postgres")]
+mod pg_types;
 #[cfg(feature = "sqlx")]
 mod postgres;
+#[cfg(feature = "sqlx")]
+pub mod sql;
+#[cfg(feature = "sqlx-sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlx")]
+pub mod sqlx_compat;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "borsh")]
+mod borsh_serialize;
+#[cfg(feature = "bson")]
+mod bson_id;
+#[cfg(feature = "subtle")]
+mod ct_eq;
+#[cfg(feature = "diesel")]
+mod diesel_sql;
+#[cfg(feature = "fake")]
+mod fake_dummy;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "nohash")]
+mod id_hash;
+#[cfg(feature = "validator")]
+mod id_validation;
+#[cfg(feature = "log")]
+mod log_value;
+#[cfg(feature = "miette")]
+mod miette_diagnostic;
+#[cfg(feature = "opaque")]
+pub mod opaque;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "kind-prost")]
+pub mod proto;
+#[cfg(feature = "pyo3")]
+pub mod python;
+#[cfg(feature = "redis")]
+mod redis_value;
+#[cfg(feature = "relay")]
+mod relay;
+#[cfg(feature = "sea-orm")]
+mod sea_orm_value;
+#[cfg(feature = "slog")]
+mod slog_value;
+#[cfg(feature = "stable-hash")]
+mod stable_hash;
+#[cfg(feature = "tonic")]
+mod tonic_status;
+#[cfg(feature = "tracing")]
+mod tracing_fields;
+#[cfg(feature = "ts")]
+mod ts_type;
+#[cfg(feature = "ulid")]
+mod ulid_id;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "serde")]
+pub mod claims;
+#[cfg(feature = "cursor")]
+mod cursor;
 #[cfg(feature = "serde")]
 mod id_enum;
+#[cfg(feature = "serde")]
+mod ided_serde;
 #[cfg(feature = "jsonschema")]
 mod jsonschema;
+#[cfg(feature = "jsonschema-1")]
+mod jsonschema1;
+#[cfg(feature = "manifest")]
+pub mod manifest;
 #[cfg(feature = "openapi")]
-mod openapi;
+pub mod openapi;
 #[cfg(feature = "serde")]
 mod serde_serialize;
+#[cfg(feature = "share-token")]
+mod share_token;
+#[cfg(all(feature = "registry", feature = "serde"))]
+pub mod spec;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "serde")]
+pub mod with;
 
 #[allow(unused_imports)]
-pub use {error::*, id::*, id_class::*, ided::*, identifiable::*, kind_proc::*};
+pub use {
+    batch::*, error::*, id::*, id_arena::*, id_class::*, id_map::*, id_path::*, id_slice::*,
+    id_status::*, ided::*, identifiable::*, keyed_event::*, kind_proc::*, merge_map::*, or_nil::*,
+    pair_id::*, recovery::*, redact::*, reference::*, scoped_id::*,
+};
 
 #[allow(unused_imports)]
 #[cfg(feature = "serde")]
-pub use {crate::serde_serialize::*, id_enum::*};
+pub use {crate::serde_serialize::*, id_enum::*, ided_serde::IdedFlat};
+
+#[cfg(feature = "events")]
+pub use crate::entity_event::*;
+
+#[allow(unused_imports)]
+#[cfg(feature = "sqlx")]
+pub use crate::postgres::*;
+
+#[cfg(feature = "actix")]
+pub use crate::actix_extract::*;
+#[cfg(feature = "axum")]
+pub use crate::axum_extract::*;
+#[cfg(feature = "cursor")]
+pub use crate::cursor::*;
+#[cfg(feature = "nohash")]
+pub use crate::id_hash::*;
+#[cfg(feature = "tower")]
+pub use crate::id_headers::*;
+#[cfg(feature = "validator")]
+pub use crate::id_validation::*;
+#[cfg(feature = "otel")]
+pub use crate::otel::*;
+#[cfg(any(feature = "axum", feature = "actix", feature = "tower"))]
+pub use crate::rejection::*;
+#[cfg(feature = "share-token")]
+pub use crate::share_token::*;
+#[cfg(feature = "tonic")]
+pub use crate::tonic_status::*;
+#[cfg(feature = "tracing")]
+pub use crate::tracing_fields::*;
 
 #[allow(unused_imports)]
 #[cfg(feature = "jsonschema")]
 pub use crate::jsonschema::*;
 
+#[cfg(feature = "registry")]
+pub use {
+    any_id::*,
+    registry::{RegisteredClass, Relation, RelationKind, KIND_CLASSES},
+};
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "async-graphql")]
+    pub use async_graphql;
+    #[cfg(feature = "registry")]
+    pub use linkme;
+    #[cfg(feature = "proptest")]
+    pub use proptest;
+    #[cfg(feature = "jsonschema-1")]
+    pub use schemars1;
+    #[cfg(feature = "tracing")]
+    pub use tracing;
+    #[cfg(feature = "openapi")]
+    pub use utoipa;
+}
+
 #[allow(unused_imports)]
 #[cfg(feature = "openapi")]
 pub use crate::openapi::*;
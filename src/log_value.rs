@@ -0,0 +1,34 @@
+use {
+    super::*,
+    log::kv::{ToValue, Value},
+};
+
+/// An id is captured by `log` key-values as its public id, eg
+/// `info!(customer = id; "charged")`, or in its redacted form with the
+/// "redact" feature
+impl<O: Identifiable> ToValue for Id<O> {
+    fn to_value(&self) -> Value<'_> {
+        #[cfg(not(feature = "redact"))]
+        return Value::from_display(self);
+        #[cfg(feature = "redact")]
+        return Value::from_debug(self);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, log::kv::ToValue};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_log_value() {
+        let id = Id::<Customer>::random_v4();
+        #[cfg(not(feature = "redact"))]
+        assert_eq!(id.to_value().to_string(), id.public_id());
+        #[cfg(feature = "redact")]
+        assert_eq!(id.to_value().to_string(), id.redacted().to_string());
+    }
+}
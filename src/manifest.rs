@@ -0,0 +1,466 @@
+//! A manifest of the classes of a crate, `kind-classes.json`, listing
+//! their prefixes and types, for documentation generators and the SDK
+//! generators of other languages.
+//!
+//! It's written by the build script of the crate declaring the classes,
+//! from the `#[kind(...)]` attributes of its sources:
+//!
+//! ```ignore
+//! // build.rs, with kind as a build dependency with the "manifest" feature
+//! fn main() {
+//!     kind::manifest::write_build_manifest().unwrap();
+//! }
+//! ```
+//!
+//! and can then be included, or copied next to the published artifacts:
+//!
+//! ```ignore
+//! const CLASSES: &str = include_str!(concat!(env!("OUT_DIR"), "/kind-classes.json"));
+//! ```
+//!
+//! With the `registry` feature, the manifest of all the classes of a
+//! program is also given by [`Manifest::from_registry`].
+//!
+//! Comparing the manifest of a new version with the one of the previous
+//! release detects the changes which break the ids already given out:
+//!
+//! ```ignore
+//! let released = Manifest::load("kind-classes.json")?;
+//! let breaking: Vec<ManifestChange> = released
+//!     .diff(&Manifest::from_registry())
+//!     .into_iter()
+//!     .filter(ManifestChange::is_breaking)
+//!     .collect();
+//! assert!(breaking.is_empty(), "breaking id changes: {breaking:?}");
+//! ```
+
+use {
+    super::*,
+    serde::{Deserialize, Serialize},
+    std::{
+        env, fmt, fs, io,
+        path::{Path, PathBuf},
+    },
+    syn::{Attribute, Ident, Item, Lit, Meta, NestedMeta},
+};
+
+/// The name of the manifest file
+pub const MANIFEST_FILE: &str = "kind-classes.json";
+
+/// The classes of a crate, or of a program
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub classes: Vec<ManifestClass>,
+}
+
+/// A class of a [`Manifest`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestClass {
+    pub prefix: String,
+    /// The path of the identifiable type, eg `billing::models::Customer`
+    #[serde(rename = "type")]
+    pub type_path: String,
+    /// The key of the id in serialized objects
+    pub id_field: String,
+    /// The sensitivity of the ids, eg `pii`
+    pub sensitivity: String,
+}
+
+/// A difference between two manifests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestChange {
+    Added {
+        prefix: String,
+        type_path: String,
+    },
+    /// The class was removed, so its ids can't be parsed anymore
+    Removed {
+        prefix: String,
+        type_path: String,
+    },
+    /// The type has a new prefix, so its former ids are rejected
+    PrefixChanged {
+        type_path: String,
+        old: String,
+        new: String,
+    },
+    /// The id of serialized objects is under a new key
+    IdFieldChanged {
+        prefix: String,
+        old: String,
+        new: String,
+    },
+    SensitivityChanged {
+        prefix: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl ManifestChange {
+    /// Tell whether the change breaks the clients of the previous
+    /// version, eg by rejecting the ids they stored
+    pub fn is_breaking(&self) -> bool {
+        matches!(
+            self,
+            Self::Removed { .. } | Self::PrefixChanged { .. } | Self::IdFieldChanged { .. }
+        )
+    }
+}
+
+impl fmt::Display for ManifestChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { prefix, type_path } => write!(f, "class {prefix} ({type_path}) added"),
+            Self::Removed { prefix, type_path } => {
+                write!(f, "class {prefix} ({type_path}) removed")
+            }
+            Self::PrefixChanged {
+                type_path,
+                old,
+                new,
+            } => write!(f, "prefix of {type_path} changed from {old} to {new}"),
+            Self::IdFieldChanged { prefix, old, new } => {
+                write!(f, "id field of {prefix} changed from {old} to {new}")
+            }
+            Self::SensitivityChanged { prefix, old, new } => {
+                write!(f, "sensitivity of {prefix} changed from {old} to {new}")
+            }
+        }
+    }
+}
+
+impl Manifest {
+    /// Return the manifest of the classes registered in the program
+    #[cfg(feature = "registry")]
+    pub fn from_registry() -> Self {
+        let classes = registry::classes()
+            .iter()
+            .map(|registered| ManifestClass {
+                prefix: registered.class().prefix().to_string(),
+                type_path: format!("{}::{}", registered.module_path(), registered.type_name()),
+                id_field: registered.id_field().to_string(),
+                sensitivity: registered.class().sensitivity().to_string(),
+            })
+            .collect();
+        Self::sorted(classes)
+    }
+
+    /// Build the manifest of the sources of a crate, from the types
+    /// deriving `Identifiable` in the files of the directory
+    pub fn scan(src_dir: impl AsRef<Path>, crate_name: &str) -> io::Result<Self> {
+        let src_dir = src_dir.as_ref();
+        let mut classes = Vec::new();
+        for file in rust_files(src_dir)? {
+            let source = fs::read_to_string(&file)?;
+            let parsed = syn::parse_file(&source).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", file.display()),
+                )
+            })?;
+            let module = module_path(crate_name, src_dir, &file);
+            scan_items(&parsed.items, &module, &mut classes);
+        }
+        Ok(Self::sorted(classes))
+    }
+
+    fn sorted(mut classes: Vec<ManifestClass>) -> Self {
+        classes.sort_by(|a, b| (&a.prefix, &a.type_path).cmp(&(&b.prefix, &b.type_path)));
+        Self { classes }
+    }
+
+    /// Read a manifest written by `save`
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Write the manifest as pretty printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).expect("manifests are serializable");
+        fs::write(path, json + "\n")
+    }
+
+    /// Return the changes from this manifest to a newer one
+    pub fn diff(&self, newer: &Manifest) -> Vec<ManifestChange> {
+        let find = |classes: &'_ [ManifestClass], prefix: &str| {
+            classes
+                .iter()
+                .find(|c| c.prefix.eq_ignore_ascii_case(prefix))
+                .cloned()
+        };
+        let mut changes = Vec::new();
+        for old in &self.classes {
+            match find(&newer.classes, &old.prefix) {
+                Some(new) => {
+                    if old.id_field != new.id_field {
+                        changes.push(ManifestChange::IdFieldChanged {
+                            prefix: new.prefix.clone(),
+                            old: old.id_field.clone(),
+                            new: new.id_field,
+                        });
+                    }
+                    if old.sensitivity != new.sensitivity {
+                        changes.push(ManifestChange::SensitivityChanged {
+                            prefix: new.prefix,
+                            old: old.sensitivity.clone(),
+                            new: new.sensitivity,
+                        });
+                    }
+                }
+                None => match newer.classes.iter().find(|c| c.type_path == old.type_path) {
+                    Some(new) => changes.push(ManifestChange::PrefixChanged {
+                        type_path: old.type_path.clone(),
+                        old: old.prefix.clone(),
+                        new: new.prefix.clone(),
+                    }),
+                    None => changes.push(ManifestChange::Removed {
+                        prefix: old.prefix.clone(),
+                        type_path: old.type_path.clone(),
+                    }),
+                },
+            }
+        }
+        for new in &newer.classes {
+            let renamed = changes.iter().any(|change| {
+                matches!(change, ManifestChange::PrefixChanged { new: prefix, .. } if *prefix == new.prefix)
+            });
+            if !renamed && find(&self.classes, &new.prefix).is_none() {
+                changes.push(ManifestChange::Added {
+                    prefix: new.prefix.clone(),
+                    type_path: new.type_path.clone(),
+                });
+            }
+        }
+        changes
+    }
+}
+
+/// Write the manifest of the crate being built in `$OUT_DIR`, scanning
+/// its `src` directory, and ask cargo to run the build script again when
+/// the sources change
+///
+/// This is to be called from a build script.
+pub fn write_build_manifest() -> io::Result<Manifest> {
+    let var = |name| {
+        env::var(name).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{name} isn't set, not in a build script?"),
+            )
+        })
+    };
+    let src_dir = PathBuf::from(var("CARGO_MANIFEST_DIR")?).join("src");
+    let crate_name = var("CARGO_PKG_NAME")?.replace('-', "_");
+    let manifest = Manifest::scan(&src_dir, &crate_name)?;
+    manifest.save(PathBuf::from(var("OUT_DIR")?).join(MANIFEST_FILE))?;
+    println!("cargo:rerun-if-changed={}", src_dir.display());
+    Ok(manifest)
+}
+
+/// The Rust files of the directory and its subdirectories, except the
+/// binaries which are other crates
+fn rust_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if !path.ends_with("bin") {
+                files.extend(rust_files(&path)?);
+            }
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The path of the module of a file, eg `billing::models` for
+/// `src/models.rs` or `src/models/mod.rs`
+fn module_path(crate_name: &str, src_dir: &Path, file: &Path) -> String {
+    let mut path = crate_name.to_string();
+    let relative = file
+        .strip_prefix(src_dir)
+        .unwrap_or(file)
+        .with_extension("");
+    for component in relative.iter() {
+        let component = component.to_string_lossy();
+        if !matches!(component.as_ref(), "lib" | "main" | "mod") {
+            path.push_str("::");
+            path.push_str(&component);
+        }
+    }
+    path
+}
+
+fn scan_items(items: &[Item], module: &str, classes: &mut Vec<ManifestClass>) {
+    for item in items {
+        match item {
+            Item::Struct(item) => classes.extend(class_of(&item.attrs, &item.ident, module)),
+            Item::Enum(item) => classes.extend(class_of(&item.attrs, &item.ident, module)),
+            Item::Mod(item) => {
+                if let Some((_, items)) = &item.content {
+                    scan_items(items, &format!("{module}::{}", item.ident), classes);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The class declared by the attributes of a type deriving `Identifiable`
+fn class_of(attributes: &[Attribute], ident: &Ident, module: &str) -> Option<ManifestClass> {
+    let metas = || attributes.iter().filter_map(|a| a.parse_meta().ok());
+    let identifiable = metas().any(|meta| match meta {
+        Meta::List(list) if list.path.is_ident("derive") => list.nested.iter().any(|nested| {
+            matches!(nested, NestedMeta::Meta(Meta::Path(path))
+                if path.segments.last().is_some_and(|s| s.ident == "Identifiable"))
+        }),
+        _ => false,
+    });
+    if !identifiable {
+        return None;
+    }
+    let mut class = ManifestClass {
+        prefix: String::new(),
+        type_path: format!("{module}::{ident}"),
+        id_field: "id".to_string(),
+        sensitivity: Sensitivity::default().to_string(),
+    };
+    for meta in metas() {
+        let Meta::List(list) = meta else { continue };
+        if !list.path.is_ident("kind") {
+            continue;
+        }
+        for nested in list.nested {
+            let NestedMeta::Meta(Meta::NameValue(name_value)) = nested else {
+                continue;
+            };
+            let Lit::Str(value) = name_value.lit else {
+                continue;
+            };
+            let Some(name) = name_value.path.get_ident() else {
+                continue;
+            };
+            match name.to_string().as_str() {
+                "class" => class.prefix = value.value(),
+                "id_field" => class.id_field = value.value(),
+                "sensitivity" => class.sensitivity = value.value(),
+                _ => {}
+            }
+        }
+    }
+    (!class.prefix.is_empty()).then_some(class)
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::manifest::{Manifest, ManifestClass, MANIFEST_FILE},
+        std::fs,
+    };
+
+    fn class(prefix: &str, type_path: &str) -> ManifestClass {
+        ManifestClass {
+            prefix: prefix.to_string(),
+            type_path: type_path.to_string(),
+            id_field: "id".to_string(),
+            sensitivity: "public".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join(format!("kind-manifest-{}", std::process::id()));
+        fs::create_dir_all(dir.join("models")).unwrap();
+        fs::write(
+            dir.join("lib.rs"),
+            r#"
+            #[derive(Debug, Identifiable)]
+            #[kind(class = "Cust", sensitivity = "pii")]
+            pub struct Customer {}
+
+            #[kind(class = "Nope")]
+            pub struct NotIdentifiable {}
+
+            mod nested {
+                #[derive(kind::Identifiable)]
+                #[kind(class = "Cont", id_field = "contract_id")]
+                pub enum Contract {}
+            }
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("models/mod.rs"),
+            r#"#[derive(Identifiable)] #[kind(class="Inv")] struct Invoice;"#,
+        )
+        .unwrap();
+        let manifest = Manifest::scan(&dir, "billing").unwrap();
+        let path = dir.join(MANIFEST_FILE);
+        manifest.save(&path).unwrap();
+        assert_eq!(Manifest::load(&path).unwrap(), manifest);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut customer = class("Cust", "billing::Customer");
+        customer.sensitivity = "pii".to_string();
+        let mut contract = class("Cont", "billing::nested::Contract");
+        contract.id_field = "contract_id".to_string();
+        assert_eq!(
+            manifest.classes,
+            [contract, customer, class("Inv", "billing::models::Invoice")]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = Manifest {
+            classes: vec![
+                class("Cust", "billing::Customer"),
+                class("Cont", "billing::Contract"),
+                class("Inv", "billing::Invoice"),
+            ],
+        };
+        let mut contract = class("Contr", "billing::Contract");
+        contract.id_field = "contract_id".to_string();
+        let mut customer = class("CUST", "billing::Customer");
+        customer.sensitivity = "pii".to_string();
+        let new = Manifest {
+            classes: vec![customer, contract, class("Plan", "billing::Plan")],
+        };
+        let changes = old.diff(&new);
+        assert_eq!(
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                "sensitivity of CUST changed from public to pii",
+                "prefix of billing::Contract changed from Cont to Contr",
+                "class Inv (billing::Invoice) removed",
+                "class Plan (billing::Plan) added",
+            ]
+        );
+        assert_eq!(changes.iter().filter(|c| c.is_breaking()).count(), 2);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn test_from_registry() {
+        use crate::*;
+
+        #[allow(dead_code)]
+        #[derive(Debug, Identifiable)]
+        #[kind(class = "ManCust", sensitivity = "pii")]
+        pub struct Customer {}
+
+        let manifest = Manifest::from_registry();
+        let customer = manifest
+            .classes
+            .iter()
+            .find(|c| c.prefix == "ManCust")
+            .unwrap();
+        assert_eq!(customer.type_path, "kind::manifest::test::Customer");
+        assert_eq!(customer.sensitivity, "pii");
+    }
+}
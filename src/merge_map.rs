@@ -0,0 +1,304 @@
+use {
+    super::*,
+    std::{collections::HashMap, convert::Infallible, fmt},
+};
+
+/// A table of the merges of objects, eg of duplicate customers, mapping
+/// the obsolete ids to the canonical ones
+///
+/// The merged ids are kept in groups, so that canonicalizing an id is a
+/// single lookup of its group, whatever the number of merges it went
+/// through:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let (old, duplicate, kept) = (Id::random_v4(), Id::random_v4(), Id::random_v4());
+/// let mut merges = MergeMap::<Customer>::new();
+/// assert!(merges.merge(old, duplicate));
+/// assert!(merges.merge(duplicate, kept));
+/// assert_eq!(merges.canonicalize(old), kept);
+/// assert_eq!(merges.canonicalize(kept), kept);
+/// assert!(!merges.merge(kept, old)); // it would loop
+///
+/// let names: IdMap<Customer, &str> = [(kept, "ACME")].into_iter().collect();
+/// assert_eq!(names.get_canonical(&merges, old), Some(&"ACME"));
+/// ```
+///
+/// With the sqlx feature, it can be loaded from and saved to a table
+/// with `id` and `merged_into` uuid columns.
+pub struct MergeMap<O: Identifiable> {
+    /// The group of each id of a merge, obsolete or canonical
+    group_of: HashMap<Id<O>, usize>,
+    groups: Vec<Group<O>>,
+    /// The indexes of the emptied groups, reused by the next ones
+    free: Vec<usize>,
+    len: usize,
+}
+
+/// A canonical id and the obsolete ids merged into it
+struct Group<O: Identifiable> {
+    canonical: Id<O>,
+    merged: Vec<Id<O>>,
+}
+
+impl<O: Identifiable> MergeMap<O> {
+    pub fn new() -> Self {
+        Self {
+            group_of: HashMap::new(),
+            groups: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Return the number of obsolete ids
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Tell whether the id was merged into another one
+    pub fn is_merged(&self, id: Id<O>) -> bool {
+        self.group_of
+            .get(&id)
+            .is_some_and(|&g| self.groups[g].canonical != id)
+    }
+
+    /// Return the id the given one was finally merged into, or the id
+    /// itself when it wasn't merged
+    pub fn canonicalize(&self, id: Id<O>) -> Id<O> {
+        self.group_of
+            .get(&id)
+            .map_or(id, |&g| self.groups[g].canonical)
+    }
+
+    /// Record the merge of `from` into `into`, returning false, without
+    /// recording it, when `into` is `from` or was merged into it
+    ///
+    /// The ids previously merged into `from` are now merged into the
+    /// canonical id of `into`. The ids of the smallest of both groups
+    /// are moved to the other group, so that merging n ids, in any
+    /// order, takes O(n log n) time.
+    pub fn merge(&mut self, from: Id<O>, into: Id<O>) -> bool {
+        let into = self.canonicalize(into);
+        if into == from {
+            return false;
+        }
+        self.detach(from);
+        let target = self.group(into);
+        match self.group_of.get(&from).copied() {
+            Some(source) => {
+                let (keep, moved) =
+                    if self.groups[source].merged.len() > self.groups[target].merged.len() {
+                        (source, target)
+                    } else {
+                        (target, source)
+                    };
+                let moved_canonical = self.groups[moved].canonical;
+                let moved_ids = std::mem::take(&mut self.groups[moved].merged);
+                for id in moved_ids.iter().chain([&moved_canonical]) {
+                    self.group_of.insert(*id, keep);
+                }
+                let group = &mut self.groups[keep];
+                group.merged.extend(moved_ids);
+                group.merged.push(from);
+                group.canonical = into;
+                self.free.push(moved);
+            }
+            None => {
+                self.group_of.insert(from, target);
+                self.groups[target].merged.push(from);
+            }
+        }
+        self.len += 1;
+        true
+    }
+
+    /// Remove an obsolete id from its group, before merging it again
+    fn detach(&mut self, id: Id<O>) {
+        let Some(&g) = self.group_of.get(&id) else {
+            return;
+        };
+        let group = &mut self.groups[g];
+        if group.canonical == id {
+            return;
+        }
+        if let Some(i) = group.merged.iter().position(|merged| *merged == id) {
+            group.merged.swap_remove(i);
+        }
+        self.group_of.remove(&id);
+        self.len -= 1;
+        if group.merged.is_empty() {
+            self.group_of.remove(&group.canonical);
+            self.free.push(g);
+        }
+    }
+
+    /// Return the group of a canonical id, creating it if needed
+    fn group(&mut self, canonical: Id<O>) -> usize {
+        if let Some(&g) = self.group_of.get(&canonical) {
+            return g;
+        }
+        let group = Group {
+            canonical,
+            merged: Vec::new(),
+        };
+        let g = match self.free.pop() {
+            Some(g) => {
+                self.groups[g] = group;
+                g
+            }
+            None => {
+                self.groups.push(group);
+                self.groups.len() - 1
+            }
+        };
+        self.group_of.insert(canonical, g);
+        g
+    }
+
+    /// Return the obsolete ids with their canonical ids, in no specific
+    /// order
+    pub fn iter(&self) -> impl Iterator<Item = (Id<O>, Id<O>)> + '_ {
+        self.groups
+            .iter()
+            .flat_map(|g| g.merged.iter().map(move |&id| (id, g.canonical)))
+    }
+}
+
+impl<O: Identifiable> Default for MergeMap<O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: Identifiable> Clone for MergeMap<O> {
+    fn clone(&self) -> Self {
+        Self {
+            group_of: self.group_of.clone(),
+            groups: self
+                .groups
+                .iter()
+                .map(|g| Group {
+                    canonical: g.canonical,
+                    merged: g.merged.clone(),
+                })
+                .collect(),
+            free: self.free.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for MergeMap<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Merges creating loops are ignored, as with [`MergeMap::merge`]
+impl<O: Identifiable> FromIterator<(Id<O>, Id<O>)> for MergeMap<O> {
+    fn from_iter<I: IntoIterator<Item = (Id<O>, Id<O>)>>(iter: I) -> Self {
+        let mut merges = Self::new();
+        merges.extend(iter);
+        merges
+    }
+}
+
+impl<O: Identifiable> Extend<(Id<O>, Id<O>)> for MergeMap<O> {
+    fn extend<I: IntoIterator<Item = (Id<O>, Id<O>)>>(&mut self, iter: I) {
+        for (from, into) in iter {
+            self.merge(from, into);
+        }
+    }
+}
+
+impl<O: Identifiable> Resolver<O> for MergeMap<O> {
+    type Error = Infallible;
+    fn status(&self, id: Id<O>) -> Result<IdStatus<O>, Infallible> {
+        Ok(match self.canonicalize(id) {
+            into if into != id => IdStatus::Merged { into },
+            _ => IdStatus::Active,
+        })
+    }
+}
+
+impl<O: Identifiable, V> IdMap<O, V> {
+    /// Return the value of the canonical id of the given one
+    pub fn get_canonical(&self, merges: &MergeMap<O>, id: Id<O>) -> Option<&V> {
+        self.get(&merges.canonicalize(id))
+    }
+}
+
+impl<O: Identifiable, V> IdArena<O, V> {
+    /// Return the value of the canonical id of the given one
+    pub fn get_canonical(&self, merges: &MergeMap<O>, id: Id<O>) -> Option<&V> {
+        self.get(merges.canonicalize(id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_merge() {
+        let [a, b, c, d] = [(); 4].map(|_| Id::<Customer>::random_v4());
+        let mut merges = MergeMap::new();
+        assert!(merges.merge(a, b));
+        assert!(merges.merge(c, a));
+        assert_eq!(merges.canonicalize(c), b);
+        assert!(merges.merge(b, d));
+        for id in [a, b, c, d] {
+            assert_eq!(merges.canonicalize(id), d);
+            assert_eq!(merges.resolve(id), Ok(d));
+        }
+        assert!(!merges.merge(d, c));
+        assert!(!merges.merge(d, d));
+        assert_eq!(merges.len(), 3);
+        assert!(!merges.is_merged(d));
+
+        let loaded: MergeMap<Customer> = [(a, b), (b, a), (c, b)].into_iter().collect();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.canonicalize(c), b);
+
+        // an obsolete id merged again doesn't move the other ids
+        let mut merges: MergeMap<Customer> = [(a, b), (c, b)].into_iter().collect();
+        assert!(merges.merge(a, d));
+        assert_eq!(merges.canonicalize(a), d);
+        assert_eq!(merges.canonicalize(c), b);
+        assert!(!merges.is_merged(b));
+        assert!(merges.merge(d, c));
+        assert_eq!(merges.canonicalize(a), b);
+        assert_eq!(merges.len(), 3);
+        let mut merged: Vec<_> = merges.iter().collect();
+        merged.sort();
+        let mut expected = vec![(a, b), (c, b), (d, b)];
+        expected.sort();
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_long_merge_chain() {
+        let ids: Vec<Id<Customer>> = (0..20_000).map(|_| Id::random_v4()).collect();
+        let mut merges = MergeMap::new();
+        for pair in ids.windows(2) {
+            assert!(merges.merge(pair[0], pair[1]));
+        }
+        let last = ids[ids.len() - 1];
+        assert!(ids.iter().all(|id| merges.canonicalize(*id) == last));
+        assert_eq!(merges.len(), ids.len() - 1);
+    }
+}
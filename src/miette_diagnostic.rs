@@ -0,0 +1,108 @@
+use {
+    super::*,
+    miette::{Diagnostic, LabeledSpan, SourceCode},
+    std::fmt::Display,
+};
+
+/// An IdError is a miette diagnostic whose source code is the offending
+/// input, with a label under the wrong prefix, the missing separator or
+/// the invalid UUID:
+///
+/// ```text
+///   × wrong object class
+///    ╭────
+///  1 │ Cont_371c35ec-34d9-4315-ab31-7ea8889a419a
+///    · ──┬─
+///    ·   ╰── expected the Cust prefix
+///    ╰────
+/// ```
+impl Diagnostic for IdError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(IdError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let class = self.expected()?;
+        Some(Box::new(format!(
+            "public ids of this class look like {}_c40bea18-c0c9-44b1-bd0c-43f5283e1670",
+            class.prefix()
+        )))
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.input.as_ref().map(|input| input as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let input = self.input()?;
+        let position = self.position()?.min(input.len());
+        let label = match self.kind() {
+            IdErrorKind::WrongClass | IdErrorKind::UnknownPrefix => {
+                let end = input.find('_').unwrap_or(input.len());
+                let text = match self.expected() {
+                    Some(class) => format!("expected the {} prefix", class.prefix()),
+                    None => "unexpected prefix".to_string(),
+                };
+                LabeledSpan::new_with_span(Some(text), 0..end)
+            }
+            IdErrorKind::TooLong => LabeledSpan::new_with_span(
+                Some("longer than any id".to_string()),
+                position..input.len(),
+            ),
+            _ if std::error::Error::source(self).is_some() => {
+                LabeledSpan::new_with_span(Some("invalid UUID".to_string()), position..input.len())
+            }
+            _ => LabeledSpan::new_with_span(Some("expected `_`".to_string()), position..position),
+        };
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, miette::Diagnostic};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    fn labels(error: &IdError) -> Vec<(usize, usize, String)> {
+        error
+            .labels()
+            .unwrap()
+            .map(|label| {
+                let text = label.label().unwrap().to_string();
+                (label.offset(), label.len(), text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_labels() {
+        let error = Id::<Customer>::from_public_id(&format!("Cont_{ID}")).unwrap_err();
+        assert_eq!(
+            Diagnostic::code(&error).unwrap().to_string(),
+            "KIND_WRONG_CLASS"
+        );
+        assert_eq!(
+            labels(&error),
+            [(0, 4, "expected the Cust prefix".to_string())]
+        );
+        assert!(error.source_code().is_some());
+
+        let error = Id::<Customer>::from_public_id("Cust_371c35ec").unwrap_err();
+        assert_eq!(labels(&error), [(5, 8, "invalid UUID".to_string())]);
+
+        let error = Id::<Customer>::from_public_id(&format!("Cust{ID}")).unwrap_err();
+        assert_eq!(labels(&error), [(4, 0, "expected `_`".to_string())]);
+
+        let error = Id::<Customer>::parse_any_untrusted(&format!("Cust_{ID}{ID}")).unwrap_err();
+        assert_eq!(labels(&error), [(50, 14, "longer than any id".to_string())]);
+
+        let error = IdError::new(IdErrorKind::InvalidFormat);
+        assert!(error.labels().is_none());
+        assert!(error.help().is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Checks of the implicit typing of uuid columns, before trusting
+//! `from_db_id` and the sqlx decoding of ids.
+//!
+//! Given the class of the ids of each table, the check reads the foreign
+//! keys of `uuid` columns in the catalog, and verifies each value of those
+//! columns is the id of a row of the referenced table. Columns holding ids
+//! without a foreign key constraint can be declared as references too.
+//!
+//! When a value isn't found in the referenced table, the other tables of
+//! the mapping are searched, so that the violation tells which class the
+//! value actually has.
+//!
+//! ```no_run
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Inv")]
+//! pub struct Invoice {}
+//!
+//! async fn check(conn: &mut sqlx::PgConnection) -> Result<(), sqlx::Error> {
+//!     let violations = migrate::IdCheck::new()
+//!         .table::<Customer>("customer")
+//!         .table::<Invoice>("billing.invoice")
+//!         .reference("audit_log", "customer", "customer")
+//!         .sample(10.0)
+//!         .run(conn)
+//!         .await?;
+//!     for violation in violations {
+//!         println!("{violation}");
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Tables are named like in queries, with their schema unless it's
+//! `public`, and are expected to have an `id` column of type `uuid`.
+
+use {
+    crate::*,
+    sqlx::PgConnection,
+    std::{
+        collections::{BTreeMap, BTreeSet, HashMap},
+        fmt,
+    },
+    uuid::Uuid,
+};
+
+/// A column holding ids of the rows of another table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub table: String,
+    pub column: String,
+    pub target: String,
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{} -> {}", self.table, self.column, self.target)
+    }
+}
+
+/// A breach of the typing assumptions found by an [`IdCheck`]
+#[derive(Debug, Clone)]
+pub enum Violation {
+    /// The referenced table isn't in the mapping, so the class
+    /// of the column is unknown
+    UnknownTarget { reference: Reference },
+    /// A value of the column isn't the id of a row of the referenced
+    /// table. `found` is the class of the table where it was found, if any.
+    Dangling {
+        reference: Reference,
+        uuid: Uuid,
+        found: Option<IdClass>,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownTarget { reference } => {
+                write!(f, "{reference}: no class for table {}", reference.target)
+            }
+            Self::Dangling {
+                reference,
+                uuid,
+                found: Some(class),
+            } => write!(f, "{reference}: {uuid} is a {} id", class.prefix()),
+            Self::Dangling {
+                reference,
+                uuid,
+                found: None,
+            } => write!(f, "{reference}: {uuid} not found"),
+        }
+    }
+}
+
+/// A check of the uuid foreign keys of a database, see the [module
+/// documentation](self)
+#[derive(Debug, Clone, Default)]
+pub struct IdCheck {
+    classes: BTreeMap<String, IdClass>,
+    references: Vec<Reference>,
+    sample: Option<f64>,
+}
+
+impl IdCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the class of the ids of a table
+    pub fn table<O: Identifiable>(mut self, table: &str) -> Self {
+        self.classes.insert(table.to_string(), O::class());
+        self
+    }
+
+    /// Declare a column holding ids of the rows of the `target` table,
+    /// when there's no foreign key constraint in the database
+    pub fn reference(mut self, table: &str, column: &str, target: &str) -> Self {
+        self.references.push(Reference {
+            table: table.to_string(),
+            column: column.to_string(),
+            target: target.to_string(),
+        });
+        self
+    }
+
+    /// Only check a sample of the rows of each table, given in
+    /// percent, instead of all the rows
+    ///
+    /// A NaN percentage, eg the ratio of empty counts, is ignored and all
+    /// the rows are checked.
+    pub fn sample(mut self, percent: f64) -> Self {
+        self.sample = (!percent.is_nan()).then(|| percent.clamp(0.0, 100.0));
+        self
+    }
+
+    /// Run the check, returning the violations
+    ///
+    /// There's a query per reference, then a query per table of the
+    /// mapping to search the classes of all the dangling values.
+    pub async fn run(&self, conn: &mut PgConnection) -> Result<Vec<Violation>, sqlx::Error> {
+        let mut references = foreign_keys(conn).await?;
+        for reference in &self.references {
+            if !references.contains(reference) {
+                references.push(reference.clone());
+            }
+        }
+        let mut violations = Vec::new();
+        let mut dangling = Vec::new();
+        for reference in references {
+            if !self.classes.contains_key(&reference.target) {
+                violations.push(Violation::UnknownTarget { reference });
+                continue;
+            }
+            let uuids: Vec<Uuid> = sqlx::query_scalar(&dangling_query(&reference, self.sample))
+                .fetch_all(&mut *conn)
+                .await?;
+            dangling.push((reference, uuids));
+        }
+        let uuids: Vec<Uuid> = dangling
+            .iter()
+            .flat_map(|(_, uuids)| uuids.iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let classes = self.find_classes(conn, &uuids).await?;
+        for (reference, uuids) in dangling {
+            for uuid in uuids {
+                violations.push(Violation::Dangling {
+                    reference: reference.clone(),
+                    uuid,
+                    found: classes.get(&uuid).copied(),
+                });
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Search the tables of the mapping for the given ids, with a query
+    /// per table
+    async fn find_classes(
+        &self,
+        conn: &mut PgConnection,
+        uuids: &[Uuid],
+    ) -> Result<HashMap<Uuid, IdClass>, sqlx::Error> {
+        let mut classes = HashMap::new();
+        if uuids.is_empty() {
+            return Ok(classes);
+        }
+        for (table, class) in &self.classes {
+            let query = format!("SELECT id FROM {} WHERE id = ANY($1)", quote_table(table));
+            let found: Vec<Uuid> = sqlx::query_scalar(&query)
+                .bind(uuids)
+                .fetch_all(&mut *conn)
+                .await?;
+            for uuid in found {
+                classes.entry(uuid).or_insert(*class);
+            }
+        }
+        Ok(classes)
+    }
+}
+
+/// Read the single column foreign keys of uuid columns
+async fn foreign_keys(conn: &mut PgConnection) -> Result<Vec<Reference>, sqlx::Error> {
+    let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+        "SELECT sn.nspname::text, s.relname::text, a.attname::text, tn.nspname::text, t.relname::text
+        FROM pg_constraint c
+        JOIN pg_class s ON s.oid = c.conrelid
+        JOIN pg_namespace sn ON sn.oid = s.relnamespace
+        JOIN pg_class t ON t.oid = c.confrelid
+        JOIN pg_namespace tn ON tn.oid = t.relnamespace
+        JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1]
+        WHERE c.contype = 'f' AND cardinality(c.conkey) = 1 AND a.atttypid = 'uuid'::regtype
+        ORDER BY 1, 2, 3",
+    )
+    .fetch_all(conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(schema, table, column, target_schema, target)| Reference {
+            table: table_name(&schema, &table),
+            column,
+            target: table_name(&target_schema, &target),
+        })
+        .collect())
+}
+
+/// The name of a table as given in the mapping
+fn table_name(schema: &str, table: &str) -> String {
+    if schema == "public" {
+        table.to_string()
+    } else {
+        format!("{schema}.{table}")
+    }
+}
+
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn quote_table(table: &str) -> String {
+    match table.split_once('.') {
+        Some((schema, table)) => format!("{}.{}", quote_ident(schema), quote_ident(table)),
+        None => quote_ident(table),
+    }
+}
+
+/// Query the distinct values of the column not found in the target table
+fn dangling_query(reference: &Reference, sample: Option<f64>) -> String {
+    let sample = sample
+        .map(|percent| format!(" TABLESAMPLE SYSTEM ({percent})"))
+        .unwrap_or_default();
+    let column = quote_ident(&reference.column);
+    format!(
+        "SELECT DISTINCT r.{column} FROM {} r{sample} \
+        WHERE r.{column} IS NOT NULL \
+        AND NOT EXISTS (SELECT 1 FROM {} t WHERE t.id = r.{column})",
+        quote_table(&reference.table),
+        quote_table(&reference.target),
+    )
+}
+
+#[test]
+fn test_dangling_query() {
+    let reference = Reference {
+        table: "billing.invoice".to_string(),
+        column: "customer".to_string(),
+        target: "Customer".to_string(),
+    };
+    assert_eq!(
+        dangling_query(&reference, None),
+        r#"SELECT DISTINCT r."customer" FROM "billing"."invoice" r WHERE r."customer" IS NOT NULL AND NOT EXISTS (SELECT 1 FROM "Customer" t WHERE t.id = r."customer")"#,
+    );
+    assert!(dangling_query(&reference, Some(2.5))
+        .contains(r#"FROM "billing"."invoice" r TABLESAMPLE SYSTEM (2.5) WHERE"#));
+    assert_eq!(quote_table(r#"odd"name"#), r#""odd""name""#);
+    assert_eq!(table_name("public", "customer"), "customer");
+    assert_eq!(table_name("billing", "invoice"), "billing.invoice");
+    assert_eq!(IdCheck::new().sample(250.0).sample, Some(100.0));
+    assert_eq!(IdCheck::new().sample(f64::NAN).sample, None);
+}
@@ -0,0 +1,101 @@
+use {
+    super::*,
+    sqlx::{
+        decode::Decode,
+        encode::{Encode, IsNull},
+        error::BoxDynError,
+        mysql::{MySql, MySqlRow, MySqlTypeInfo, MySqlValueRef},
+        types::Uuid,
+        Row, Type,
+    },
+};
+
+/// An Id is bound as 16 bytes, for a `BINARY(16)` column.
+///
+/// It can be read from a `BINARY(16)` column or from a `CHAR(36)` one
+/// holding the hyphenated uuid. To write in a `CHAR(36)` column, bind
+/// `id.uuid().hyphenated()`.
+impl<O: Identifiable> Type<MySql> for Id<O> {
+    fn type_info() -> MySqlTypeInfo {
+        <Uuid as Type<MySql>>::type_info()
+    }
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <Uuid as Type<MySql>>::compatible(ty) || <&str as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<O: Identifiable> Encode<'_, MySql> for Id<O> {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<IsNull, BoxDynError> {
+        <Uuid as Encode<'_, MySql>>::encode_by_ref(&self.uuid(), buf)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, MySql> for Id<O> {
+    fn decode(value: MySqlValueRef<'_>) -> Result<Self, BoxDynError> {
+        let bytes = <&[u8] as Decode<'_, MySql>>::decode(value)?;
+        Ok(Id::from_uuid_column(bytes)?)
+    }
+}
+
+/// Binding an Ided in a query binds its id
+impl<T: Identifiable, E> Type<MySql> for Ided<T, E> {
+    fn type_info() -> MySqlTypeInfo {
+        <Id<T> as Type<MySql>>::type_info()
+    }
+}
+
+impl<T: Identifiable, E> Encode<'_, MySql> for Ided<T, E> {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> Result<IsNull, BoxDynError> {
+        <Id<T> as Encode<'_, MySql>>::encode_by_ref(&self.id(), buf)
+    }
+}
+
+impl<'e, T, E> sqlx::FromRow<'e, MySqlRow> for Ided<T, E>
+where
+    T: Identifiable,
+    E: sqlx::FromRow<'e, MySqlRow>,
+{
+    fn from_row(row: &'e MySqlRow) -> sqlx::Result<Self> {
+        let id = row.try_get::<Id<T>, _>("id")?;
+        let entity = E::from_row(row)?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        sqlx::{mysql::MySqlArguments, Arguments, MySql, Type},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_encoded_as_binary() {
+        let id = Id::<Customer>::random_v4();
+        let mut with_id = MySqlArguments::default();
+        with_id.add(id).unwrap();
+        let mut with_uuid = MySqlArguments::default();
+        with_uuid.add(id.uuid()).unwrap();
+        let mut with_ided = MySqlArguments::default();
+        with_ided.add(Ided::new(id, Customer {})).unwrap();
+        assert_eq!(format!("{with_id:?}"), format!("{with_uuid:?}"));
+        assert_eq!(format!("{with_ided:?}"), format!("{with_uuid:?}"));
+    }
+
+    #[test]
+    fn test_columns() {
+        let binary = <Vec<u8> as Type<MySql>>::type_info();
+        let char = <String as Type<MySql>>::type_info();
+        assert!(<Id<Customer> as Type<MySql>>::compatible(&binary));
+        assert!(<Id<Customer> as Type<MySql>>::compatible(&char));
+
+        let id = Id::<Customer>::random_v4();
+        assert_eq!(Id::from_uuid_column(id.uuid().as_bytes()).unwrap(), id);
+        assert_eq!(Id::from_uuid_column(id.db_id().as_bytes()).unwrap(), id);
+        assert!(Id::<Customer>::from_uuid_column(b"not an uuid").is_err());
+    }
+}
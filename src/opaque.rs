@@ -0,0 +1,374 @@
+//! Opaque public ids, encrypting the uuid so that external ids don't
+//! reveal the creation time of v7 uuids, and can't be cross-referenced
+//! with the ids found in other systems, eg a data warehouse.
+//!
+//! The uuid, a 16 bytes block, is encrypted with AES-256 under a key
+//! derived from the key of the ring and the class prefix, and written
+//! after the prefix and the id of the key, in hexadecimal, followed by a
+//! tag authenticating the class, the key id and the encrypted block:
+//!
+//! ```
+//! use kind::{opaque::KeyRing, *};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let ring = KeyRing::new(1, [42; 32]);
+//! let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+//! let opaque = id.to_opaque(&ring);
+//! assert!(opaque.starts_with("Cust_01"));
+//! assert!(!opaque.contains("371c35ec"));
+//! assert_eq!(Id::<Customer>::from_opaque(&opaque, &ring).unwrap(), id);
+//! ```
+//!
+//! The db id stays the plain uuid.
+//!
+//! Keys can be rotated: a ring encrypts with its current key, but also
+//! decrypts the ids encrypted with its older keys.
+//!
+//! As the class is part of both the encryption key and the tag, an opaque
+//! id can't be relabeled with the prefix of another class: `Inv_<hex>`
+//! made from the opaque id of a customer is rejected. Forged or altered
+//! opaque ids are rejected too.
+//!
+//! With the serde feature, [`OpaqueId`] serializes an id in its opaque
+//! form, and [`OpaqueSeed`] deserializes one, both with an explicit ring.
+
+use {
+    super::*,
+    aes::{
+        cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
+        Aes256,
+    },
+    hmac::{Hmac, Mac},
+    sha2::Sha256,
+    std::fmt,
+    uuid::Uuid,
+};
+
+/// The length of the tag appended to the encrypted uuid
+const TAG_LEN: usize = 8;
+
+/// The length of the bytes written in hexadecimal: key id, encrypted
+/// uuid and tag
+const OPAQUE_LEN: usize = 1 + 16 + TAG_LEN;
+
+/// A set of keys, identified by a byte
+#[derive(Clone)]
+pub struct KeyRing {
+    current: u8,
+    keys: Vec<(u8, Hmac<Sha256>)>,
+}
+
+impl KeyRing {
+    /// Create a ring encrypting with the given key
+    pub fn new(key_id: u8, key: [u8; 32]) -> Self {
+        Self {
+            current: key_id,
+            keys: vec![(key_id, master(&key))],
+        }
+    }
+
+    /// Add a key only used to decrypt ids, eg the previous key after a
+    /// rotation
+    pub fn with_old_key(mut self, key_id: u8, key: [u8; 32]) -> Self {
+        assert!(
+            self.key(key_id).is_none(),
+            "duplicate opaque key id {key_id}"
+        );
+        self.keys.push((key_id, master(&key)));
+        self
+    }
+
+    fn key(&self, key_id: u8) -> Option<&Hmac<Sha256>> {
+        self.keys
+            .iter()
+            .find(|(id, _)| *id == key_id)
+            .map(|(_, key)| key)
+    }
+
+    fn encrypt(&self, class: IdClass, uuid: &Uuid) -> [u8; OPAQUE_LEN] {
+        let key = self
+            .key(self.current)
+            .expect("the current key is in the ring");
+        let mut block = GenericArray::from(*uuid.as_bytes());
+        cipher(key, class).encrypt_block(&mut block);
+        let mut bytes = [0; OPAQUE_LEN];
+        bytes[0] = self.current;
+        bytes[1..17].copy_from_slice(&block);
+        let tag = tag(key, class, &bytes[..17]).finalize().into_bytes();
+        bytes[17..].copy_from_slice(&tag[..TAG_LEN]);
+        bytes
+    }
+
+    fn decrypt(&self, class: IdClass, bytes: &[u8; OPAQUE_LEN]) -> Option<Uuid> {
+        let key = self.key(bytes[0])?;
+        tag(key, class, &bytes[..17])
+            .verify_truncated_left(&bytes[17..])
+            .ok()?;
+        let mut block = GenericArray::clone_from_slice(&bytes[1..17]);
+        cipher(key, class).decrypt_block(&mut block);
+        Some(Uuid::from_bytes(block.into()))
+    }
+}
+
+fn master(key: &[u8; 32]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any size")
+}
+
+/// Return the HMAC of the key with the label and the class prefix, whose
+/// case is ignored as in public ids
+fn derive(key: &Hmac<Sha256>, label: &[u8], class: IdClass) -> Hmac<Sha256> {
+    let mut mac = key.clone();
+    mac.update(label);
+    mac.update(class.prefix().to_ascii_lowercase().as_bytes());
+    mac.update(b"\0");
+    mac
+}
+
+/// Return the cipher of the class
+fn cipher(key: &Hmac<Sha256>, class: IdClass) -> Aes256 {
+    let subkey = derive(key, b"kind opaque cipher\0", class)
+        .finalize()
+        .into_bytes();
+    Aes256::new(&subkey)
+}
+
+/// Return the MAC of the key id and the encrypted block
+fn tag(key: &Hmac<Sha256>, class: IdClass, bytes: &[u8]) -> Hmac<Sha256> {
+    let mut mac = derive(key, b"kind opaque tag\0", class);
+    mac.update(bytes);
+    mac
+}
+
+/// The keys aren't written
+impl fmt::Debug for KeyRing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key_ids: Vec<u8> = self.keys.iter().map(|(id, _)| *id).collect();
+        f.debug_struct("KeyRing")
+            .field("current", &self.current)
+            .field("key_ids", &key_ids)
+            .finish()
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+fn hex_value(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Return the opaque public id, encrypted with the current key of
+    /// the ring
+    pub fn to_opaque(&self, ring: &KeyRing) -> String {
+        let bytes = ring.encrypt(O::class(), &self.uuid);
+        let prefix = self.class().prefix();
+        let mut opaque = String::with_capacity(prefix.len() + 1 + 2 * OPAQUE_LEN);
+        opaque.push_str(prefix);
+        opaque.push('_');
+        for byte in bytes {
+            opaque.push(HEX[(byte >> 4) as usize] as char);
+            opaque.push(HEX[(byte & 0xf) as usize] as char);
+        }
+        opaque
+    }
+
+    /// Parse an opaque public id, checking the class, and decrypting
+    /// it with the key of the ring it was encrypted with
+    pub fn from_opaque(opaque: &str, ring: &KeyRing) -> Result<Self, IdError> {
+        let class = O::class();
+        let hex = class.strip_prefix(opaque)?;
+        let position = opaque.len() - hex.len();
+        let error = |position| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(opaque)
+                .with_expected(class)
+                .with_position(position)
+        };
+        if hex.len() != 2 * OPAQUE_LEN {
+            return Err(error(position));
+        }
+        let mut bytes = [0; OPAQUE_LEN];
+        for (i, pair) in hex.as_bytes().chunks(2).enumerate() {
+            let (Some(high), Some(low)) = (hex_value(pair[0]), hex_value(pair[1])) else {
+                return Err(error(position + 2 * i));
+            };
+            bytes[i] = high << 4 | low;
+        }
+        ring.decrypt(class, &bytes)
+            .map(Self::unchecked)
+            .ok_or_else(|| error(position))
+    }
+}
+
+/// An id serialized in its opaque form, see [`Id::to_opaque`]
+///
+/// ```
+/// use kind::{opaque::{KeyRing, OpaqueId, OpaqueSeed}, *};
+/// use serde::de::DeserializeSeed;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let ring = KeyRing::new(1, [42; 32]);
+/// let id = Id::<Customer>::random_v4();
+/// let json = serde_json::to_string(&OpaqueId::new(id, &ring)).unwrap();
+/// let mut deserializer = serde_json::Deserializer::from_str(&json);
+/// let seed = OpaqueSeed::<Customer>::new(&ring);
+/// assert_eq!(seed.deserialize(&mut deserializer).unwrap(), id);
+/// ```
+#[cfg(feature = "serde")]
+pub struct OpaqueId<'r, O: Identifiable> {
+    id: Id<O>,
+    ring: &'r KeyRing,
+}
+
+#[cfg(feature = "serde")]
+impl<'r, O: Identifiable> OpaqueId<'r, O> {
+    pub fn new(id: Id<O>, ring: &'r KeyRing) -> Self {
+        Self { id, ring }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for OpaqueId<'_, O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id.to_opaque(self.ring))
+    }
+}
+
+/// The seed deserializing an opaque id with a ring, see [`OpaqueId`]
+#[cfg(feature = "serde")]
+pub struct OpaqueSeed<'r, O: Identifiable> {
+    ring: &'r KeyRing,
+    phantom: std::marker::PhantomData<O>,
+}
+
+#[cfg(feature = "serde")]
+impl<'r, O: Identifiable> OpaqueSeed<'r, O> {
+    pub fn new(ring: &'r KeyRing) -> Self {
+        Self {
+            ring,
+            phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::de::DeserializeSeed<'de> for OpaqueSeed<'_, O> {
+    type Value = Id<O>;
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<Id<O>, D::Error> {
+        let opaque = <std::borrow::Cow<str> as serde::Deserialize>::deserialize(deserializer)?;
+        Id::from_opaque(&opaque, self.ring).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{opaque::KeyRing, *};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[test]
+    fn test_opaque_round_trip() {
+        let ring = KeyRing::new(1, [1; 32]);
+        for _ in 0..100 {
+            let id = Id::<Customer>::random_v4();
+            let opaque = id.to_opaque(&ring);
+            assert_eq!(opaque.len(), 5 + 2 * 25);
+            assert_eq!(opaque, id.to_opaque(&ring));
+            assert_ne!(&opaque[7..], id.uuid().simple().to_string());
+            assert_eq!(Id::from_opaque(&opaque, &ring), Ok(id));
+            assert_eq!(Id::from_opaque(&opaque.to_uppercase(), &ring), Ok(id));
+        }
+    }
+
+    #[test]
+    fn test_opaque_key_rotation() {
+        let id = Id::<Customer>::random_v4();
+        let old_ring = KeyRing::new(1, [1; 32]);
+        let old_opaque = id.to_opaque(&old_ring);
+        let ring = KeyRing::new(2, [2; 32]).with_old_key(1, [1; 32]);
+        let opaque = id.to_opaque(&ring);
+        assert!(opaque.starts_with("Cust_02"));
+        assert_ne!(opaque[7..], old_opaque[7..]);
+        assert_eq!(Id::from_opaque(&old_opaque, &ring), Ok(id));
+        assert_eq!(Id::from_opaque(&opaque, &ring), Ok(id));
+        assert!(Id::<Customer>::from_opaque(&opaque, &old_ring).is_err());
+        assert_eq!(
+            format!("{ring:?}"),
+            "KeyRing { current: 2, key_ids: [2, 1] }"
+        );
+    }
+
+    #[test]
+    fn test_opaque_errors() {
+        let ring = KeyRing::new(1, [1; 32]);
+        let opaque = Id::<Customer>::random_v4().to_opaque(&ring);
+        let error = Id::<Contract>::from_opaque(&opaque, &ring).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::WrongClass);
+        let error = Id::<Customer>::from_opaque(&opaque[..30], &ring).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+        let error =
+            Id::<Customer>::from_opaque(&format!("{}zz", &opaque[..53]), &ring).unwrap_err();
+        assert_eq!(error.position(), Some(53));
+        let plain = Id::<Customer>::random_v4().public_id();
+        assert!(Id::<Customer>::from_opaque(&plain, &ring).is_err());
+    }
+
+    #[test]
+    fn test_relabeled_opaque_id() {
+        let ring = KeyRing::new(1, [1; 32]);
+        let customer = Id::<Customer>::random_v4();
+        let opaque = customer.to_opaque(&ring);
+        let relabeled = opaque.replacen("Cust", "Cont", 1);
+        let error = Id::<Contract>::from_opaque(&relabeled, &ring).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+        let contract = Id::<Contract>::from_uuid_trusted(customer.uuid());
+        assert_ne!(contract.to_opaque(&ring)[5..], opaque[5..]);
+
+        // altering any byte is detected
+        for i in 5..opaque.len() {
+            let mut altered = opaque.clone().into_bytes();
+            altered[i] = if altered[i] == b'0' { b'1' } else { b'0' };
+            let altered = String::from_utf8(altered).unwrap();
+            assert!(
+                Id::<Customer>::from_opaque(&altered, &ring).is_err(),
+                "{altered}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_opaque_serde() {
+        use {
+            crate::opaque::{OpaqueId, OpaqueSeed},
+            serde::de::DeserializeSeed,
+        };
+        let ring = KeyRing::new(7, [7; 32]);
+        let id = Id::<Customer>::random_v4();
+        let json = serde_json::to_value(OpaqueId::new(id, &ring)).unwrap();
+        assert!(json.as_str().unwrap().starts_with("Cust_07"));
+        let seed = OpaqueSeed::<Customer>::new(&ring);
+        assert_eq!(seed.deserialize(json.clone()).unwrap(), id);
+        let other_ring = KeyRing::new(7, [8; 32]);
+        assert!(OpaqueSeed::<Customer>::new(&other_ring)
+            .deserialize(json)
+            .is_err());
+    }
+}
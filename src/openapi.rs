@@ -1,7 +1,20 @@
 #[cfg(feature = "serde")]
 use serde_json::json;
-use utoipa::openapi::Schema;
+#[cfg(feature = "registry")]
+use utoipa::openapi::{Components, ComponentsBuilder, OpenApi};
+use {
+    super::*,
+    std::sync::Mutex,
+    utoipa::{
+        openapi::{
+            path::{Parameter, ParameterBuilder, ParameterIn},
+            AllOfBuilder, ObjectBuilder, OneOfBuilder, RefOr, Required, Schema, SchemaType,
+        },
+        IntoParams, ToSchema,
+    },
+};
 
+#[deprecated(note = "use `kind::openapi::components()` or the `ToSchema` impl of `Id`")]
 pub fn openapi_schema() -> (&'static str, Schema) {
     let schema = utoipa::openapi::ObjectBuilder::new()
         .schema_type(utoipa::openapi::SchemaType::String)
@@ -12,3 +25,245 @@ pub fn openapi_schema() -> (&'static str, Schema) {
     let schema = schema.example(Some(json!("Cust_c40bea18-c0c9-44b1-bd0c-43f5283e1670")));
     ("Id", schema.into())
 }
+
+/// The names of the schemas, which must be `'static` for utoipa, so
+/// are leaked once per name
+static SCHEMA_NAMES: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+fn static_name(name: String) -> &'static str {
+    let mut names = SCHEMA_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(name) = names.iter().find(|n| **n == name) {
+        return name;
+    }
+    let name: &'static str = Box::leak(name.into_boxed_str());
+    names.push(name);
+    name
+}
+
+fn schema_name(class: IdClass) -> &'static str {
+    static_name(format!("{}_uuid", class.prefix()))
+}
+
+/// The string schema of the public ids of a class, with the pattern
+/// checking the prefix
+fn class_schema(class: IdClass) -> Schema {
+    let schema = ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .description(Some(format!(
+            "Unique identifier of a {0} object: the {0} prefix and a UUID",
+            class.prefix()
+        )))
+        .pattern(Some(class.public_id_pattern()));
+    #[cfg(feature = "serde")]
+    let schema = schema.example(Some(json!(class.example_public_id())));
+    schema.into()
+}
+
+/// An Id has a schema per class, named like the JSON schema
+/// (eg `Cust_uuid`), giving the expected prefix.
+///
+/// As utoipa refers to the fields of derived schemas by the name of
+/// their type, `Id` fields should be inlined:
+///
+/// ```ignore
+/// #[derive(ToSchema)]
+/// pub struct Invoice {
+///     #[schema(inline)]
+///     customer: Id<Customer>,
+/// }
+/// ```
+impl<'s, O: Identifiable> ToSchema<'s> for Id<O> {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        (schema_name(O::class()), class_schema(O::class()).into())
+    }
+}
+
+/// An Ided is an object with the required id field, and the fields of
+/// the entity, like when serialized with serde. Its schema is named
+/// after the one of the entity (eg `Customer_ided`).
+impl<'s, T: Identifiable, E: ToSchema<'s>> ToSchema<'s> for Ided<T, E> {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let (entity_name, entity) = E::schema();
+        let id = ObjectBuilder::new()
+            .property(T::ID_FIELD, class_schema(T::class()))
+            .required(T::ID_FIELD);
+        let schema = AllOfBuilder::new()
+            .item(id)
+            .item(entity)
+            .description(Some(format!("Identified version of {entity_name}")));
+        (static_name(format!("{entity_name}_ided")), schema.into())
+    }
+}
+
+/// The schema of an enumeration declared with `id_enum!`: one of the
+/// schemas of the ids of the classes
+#[doc(hidden)]
+pub fn __id_enum_schema(classes: &[IdClass]) -> RefOr<Schema> {
+    let mut schema = OneOfBuilder::new();
+    for class in classes {
+        schema = schema.item(class_schema(*class));
+    }
+    schema.into()
+}
+
+/// Return the schemas of the ids of all the registered classes, named
+/// like the schemas of the `ToSchema` impl of `Id` (eg `Cust_uuid`)
+#[cfg(feature = "registry")]
+pub fn components() -> Components {
+    let mut components = ComponentsBuilder::new();
+    for registered in registry::classes() {
+        let class = registered.class();
+        components = components.schema(schema_name(class), class_schema(class));
+    }
+    components.build()
+}
+
+/// Add the schemas of the ids of all the registered classes to the
+/// components of the document, keeping the schemas already there
+///
+/// ```ignore
+/// let mut openapi = ApiDoc::openapi();
+/// kind::openapi::merge_components(&mut openapi);
+/// ```
+#[cfg(feature = "registry")]
+pub fn merge_components(openapi: &mut OpenApi) {
+    let schemas = &mut openapi
+        .components
+        .get_or_insert_with(Default::default)
+        .schemas;
+    for (name, schema) in components().schemas {
+        schemas.entry(name).or_insert(schema);
+    }
+}
+
+/// An Id is a parameter named like the id field of the class (`id` by
+/// default), in the path unless specified otherwise:
+///
+/// ```ignore
+/// #[utoipa::path(get, path = "/customers/{id}", params(Id<Customer>))]
+/// async fn get_customer(id: Id<Customer>) -> Json<Ided<Customer>> {
+///     // ...
+/// }
+/// ```
+impl<O: Identifiable> IntoParams for Id<O> {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![ParameterBuilder::new()
+            .name(O::ID_FIELD)
+            .parameter_in(parameter_in_provider().unwrap_or_default())
+            .required(Required::True)
+            .description(Some(format!(
+                "Public id of a {} object",
+                O::class().prefix()
+            )))
+            .schema(Some(class_schema(O::class())))
+            .build()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        utoipa::{
+            openapi::{path::ParameterIn, RefOr, Schema},
+            IntoParams, ToSchema,
+        },
+    };
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, ToSchema)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+    }
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont", id_field = "contract")]
+    pub struct Contract {}
+
+    fn pattern(schema: &RefOr<Schema>) -> Option<String> {
+        match schema {
+            RefOr::T(Schema::Object(object)) => object.pattern.clone(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let (name, schema) = <Id<Customer> as ToSchema>::schema();
+        assert_eq!(name, "Cust_uuid");
+        assert_eq!(
+            pattern(&schema).unwrap(),
+            "^Cust_[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"
+        );
+        assert_eq!(<Id<Contract> as ToSchema>::schema().0, "Cont_uuid");
+        assert!(std::ptr::eq(name, <Id<Customer> as ToSchema>::schema().0));
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn test_components() {
+        let components = openapi::components();
+        assert!(pattern(&components.schemas["Cust_uuid"])
+            .unwrap()
+            .starts_with("^Cust_"));
+        assert!(components.schemas.contains_key("Cont_uuid"));
+
+        let mut openapi = utoipa::openapi::OpenApiBuilder::new()
+            .components(Some(
+                utoipa::openapi::ComponentsBuilder::new()
+                    .schema("Cust_uuid", utoipa::openapi::ObjectBuilder::new())
+                    .build(),
+            ))
+            .build();
+        openapi::merge_components(&mut openapi);
+        let schemas = openapi.components.unwrap().schemas;
+        assert_eq!(pattern(&schemas["Cust_uuid"]), None);
+        assert!(schemas.contains_key("Cont_uuid"));
+    }
+
+    #[test]
+    fn test_params() {
+        let params = Id::<Contract>::into_params(|| Some(ParameterIn::Query));
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].name, "contract");
+        assert!(matches!(params[0].parameter_in, ParameterIn::Query));
+        assert!(pattern(params[0].schema.as_ref().unwrap())
+            .unwrap()
+            .starts_with("^Cont_"));
+        let params = Id::<Customer>::into_params(|| None);
+        assert!(matches!(params[0].parameter_in, ParameterIn::Path));
+    }
+
+    #[test]
+    fn test_ided_schema() {
+        let (name, schema) = <Ided<Customer> as ToSchema>::schema();
+        assert_eq!(name, "Customer_ided");
+        let RefOr::T(Schema::AllOf(all_of)) = schema else {
+            panic!("Ided should be all of the id and the entity");
+        };
+        let RefOr::T(Schema::Object(id)) = &all_of.items[0] else {
+            panic!("the id should be an object");
+        };
+        assert_eq!(id.required, ["id"]);
+        assert!(pattern(&id.properties["id"]).unwrap().starts_with("^Cust_"));
+        let RefOr::T(Schema::Object(entity)) = &all_of.items[1] else {
+            panic!("the entity should be inlined");
+        };
+        assert!(entity.properties.contains_key("name"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_id_enum_schema() {
+        id_enum! { PartyId: Customer, Contract }
+        let (name, schema) = <PartyId as ToSchema>::schema();
+        assert_eq!(name, "PartyId");
+        let RefOr::T(Schema::OneOf(one_of)) = schema else {
+            panic!("an id enum should be one of the ids");
+        };
+        let patterns: Vec<_> = one_of.items.iter().filter_map(pattern).collect();
+        assert_eq!(patterns.len(), 2);
+        assert!(patterns[1].starts_with("^Cont_"));
+    }
+}
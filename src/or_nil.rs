@@ -0,0 +1,153 @@
+use {super::*, std::fmt, uuid::Uuid};
+
+/// An optional id, for schemas where the nil uuid (all zeros) means
+/// "unset"
+///
+/// `Id` deliberately doesn't implement `Default`, as no id is a sensible
+/// default and a nil id would silently pass for a real one. When a schema
+/// uses the nil uuid as a placeholder, wrap the id in `OrNil` instead:
+/// the nil uuid is read as `None`, and `None` is written as the nil uuid.
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let unset: OrNil<Id<Customer>> = uuid::Uuid::nil().into();
+/// assert_eq!(unset.get(), None);
+/// assert_eq!(unset.uuid(), uuid::Uuid::nil());
+///
+/// let id = Id::<Customer>::random_v4();
+/// assert_eq!(OrNil::from(id.uuid()).get(), Some(id));
+/// ```
+///
+/// With the serde feature, `None` is serialized as the nil public id, eg
+/// `Cust_00000000-0000-0000-0000-000000000000`, and both this id and
+/// `null` are deserialized as `None`. With the sqlx feature, it's stored
+/// in a non nullable `uuid` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrNil<T>(Option<T>);
+
+impl<O: Identifiable> OrNil<Id<O>> {
+    pub fn new(id: Option<Id<O>>) -> Self {
+        Self(id)
+    }
+    /// Return the unset value
+    pub fn nil() -> Self {
+        Self(None)
+    }
+    /// Return the id, unless it's unset
+    pub fn get(self) -> Option<Id<O>> {
+        self.0
+    }
+    pub fn is_nil(self) -> bool {
+        self.0.is_none()
+    }
+    /// Return the uuid, the nil one when unset
+    pub fn uuid(self) -> Uuid {
+        self.0.map_or(Uuid::nil(), |id| id.uuid())
+    }
+}
+
+/// The default value is unset
+impl<T> Default for OrNil<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+/// The nil uuid is unset, other uuids are taken as ids of the class
+impl<O: Identifiable> From<Uuid> for OrNil<Id<O>> {
+    fn from(uuid: Uuid) -> Self {
+        Self((!uuid.is_nil()).then(|| Id::unchecked(uuid)))
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for OrNil<Id<O>> {
+    fn from(id: Id<O>) -> Self {
+        Self(Some(id))
+    }
+}
+
+impl<O: Identifiable> From<Option<Id<O>>> for OrNil<Id<O>> {
+    fn from(id: Option<Id<O>>) -> Self {
+        Self(id)
+    }
+}
+
+impl<O: Identifiable> From<OrNil<Id<O>>> for Option<Id<O>> {
+    fn from(id: OrNil<Id<O>>) -> Self {
+        id.0
+    }
+}
+
+/// Display the public id, the nil one when unset
+impl<O: Identifiable> fmt::Display for OrNil<Id<O>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        O::class().fmt_public_id(&self.uuid(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for OrNil<Id<O>> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Id::<O>::unchecked(self.uuid()).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::Deserialize<'de> for OrNil<Id<O>> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = Option::<Id<O>>::deserialize(deserializer)?;
+        Ok(id.map_or(Self(None), |id| id.uuid().into()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, uuid::Uuid};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const NIL: &str = "Cust_00000000-0000-0000-0000-000000000000";
+
+    #[test]
+    fn test_or_nil() {
+        let id = Id::<Customer>::random_v4();
+        assert_eq!(OrNil::from(Uuid::nil()), OrNil::<Id<Customer>>::nil());
+        assert_eq!(OrNil::<Id<Customer>>::default().to_string(), NIL);
+        assert_eq!(OrNil::from(id).uuid(), id.uuid());
+        assert_eq!(OrNil::from(id).to_string(), id.to_string());
+        assert_eq!(Option::from(OrNil::from(id)), Some(id));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let id = Id::<Customer>::random_v4();
+        let json = serde_json::to_string(&OrNil::from(id)).unwrap();
+        assert_eq!(json, format!("\"{id}\""));
+        assert_eq!(
+            serde_json::from_str::<OrNil<Id<Customer>>>(&json).unwrap(),
+            OrNil::from(id)
+        );
+        let nil = format!("\"{NIL}\"");
+        assert_eq!(
+            serde_json::to_string(&OrNil::<Id<Customer>>::nil()).unwrap(),
+            nil
+        );
+        for json in [nil.as_str(), "null"] {
+            assert!(serde_json::from_str::<OrNil<Id<Customer>>>(json)
+                .unwrap()
+                .is_nil());
+        }
+        assert!(serde_json::from_str::<OrNil<Id<Customer>>>(
+            "\"Cont_00000000-0000-0000-0000-000000000000\""
+        )
+        .is_err());
+    }
+}
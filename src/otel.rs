@@ -0,0 +1,106 @@
+use {
+    super::*,
+    opentelemetry::{KeyValue, StringValue, Value},
+    std::fmt,
+};
+
+impl<O: Identifiable> Id<O> {
+    /// Return the `entity.class` and `entity.id` OpenTelemetry attributes
+    /// of the id, the same keys as the span fields of
+    /// [`entity_span!`](crate::entity_span) with the tracing feature, so
+    /// that all services describe entities the same way:
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+    /// let [class, id] = id.otel_attributes();
+    /// assert_eq!(class.key.as_str(), "entity.class");
+    /// assert_eq!(class.value.as_str(), "Cust");
+    /// assert_eq!(id.key.as_str(), "entity.id");
+    /// ```
+    ///
+    /// The id is the public id, or its redacted form with the "redact"
+    /// feature.
+    pub fn otel_attributes(&self) -> [KeyValue; 2] {
+        [
+            KeyValue::new(ENTITY_CLASS_FIELD, O::class().prefix()),
+            KeyValue::new(ENTITY_ID_FIELD, self.otel_value()),
+        ]
+    }
+
+    /// Return a display adapter writing the value of the `entity.id`
+    /// attribute, for the fields of `#[instrument]`:
+    ///
+    /// ```ignore
+    /// #[instrument(fields(entity.class = "Inv", entity.id = %invoice.otel_value()))]
+    /// async fn charge(invoice: Id<Invoice>) { ... }
+    /// ```
+    pub fn otel_value(&self) -> OtelValue<O> {
+        OtelValue { id: *self }
+    }
+}
+
+/// A display adapter writing the id as the value of its `entity.id`
+/// attribute, see [`Id::otel_value`]
+#[derive(Clone, Copy)]
+pub struct OtelValue<O: Identifiable> {
+    id: Id<O>,
+}
+
+impl<O: Identifiable> fmt::Display for OtelValue<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        #[cfg(not(feature = "redact"))]
+        return fmt::Display::fmt(&self.id, f);
+        #[cfg(feature = "redact")]
+        return fmt::Display::fmt(&self.id.redacted(), f);
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for OtelValue<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<O: Identifiable> From<OtelValue<O>> for Value {
+    fn from(value: OtelValue<O>) -> Self {
+        Value::String(StringValue::from(value.to_string()))
+    }
+}
+
+/// An id is an attribute value written as its public id (redacted with the
+/// "redact" feature)
+impl<O: Identifiable> From<Id<O>> for Value {
+    fn from(id: Id<O>) -> Self {
+        id.otel_value().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, opentelemetry::Value};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_otel_attributes() {
+        let id = Id::<Customer>::random_v4();
+        let [class, entity] = id.otel_attributes();
+        assert_eq!(class.key.as_str(), ENTITY_CLASS_FIELD);
+        assert_eq!(class.value, Value::from("Cust"));
+        assert_eq!(entity.key.as_str(), ENTITY_ID_FIELD);
+        assert_eq!(entity.value, Value::from(id));
+        assert_eq!(entity.value.as_str(), id.otel_value().to_string());
+        #[cfg(not(feature = "redact"))]
+        assert_eq!(id.otel_value().to_string(), id.public_id());
+        #[cfg(feature = "redact")]
+        assert_eq!(id.otel_value().to_string(), id.redacted().to_string());
+    }
+}
@@ -0,0 +1,274 @@
+use {
+    super::*,
+    std::{cmp::Ordering, fmt, hash::Hash, str::FromStr},
+    uuid::Uuid,
+};
+
+/// The id of an association of two objects, eg of a customer and a
+/// contract, for the rows of a join table without surrogate key
+///
+/// The public form is the prefixes of both classes, then both uuids:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cont")]
+/// pub struct Contract {}
+///
+/// let customer: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// let contract: Id<Contract> = "Cont_4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a".parse().unwrap();
+/// let pair = PairId::new(customer, contract);
+/// assert_eq!(
+///     pair.to_string(),
+///     "CustCont_371c35ec-34d9-4315-ab31-7ea8889a419a_4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a",
+/// );
+/// assert_eq!(pair.to_string().parse(), Ok(pair));
+/// assert_eq!(pair.into_parts(), (customer, contract));
+/// ```
+///
+/// When both ids are of the same class, and the association isn't
+/// oriented (eg two customers sharing a billing account), build the pair
+/// with [`PairId::canonical`] so that both orders give the same pair.
+///
+/// With the sqlx feature, it's bound as a composite value of type
+/// `kind_pair_id`, which must be declared with
+/// `CREATE TYPE kind_pair_id AS (a uuid, b uuid)`, and it can also be read
+/// from an anonymous record, eg `SELECT (customer_id, contract_id)`.
+pub struct PairId<A: Identifiable, B: Identifiable> {
+    a: Id<A>,
+    b: Id<B>,
+}
+
+impl<A: Identifiable, B: Identifiable> PairId<A, B> {
+    pub fn new(a: Id<A>, b: Id<B>) -> Self {
+        Self { a, b }
+    }
+    /// Return the first id
+    pub fn a(&self) -> Id<A> {
+        self.a
+    }
+    /// Return the second id
+    pub fn b(&self) -> Id<B> {
+        self.b
+    }
+    pub fn into_parts(self) -> (Id<A>, Id<B>) {
+        (self.a, self.b)
+    }
+    /// Return the public representation as a string
+    pub fn public_id(&self) -> String {
+        self.to_string()
+    }
+    /// Parse a pair from its public representation, checking the
+    /// classes of both ids
+    pub fn from_public_id(public_id: &str) -> Result<Self, IdError> {
+        let (class_a, class_b) = (A::class(), B::class());
+        let bytes = public_id.as_bytes();
+        let prefix_len = class_a.prefix().len() + class_b.prefix().len();
+        let matching = class_a
+            .prefix_bytes()
+            .iter()
+            .chain(class_b.prefix_bytes())
+            .zip(bytes)
+            .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count();
+        if matching < prefix_len || bytes.get(prefix_len) != Some(&b'_') {
+            let mut position = matching.min(public_id.len());
+            while !public_id.is_char_boundary(position) {
+                position -= 1;
+            }
+            return Err(IdError::new(IdErrorKind::WrongClass)
+                .with_input(public_id)
+                .with_expected(if matching < class_a.prefix().len() {
+                    class_a
+                } else {
+                    class_b
+                })
+                .with_position(position));
+        }
+        let error = |class, position| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(public_id)
+                .with_expected(class)
+                .with_position(position)
+        };
+        let rest = &public_id[prefix_len + 1..];
+        let Some((a, b)) = rest.split_once('_') else {
+            return Err(error(class_b, public_id.len()));
+        };
+        let a = Uuid::try_parse_ascii(a.as_bytes())
+            .map_err(|e| error(class_a, prefix_len + 1).with_source(e))?;
+        let b = Uuid::try_parse_ascii(b.as_bytes())
+            .map_err(|e| error(class_b, public_id.len() - b.len()).with_source(e))?;
+        Ok(Self::new(Id::unchecked(a), Id::unchecked(b)))
+    }
+}
+
+impl<O: Identifiable> PairId<O, O> {
+    /// Build the pair of two ids of the same class, the smallest one
+    /// first, so that the pair doesn't depend on their order
+    pub fn canonical(a: Id<O>, b: Id<O>) -> Self {
+        if a <= b {
+            Self::new(a, b)
+        } else {
+            Self::new(b, a)
+        }
+    }
+    /// Tell whether the smallest id is the first one
+    pub fn is_canonical(&self) -> bool {
+        self.a <= self.b
+    }
+    /// Tell whether the id is one of the pair
+    pub fn contains(&self, id: Id<O>) -> bool {
+        self.a == id || self.b == id
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> Clone for PairId<A, B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<A: Identifiable, B: Identifiable> Copy for PairId<A, B> {}
+
+impl<A: Identifiable, B: Identifiable> PartialEq for PairId<A, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a && self.b == other.b
+    }
+}
+impl<A: Identifiable, B: Identifiable> Eq for PairId<A, B> {}
+
+/// Pairs are sorted by first id, then by second id
+impl<A: Identifiable, B: Identifiable> Ord for PairId<A, B> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.a, self.b).cmp(&(other.a, other.b))
+    }
+}
+impl<A: Identifiable, B: Identifiable> PartialOrd for PairId<A, B> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> Hash for PairId<A, B> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.a.hash(state);
+        self.b.hash(state);
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> fmt::Display for PairId<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Uuid::encode_buffer();
+        f.write_str(A::class().prefix())?;
+        f.write_str(B::class().prefix())?;
+        f.write_str("_")?;
+        f.write_str(self.a.uuid.hyphenated().encode_lower(&mut buffer))?;
+        f.write_str("_")?;
+        f.write_str(self.b.uuid.hyphenated().encode_lower(&mut buffer))
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> fmt::Debug for PairId<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PairId")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> FromStr for PairId<A, B> {
+    type Err = IdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_public_id(s)
+    }
+}
+
+/// A pair is serialized as its public representation
+#[cfg(feature = "serde")]
+impl<A: Identifiable, B: Identifiable> serde::Serialize for PairId<A, B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, A: Identifiable, B: Identifiable> serde::Deserialize<'de> for PairId<A, B> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PairIdVisitor<A: Identifiable, B: Identifiable>(std::marker::PhantomData<(A, B)>);
+        impl<A: Identifiable, B: Identifiable> serde::de::Visitor<'_> for PairIdVisitor<A, B> {
+            type Value = PairId<A, B>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a pair public id")
+            }
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                PairId::from_public_id(v).map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(PairIdVisitor(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const A: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+    const B: &str = "4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a";
+
+    #[test]
+    fn test_pair_id_parsing() {
+        let pair: PairId<Customer, Contract> = format!("custCONT_{A}_{B}").parse().unwrap();
+        assert_eq!(pair.a().db_id(), A);
+        assert_eq!(pair.b().db_id(), B);
+        assert_eq!(pair.public_id(), format!("CustCont_{A}_{B}"));
+
+        for (public_id, kind, position) in [
+            (format!("ContCust_{A}_{B}"), IdErrorKind::WrongClass, 1),
+            (format!("CustCust_{A}_{B}"), IdErrorKind::WrongClass, 5),
+            (format!("Cust_{A}_{B}"), IdErrorKind::WrongClass, 4),
+            (format!("CustCont_{A}"), IdErrorKind::InvalidFormat, 45),
+            (format!("CustCont_{A}x_{B}"), IdErrorKind::InvalidFormat, 9),
+            (format!("CustCont_{A}_{B}x"), IdErrorKind::InvalidFormat, 46),
+        ] {
+            let error = PairId::<Customer, Contract>::from_public_id(&public_id).unwrap_err();
+            assert_eq!(error.kind(), kind, "{public_id}");
+            assert_eq!(error.position(), Some(position), "{public_id}");
+        }
+    }
+
+    #[test]
+    fn test_canonical_pair() {
+        let (a, b) = (Id::<Customer>::random_v4(), Id::<Customer>::random_v4());
+        let pair = PairId::canonical(a, b);
+        assert_eq!(pair, PairId::canonical(b, a));
+        assert!(pair.is_canonical());
+        assert!(pair.contains(a) && pair.contains(b));
+        let (first, second) = pair.into_parts();
+        assert!(!PairId::new(second, first).is_canonical());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_pair_id_serde() {
+        let pair = PairId::new(Id::<Customer>::random_v4(), Id::<Contract>::random_v4());
+        let json = serde_json::to_string(&pair).unwrap();
+        assert_eq!(json, format!("\"{pair}\""));
+        let parsed: PairId<Customer, Contract> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, pair);
+        assert!(serde_json::from_str::<PairId<Contract, Customer>>(&json).is_err());
+    }
+}
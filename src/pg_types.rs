@@ -0,0 +1,105 @@
+use {
+    super::*,
+    bytes::BytesMut,
+    postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type},
+    std::{error::Error, fmt},
+    uuid::Uuid,
+};
+
+/// An Id is written in `uuid` columns, with no check nor conversion,
+/// for services using tokio-postgres (or deadpool-postgres) directly:
+///
+/// ```ignore
+/// let row = client
+///     .query_one("SELECT name FROM customer WHERE id = $1", &[&id])
+///     .await?;
+/// ```
+///
+/// Slices and `Vec` of ids are written as `uuid[]`.
+impl<O: Identifiable> ToSql for Id<O> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.uuid.to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <Uuid as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+impl<'a, O: Identifiable> FromSql<'a> for Id<O> {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Uuid::from_sql(ty, raw).map(Id::unchecked)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <Uuid as FromSql>::accepts(ty)
+    }
+}
+
+/// Binding an Ided in a query binds its id
+impl<T: Identifiable + fmt::Debug, E: fmt::Debug> ToSql for Ided<T, E> {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.id().to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <Id<T> as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        bytes::BytesMut,
+        postgres_types::{FromSql, ToSql, Type},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    fn encode(value: &dyn ToSql, ty: &Type) -> BytesMut {
+        let mut out = BytesMut::new();
+        value.to_sql_checked(ty, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_encoded_as_uuid() {
+        let id = Id::<Customer>::random_v4();
+        assert_eq!(encode(&id, &Type::UUID), encode(&id.uuid(), &Type::UUID));
+        assert_eq!(
+            encode(&Ided::new(id, Customer {}), &Type::UUID),
+            encode(&id.uuid(), &Type::UUID)
+        );
+        assert_eq!(
+            encode(&vec![id], &Type::UUID_ARRAY),
+            encode(&vec![id.uuid()], &Type::UUID_ARRAY)
+        );
+        assert!(id
+            .to_sql_checked(&Type::TEXT, &mut BytesMut::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_decoded_from_uuid() {
+        let id = Id::<Customer>::random_v4();
+        let raw = encode(&id, &Type::UUID);
+        assert_eq!(Id::<Customer>::from_sql(&Type::UUID, &raw).unwrap(), id);
+        assert!(<Id<Customer> as FromSql>::accepts(&Type::UUID));
+        assert!(!<Id<Customer> as FromSql>::accepts(&Type::TEXT));
+    }
+}
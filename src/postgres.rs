@@ -4,12 +4,19 @@ use {
         decode::Decode,
         encode::{Encode, IsNull},
         error::BoxDynError,
-        postgres::{PgArgumentBuffer, PgHasArrayType, PgRow, PgTypeInfo, PgValueRef, Postgres},
+        postgres::{
+            types::{Oid, PgRecordDecoder, PgRecordEncoder},
+            PgArgumentBuffer, PgHasArrayType, PgRow, PgTypeInfo, PgValueFormat, PgValueRef,
+            Postgres,
+        },
         types::Uuid,
-        Row, Type,
+        ColumnIndex, Row, Type,
     },
 };
 
+#[cfg(feature = "serde")]
+use sqlx::types::Json;
+
 impl<O: Identifiable> Type<Postgres> for Id<O> {
     fn type_info() -> PgTypeInfo {
         <Uuid as Type<Postgres>>::type_info()
@@ -26,7 +33,7 @@ impl<O: Identifiable> PgHasArrayType for Id<O> {
 
 impl<O: Identifiable> Encode<'_, Postgres> for Id<O> {
     fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
-        self.uuid().encode_by_ref(buf)
+        <Uuid as Encode<'_, Postgres>>::encode_by_ref(&self.uuid(), buf)
     }
 }
 
@@ -38,6 +45,430 @@ impl<O: Identifiable> Decode<'_, Postgres> for Id<O> {
     }
 }
 
+/// Binding an Ided in a query binds its id, eg for a
+/// `WHERE id = $1` clause
+impl<T: Identifiable, E> Type<Postgres> for Ided<T, E> {
+    fn type_info() -> PgTypeInfo {
+        <Uuid as Type<Postgres>>::type_info()
+    }
+}
+
+/// Make it possible to bind a slice of Ided as the
+/// array of their ids
+impl<T: Identifiable, E> PgHasArrayType for Ided<T, E> {
+    fn array_type_info() -> PgTypeInfo {
+        <Uuid as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<T: Identifiable, E> Encode<'_, Postgres> for Ided<T, E> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <Id<T> as Encode<'_, Postgres>>::encode_by_ref(&self.id(), buf)
+    }
+}
+
+/// Binding an IdMap in a query binds the `UUID[]` array of its keys,
+/// eg for a `WHERE id = ANY($1)` clause
+impl<O: Identifiable, V> Type<Postgres> for IdMap<O, V> {
+    fn type_info() -> PgTypeInfo {
+        <Uuid as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<O: Identifiable, V> Encode<'_, Postgres> for IdMap<O, V> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        let ids: Vec<Id<O>> = self.keys().copied().collect();
+        <Vec<Id<O>> as Encode<'_, Postgres>>::encode(ids, buf)
+    }
+}
+
+/// An IdSet is bound, and decoded, as a `UUID[]` array
+impl<O: Identifiable> Type<Postgres> for IdSet<O> {
+    fn type_info() -> PgTypeInfo {
+        <Uuid as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<O: Identifiable> Encode<'_, Postgres> for IdSet<O> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <Vec<Id<O>> as Encode<'_, Postgres>>::encode(self.to_vec(), buf)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Postgres> for IdSet<O> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let ids = <Vec<Id<O>> as Decode<'_, Postgres>>::decode(value)?;
+        Ok(ids.into_iter().collect())
+    }
+}
+
+/// A reference is stored like the referenced id, in a UUID column
+impl<O: Identifiable> Type<Postgres> for Ref<O> {
+    fn type_info() -> PgTypeInfo {
+        <Uuid as Type<Postgres>>::type_info()
+    }
+}
+
+impl<O: Identifiable> PgHasArrayType for Ref<O> {
+    fn array_type_info() -> PgTypeInfo {
+        <Uuid as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<O: Identifiable> Encode<'_, Postgres> for Ref<O> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <Id<O> as Encode<'_, Postgres>>::encode_by_ref(&self.id(), buf)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Postgres> for Ref<O> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        <Id<O> as Decode<'_, Postgres>>::decode(value).map(Ref::new)
+    }
+}
+
+/// An optional id is stored in a non nullable UUID column, the nil uuid
+/// meaning unset
+impl<O: Identifiable> Type<Postgres> for OrNil<Id<O>> {
+    fn type_info() -> PgTypeInfo {
+        <Uuid as Type<Postgres>>::type_info()
+    }
+}
+
+impl<O: Identifiable> PgHasArrayType for OrNil<Id<O>> {
+    fn array_type_info() -> PgTypeInfo {
+        <Uuid as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl<O: Identifiable> Encode<'_, Postgres> for OrNil<Id<O>> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <Uuid as Encode<'_, Postgres>>::encode_by_ref(&self.uuid(), buf)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Postgres> for OrNil<Id<O>> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        <Uuid as Decode<'_, Postgres>>::decode(value).map(OrNil::from)
+    }
+}
+
+/// A path is stored in an LTREE column (the type of the ltree extension)
+impl<O: Identifiable> Type<Postgres> for IdPath<O> {
+    fn type_info() -> PgTypeInfo {
+        // ltree comes from an extension, without a stable OID
+        PgTypeInfo::with_name("ltree")
+    }
+}
+
+impl<O: Identifiable> PgHasArrayType for IdPath<O> {
+    fn array_type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("_ltree")
+    }
+}
+
+impl<O: Identifiable> Encode<'_, Postgres> for IdPath<O> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        // the binary format is the text one after a version byte
+        buf.push(1);
+        buf.extend_from_slice(self.ltree().as_bytes());
+        Ok(IsNull::No)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Postgres> for IdPath<O> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let ltree = match value.format() {
+            PgValueFormat::Binary => match value.as_bytes()? {
+                [1, ltree @ ..] => std::str::from_utf8(ltree)?,
+                _ => return Err("unsupported ltree version".into()),
+            },
+            PgValueFormat::Text => value.as_str()?,
+        };
+        Ok(IdPath::from_ltree(ltree)?)
+    }
+}
+
+/// An id stored in a TEXT (or VARCHAR) column with its public
+/// representation, eg for legacy tables
+///
+/// Decoding checks the class of the stored id.
+///
+/// ```ignore
+/// let row: (PublicText<Id<Customer>>,) = sqlx::query_as("SELECT customer FROM legacy")
+///     .fetch_one(&pool)
+///     .await?;
+/// let customer_id: Id<Customer> = row.0.into_inner();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PublicText<T>(pub T);
+
+impl<T> PublicText<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for PublicText<Id<O>> {
+    fn from(id: Id<O>) -> Self {
+        Self(id)
+    }
+}
+
+impl<O: Identifiable> Type<Postgres> for PublicText<Id<O>> {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<O: Identifiable> PgHasArrayType for PublicText<Id<O>> {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <String as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+impl<O: Identifiable> Encode<'_, Postgres> for PublicText<Id<O>> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <String as Encode<'_, Postgres>>::encode(self.0.public_id(), buf)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Postgres> for PublicText<Id<O>> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let public_id = <&str as Decode<'_, Postgres>>::decode(value)?;
+        Ok(Self(Id::from_public_id(public_id)?))
+    }
+}
+
+/// An AnyId is stored in a TEXT column with its public id, the class
+/// being only known from the prefix
+#[cfg(feature = "registry")]
+impl Type<Postgres> for AnyId {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "registry")]
+impl PgHasArrayType for AnyId {
+    fn array_type_info() -> PgTypeInfo {
+        <String as PgHasArrayType>::array_type_info()
+    }
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <String as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Encode<'_, Postgres> for AnyId {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <String as Encode<'_, Postgres>>::encode(self.public_id(), buf)
+    }
+}
+
+#[cfg(feature = "registry")]
+impl Decode<'_, Postgres> for AnyId {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let public_id = <&str as Decode<'_, Postgres>>::decode(value)?;
+        Ok(AnyId::from_public_id(public_id)?)
+    }
+}
+
+/// Encode a value holding ids, eg the snapshot of an entity in an audit
+/// table, as JSONB, the ids being written as public ids
+///
+/// ```ignore
+/// impl Encode<'_, Postgres> for Snapshot {
+///     fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+///         kind::encode_jsonb(self, buf)
+///     }
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub fn encode_jsonb<T: serde::Serialize>(
+    value: &T,
+    buf: &mut PgArgumentBuffer,
+) -> Result<IsNull, BoxDynError> {
+    <Json<&T> as Encode<'_, Postgres>>::encode(Json(value), buf)
+}
+
+/// Decode a value holding ids from JSONB, checking the class of each
+/// public id it contains
+#[cfg(feature = "serde")]
+pub fn decode_jsonb<T: serde::de::DeserializeOwned>(
+    value: PgValueRef<'_>,
+) -> Result<T, BoxDynError> {
+    match value.format() {
+        PgValueFormat::Binary => decode_jsonb_binary(value.as_bytes()?),
+        PgValueFormat::Text => Ok(serde_json::from_str(value.as_str()?)?),
+    }
+}
+
+/// Decode the binary format of JSONB, the JSON text after a version byte
+#[cfg(feature = "serde")]
+fn decode_jsonb_binary<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, BoxDynError> {
+    match bytes {
+        [1, json @ ..] => Ok(serde_json::from_slice(json)?),
+        _ => Err("unsupported JSONB version".into()),
+    }
+}
+
+/// The oid of the anonymous `RECORD` pseudo type
+const RECORD_OID: Oid = Oid(2249);
+
+/// A scoped id is a `kind_scoped_id` composite, of the tenant and the
+/// object uuids, and can also be decoded from an anonymous record
+impl<T: Identifiable, O: Identifiable> Type<Postgres> for ScopedId<T, O> {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("kind_scoped_id")
+    }
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        *ty == Self::type_info() || *ty == PgTypeInfo::with_oid(RECORD_OID)
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> Encode<'_, Postgres> for ScopedId<T, O> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        let mut encoder = PgRecordEncoder::new(buf);
+        encoder.encode(self.tenant())?;
+        encoder.encode(self.unscoped())?;
+        encoder.finish();
+        Ok(IsNull::No)
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> Decode<'_, Postgres> for ScopedId<T, O> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let mut decoder = PgRecordDecoder::new(value)?;
+        let tenant = decoder.try_decode::<Id<T>>()?;
+        let id = decoder.try_decode::<Id<O>>()?;
+        Ok(ScopedId::new(tenant, id))
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> ScopedId<T, O> {
+    /// Read a scoped id from the tenant and id columns of a row
+    ///
+    /// ```ignore
+    /// let customer = ScopedId::<Organization, Customer>::from_columns(&row, "org_id", "id")?;
+    /// ```
+    pub fn from_columns(
+        row: &PgRow,
+        tenant_column: &str,
+        id_column: &str,
+    ) -> Result<Self, sqlx::Error> {
+        Ok(ScopedId::new(
+            row.try_get(tenant_column)?,
+            row.try_get(id_column)?,
+        ))
+    }
+}
+
+/// A pair id is a `kind_pair_id` composite of both uuids, and can also be
+/// decoded from an anonymous record
+impl<A: Identifiable, B: Identifiable> Type<Postgres> for PairId<A, B> {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("kind_pair_id")
+    }
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        *ty == Self::type_info() || *ty == PgTypeInfo::with_oid(RECORD_OID)
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> Encode<'_, Postgres> for PairId<A, B> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        let mut encoder = PgRecordEncoder::new(buf);
+        encoder.encode(self.a())?;
+        encoder.encode(self.b())?;
+        encoder.finish();
+        Ok(IsNull::No)
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> Decode<'_, Postgres> for PairId<A, B> {
+    fn decode(value: PgValueRef<'_>) -> Result<Self, BoxDynError> {
+        let mut decoder = PgRecordDecoder::new(value)?;
+        let a = decoder.try_decode::<Id<A>>()?;
+        let b = decoder.try_decode::<Id<B>>()?;
+        Ok(PairId::new(a, b))
+    }
+}
+
+impl<A: Identifiable, B: Identifiable> PairId<A, B> {
+    /// Read a pair id from two columns of a row
+    ///
+    /// ```ignore
+    /// let pair = PairId::<Customer, Contract>::from_columns(&row, "customer_id", "contract_id")?;
+    /// ```
+    pub fn from_columns(row: &PgRow, a_column: &str, b_column: &str) -> Result<Self, sqlx::Error> {
+        Ok(PairId::new(row.try_get(a_column)?, row.try_get(b_column)?))
+    }
+}
+
+/// A struct which can be read from a row whose columns are prefixed,
+/// eg from the `c_name` column for the `name` field with the `c_` prefix.
+///
+/// This makes it possible to read several entities from the rows of a JOIN.
+/// The rows are the ones of PostgreSQL unless another `sqlx::Row` type is
+/// given, eg `SqliteRow` with the sqlx-sqlite feature.
+///
+/// It can be implemented by the derive, for structs whose fields
+/// have the same name than the columns:
+///
+/// ```ignore
+/// #[derive(Identifiable)]
+/// #[kind(class = "Cust", prefixed_row)]
+/// pub struct Customer {
+///     pub name: String,
+/// }
+/// ```
+pub trait FromPrefixedRow<R: Row = PgRow>: Sized {
+    fn from_prefixed_row(prefix: &str, row: &R) -> Result<Self, sqlx::Error>;
+}
+
+/// An Ided reads its id from the `<prefix>id` column
+impl<T, E, R> FromPrefixedRow<R> for Ided<T, E>
+where
+    T: Identifiable,
+    E: FromPrefixedRow<R>,
+    R: Row,
+    Id<T>: for<'r> Decode<'r, R::Database> + Type<R::Database>,
+    for<'i> &'i str: ColumnIndex<R>,
+{
+    fn from_prefixed_row(prefix: &str, row: &R) -> Result<Self, sqlx::Error> {
+        let id = row.try_get::<Id<T>, _>(format!("{prefix}id").as_str())?;
+        let entity = E::from_prefixed_row(prefix, row)?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+impl<T: Identifiable, E> Ided<T, E> {
+    /// Read an Ided from prefixed columns, eg `c_id` and `c_name`
+    /// with the `c_` prefix
+    ///
+    /// ```ignore
+    /// // SELECT c.id c_id, c.name c_name, i.id i_id, i.total i_total
+    /// // FROM invoice i JOIN customer c ON c.id = i.customer
+    /// let customer = Ided::<Customer>::from_prefixed_row("c_", &row)?;
+    /// let invoice = Ided::<Invoice>::from_prefixed_row("i_", &row)?;
+    /// ```
+    pub fn from_prefixed_row<R: Row>(prefix: &str, row: &R) -> Result<Self, sqlx::Error>
+    where
+        Self: FromPrefixedRow<R>,
+    {
+        <Self as FromPrefixedRow<R>>::from_prefixed_row(prefix, row)
+    }
+}
+
 impl<'r, T, E> Ided<T, E>
 where
     T: Identifiable,
@@ -52,3 +483,233 @@ where
         Ok(Ided::new(id, entity))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        sqlx::{
+            postgres::{PgArguments, PgRow},
+            Arguments, Encode, Postgres, Type,
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    // only checks the derive generates an implementation for PgRow
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Inv", prefixed_row)]
+    pub struct Invoice {
+        pub customer: Id<Customer>,
+        pub r#type: String,
+        pub total: i64,
+    }
+
+    #[test]
+    fn test_prefixed_row_impls() {
+        type Reader<E> = fn(&str, &PgRow) -> Result<E, sqlx::Error>;
+        let _: Reader<Invoice> = Invoice::from_prefixed_row;
+        let _: Reader<Ided<Invoice>> = Ided::from_prefixed_row;
+    }
+
+    fn bind<'q, T: Encode<'q, Postgres> + Type<Postgres> + 'q>(value: T) {
+        let mut arguments = PgArguments::default();
+        arguments.add(value).unwrap();
+        assert_eq!(arguments.len(), 1);
+    }
+
+    // binding references is what's tested here
+    #[allow(clippy::needless_borrows_for_generic_args)]
+    #[test]
+    fn test_binds() {
+        let id = Id::<Customer>::random_v4();
+        let ids = vec![id, Id::random_v4()];
+        let customers = [Ided::new(id, Customer {})];
+        bind(id);
+        bind(&id);
+        bind(Some(id));
+        bind(None::<Id<Customer>>);
+        bind(&ids[..]);
+        bind(&ids);
+        bind(ids.clone());
+        bind(Some(ids));
+        bind(&customers[0]);
+        bind(&customers[..]);
+        let map: IdMap<Customer, Customer> = customers.into_iter().collect();
+        bind(map.id_set());
+        bind(&map);
+        bind(Ref::new(id));
+        bind(vec![Ref::new(id)]);
+    }
+
+    #[cfg(feature = "events")]
+    #[test]
+    fn test_entity_event() {
+        let event = EntityEvent::new(
+            Id::<Customer>::random_v4(),
+            EntityAction::Created,
+            Id::<Customer>::random_v4(),
+            (),
+        );
+        bind(event.actor());
+        bind(event.action().clone());
+        bind(vec![event.actor()]);
+        fn from_row<'r, E: sqlx::FromRow<'r, PgRow>>() {}
+        from_row::<EntityEvent<Customer, serde_json::Value>>();
+    }
+
+    #[test]
+    fn test_scoped_id() {
+        #[derive(Debug, Identifiable)]
+        #[kind(class = "Org")]
+        pub struct Organization {}
+
+        let scoped = Id::<Customer>::random_v4().scoped(Id::<Organization>::random_v4());
+        bind(scoped);
+        assert_eq!(
+            <ScopedId<Organization, Customer> as Type<Postgres>>::type_info().to_string(),
+            "kind_scoped_id"
+        );
+    }
+
+    #[test]
+    fn test_public_text() {
+        let id = Id::<Customer>::random_v4();
+        bind(PublicText(id));
+        bind(vec![PublicText(id)]);
+        let mut with_text = PgArguments::default();
+        with_text.add(PublicText(id)).unwrap();
+        let mut with_string = PgArguments::default();
+        with_string.add(id.public_id()).unwrap();
+        assert_eq!(format!("{with_text:?}"), format!("{with_string:?}"));
+    }
+
+    /// JSONB payloads hold public ids, whose class is checked
+    /// when decoded with `sqlx::types::Json`
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_jsonb() {
+        use sqlx::{postgres::PgArgumentBuffer, types::Json};
+
+        #[derive(Debug, Identifiable, serde::Serialize, serde::Deserialize)]
+        #[kind(class = "Snap")]
+        pub struct Snapshot {
+            customer: Id<Customer>,
+        }
+
+        let customer = Id::<Customer>::random_v4();
+        let snapshot = Ided::new(Id::<Snapshot>::random_v4(), Snapshot { customer });
+        bind(Json(&snapshot));
+        bind(Json(vec![customer]));
+
+        // the payload is the serde representation, with public ids
+        assert_eq!(
+            serde_json::to_value(&snapshot).unwrap(),
+            serde_json::json!({
+                "id": snapshot.id().public_id(),
+                "customer": customer.public_id(),
+            })
+        );
+
+        // the JSONB value encoded for postgres is decoded back, with the
+        // classes of its ids checked
+        let mut buf = PgArgumentBuffer::default();
+        assert!(matches!(
+            encode_jsonb(&snapshot, &mut buf),
+            Ok(sqlx::encode::IsNull::No)
+        ));
+        assert_eq!(buf[0], 1);
+        let decoded: Ided<Snapshot> = super::decode_jsonb_binary(&buf).unwrap();
+        assert_eq!(decoded.id(), snapshot.id());
+        assert_eq!(decoded.entity().customer, customer);
+        let jsonb = String::from_utf8(buf.to_vec())
+            .unwrap()
+            .replace("Cust_", "Cont_");
+        let error = super::decode_jsonb_binary::<Ided<Snapshot>>(jsonb.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("wrong object class"), "{error}");
+        assert!(super::decode_jsonb_binary::<Ided<Snapshot>>(b"{}").is_err());
+    }
+
+    #[test]
+    fn test_encoded_as_uuid() {
+        let id = Id::<Customer>::random_v4();
+        let mut with_id = PgArguments::default();
+        with_id.add(id).unwrap();
+        let mut with_uuid = PgArguments::default();
+        with_uuid.add(id.uuid()).unwrap();
+        let mut with_ided = PgArguments::default();
+        with_ided.add(Ided::new(id, Customer {})).unwrap();
+        assert_eq!(format!("{with_id:?}"), format!("{with_uuid:?}"));
+        assert_eq!(format!("{with_ided:?}"), format!("{with_uuid:?}"));
+    }
+}
+
+#[cfg(all(test, feature = "sqlx-sqlite"))]
+mod join_test {
+    use {
+        crate::*,
+        sqlx::{sqlite::SqliteRow, Connection, SqliteConnection},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust", prefixed_row)]
+    pub struct Customer {
+        pub name: String,
+    }
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Inv", prefixed_row)]
+    pub struct Invoice {
+        pub customer: Id<Customer>,
+        pub r#type: String,
+        pub total: i64,
+    }
+
+    #[test]
+    fn test_prefixed_row_join() {
+        async_std::task::block_on(async {
+            let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            sqlx::query(
+                "CREATE TABLE customer (id BLOB PRIMARY KEY, name TEXT);
+                CREATE TABLE invoice (id BLOB PRIMARY KEY, customer BLOB, type TEXT, total INT)",
+            )
+            .execute(&mut conn)
+            .await
+            .unwrap();
+            let customer_id = Id::<Customer>::random_v4();
+            let invoice_id = Id::<Invoice>::random_v4();
+            sqlx::query("INSERT INTO customer (id, name) VALUES ($1, 'John')")
+                .bind(customer_id)
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO invoice VALUES ($1, $2, 'credit', 42)")
+                .bind(invoice_id)
+                .bind(customer_id)
+                .execute(&mut conn)
+                .await
+                .unwrap();
+
+            let row: SqliteRow = sqlx::query(
+                "SELECT c.id c_id, c.name c_name,
+                    i.id i_id, i.customer i_customer, i.type i_type, i.total i_total
+                FROM invoice i JOIN customer c ON c.id = i.customer",
+            )
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+            let customer = Ided::<Customer>::from_prefixed_row("c_", &row).unwrap();
+            let invoice = Ided::<Invoice>::from_prefixed_row("i_", &row).unwrap();
+            assert_eq!(customer.id(), customer_id);
+            assert_eq!(customer.name, "John");
+            assert_eq!(invoice.id(), invoice_id);
+            assert_eq!(invoice.customer, customer_id);
+            assert_eq!(invoice.r#type, "credit");
+            assert_eq!(invoice.total, 42);
+            assert!(Ided::<Invoice>::from_prefixed_row("x_", &row).is_err());
+        });
+    }
+}
@@ -0,0 +1,114 @@
+//! Protobuf interop, with the `kind.Id` message of `proto/kind.proto`
+//!
+//! ```
+//! use {kind::*, prost::Message};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let id: Id<Customer> = Id::random_v4();
+//! let bytes = proto::Id::from(id).encode_to_vec();
+//!
+//! let decoded = proto::Id::decode(bytes.as_slice()).unwrap();
+//! assert_eq!(decoded.class, "Cust");
+//! assert_eq!(decoded.to_id::<Customer>(), Ok(id));
+//! ```
+
+use crate::{IdError, IdErrorKind, Identifiable};
+
+/// The `kind.Id` message: the class and the bytes of the UUID
+#[derive(Clone, PartialEq, Eq, Hash, prost::Message)]
+pub struct Id {
+    #[prost(string, tag = "1")]
+    pub class: String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub uuid: Vec<u8>,
+}
+
+impl Id {
+    /// Convert the message into an id, checking the class
+    pub fn to_id<O: Identifiable>(&self) -> Result<crate::Id<O>, IdError> {
+        if !self.class.eq_ignore_ascii_case(O::class().prefix()) {
+            return Err(IdError::new(IdErrorKind::WrongClass)
+                .with_input(&self.class)
+                .with_expected(O::class()));
+        }
+        let uuid = uuid::Uuid::from_slice(&self.uuid).map_err(|e| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_expected(O::class())
+                .with_source(e)
+        })?;
+        Ok(crate::Id::unchecked(uuid))
+    }
+
+    /// Convert a message field, which prost makes optional, into an id
+    pub fn required<O: Identifiable>(field: Option<&Self>) -> Result<crate::Id<O>, IdError> {
+        field
+            .ok_or_else(|| IdError::new(IdErrorKind::InvalidFormat).with_expected(O::class()))?
+            .to_id()
+    }
+}
+
+impl<O: Identifiable> From<crate::Id<O>> for Id {
+    fn from(id: crate::Id<O>) -> Self {
+        Self {
+            class: id.class().prefix().to_string(),
+            uuid: id.uuid().as_bytes().to_vec(),
+        }
+    }
+}
+
+impl<O: Identifiable> TryFrom<&Id> for crate::Id<O> {
+    type Error = IdError;
+    fn try_from(message: &Id) -> Result<Self, IdError> {
+        message.to_id()
+    }
+}
+
+impl<O: Identifiable> TryFrom<Id> for crate::Id<O> {
+    type Error = IdError;
+    fn try_from(message: Id) -> Result<Self, IdError> {
+        message.to_id()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{IdClass, IdErrorKind, Identifiable};
+    use prost::Message;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Test")]
+    struct TestStruct {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Other")]
+    struct Other {}
+
+    #[test]
+    fn test_conversions() {
+        let id = crate::Id::<TestStruct>::random_v4();
+        let message = super::Id::from(id);
+        let decoded = super::Id::decode(message.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(crate::Id::<TestStruct>::try_from(&decoded), Ok(id));
+        assert_eq!(
+            crate::Id::<Other>::try_from(&decoded).unwrap_err().kind(),
+            IdErrorKind::WrongClass
+        );
+
+        let truncated = super::Id {
+            uuid: message.uuid[..8].to_vec(),
+            ..message
+        };
+        assert_eq!(
+            truncated.to_id::<TestStruct>().unwrap_err().kind(),
+            IdErrorKind::InvalidFormat
+        );
+        assert_eq!(
+            super::Id::required::<TestStruct>(None).unwrap_err().kind(),
+            IdErrorKind::InvalidFormat
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Python bindings, behind the `pyo3` feature, so that Python code
+//! parses and validates ids with the rules of the crate, and knows the
+//! registered classes.
+//!
+//! The bindings are added to a module by [`register`], in an extension
+//! crate which links the crates declaring the classes:
+//!
+//! ```ignore
+//! // lib.rs of the kind-py crate, built with maturin
+//! use pyo3::prelude::*;
+//!
+//! extern crate billing_models; // registers the billing classes
+//!
+//! #[pymodule]
+//! fn kind_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+//!     kind::python::register(m)
+//! }
+//! ```
+//!
+//! ```python
+//! from kind_py import KindId, IdError, classes, is_valid_id
+//!
+//! id = KindId("cust_371C35EC-34d9-4315-ab31-7ea8889a419a", "Cust")
+//! str(id)      # "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a"
+//! id.uuid      # UUID("371c35ec-34d9-4315-ab31-7ea8889a419a")
+//! KindId("Cust_371c35ec-34d9-4315-ab31-7ea8889a419a").type_name  # "Customer"
+//! [c.prefix for c in classes()]
+//! ```
+//!
+//! Only the classes of the registry are known: parsing an id of another
+//! class raises an `IdError`, which is a `ValueError` whose arguments are
+//! the message and the code of the error, eg `KIND_UNKNOWN_PREFIX`.
+
+use {
+    super::*,
+    pyo3::{create_exception, exceptions::PyValueError, prelude::*},
+    uuid::Uuid,
+};
+
+create_exception!(kind_py, PyIdError, PyValueError);
+
+impl From<IdError> for PyErr {
+    fn from(error: IdError) -> Self {
+        PyIdError::new_err((error.to_string(), error.code()))
+    }
+}
+
+fn registered(prefix: &str) -> Result<&'static RegisteredClass, IdError> {
+    registry::find(prefix)
+        .ok_or_else(|| IdError::new(IdErrorKind::UnknownPrefix).with_input(prefix))
+}
+
+/// An id of a registered class
+#[pyclass(name = "KindId", module = "kind_py", frozen, eq, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PyKindId {
+    class: IdClass,
+    uuid: Uuid,
+}
+
+impl PyKindId {
+    fn parse(public_id: &str, class: Option<&str>) -> Result<Self, IdError> {
+        let class = match class {
+            Some(prefix) => registered(prefix)?.class(),
+            None => AnyId::from_public_id(public_id)?.class(),
+        };
+        let uuid = class.parse_untrusted(public_id)?;
+        Ok(Self { class, uuid })
+    }
+}
+
+#[pymethods]
+impl PyKindId {
+    /// Parse a public id of the class with the given prefix, or of any
+    /// registered class when there's no prefix
+    #[new]
+    #[pyo3(signature = (public_id, class_=None))]
+    fn new(public_id: &str, class_: Option<&str>) -> PyResult<Self> {
+        Ok(Self::parse(public_id, class_)?)
+    }
+
+    /// Create the id of a class from a db id
+    #[staticmethod]
+    fn from_db_id(db_id: &str, class_: &str) -> PyResult<Self> {
+        let class = registered(class_)?.class();
+        let uuid = class.parse_db_id(db_id)?;
+        Ok(Self { class, uuid })
+    }
+
+    /// The prefix of the class
+    #[getter]
+    fn prefix(&self) -> &'static str {
+        self.class.prefix()
+    }
+
+    /// The name of the identifiable type, eg `Customer`
+    #[getter]
+    fn type_name(&self) -> &'static str {
+        registry::find(self.class.prefix())
+            .expect("KindId classes are registered")
+            .type_name()
+    }
+
+    /// The uuid, as a `uuid.UUID`
+    #[getter]
+    fn uuid<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        py.import("uuid")?.getattr("UUID")?.call1((self.db_id(),))
+    }
+
+    /// The hyphenated uuid
+    #[getter]
+    fn db_id(&self) -> String {
+        self.uuid.hyphenated().to_string()
+    }
+
+    fn __str__(&self) -> String {
+        self.class.public_id(&self.uuid)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KindId({:?})", self.__str__())
+    }
+}
+
+/// A class of the registry
+#[pyclass(name = "KindClass", module = "kind_py", frozen)]
+pub struct PyKindClass(&'static RegisteredClass);
+
+#[pymethods]
+impl PyKindClass {
+    #[getter]
+    fn prefix(&self) -> &'static str {
+        self.0.class().prefix()
+    }
+
+    #[getter]
+    fn type_name(&self) -> &'static str {
+        self.0.type_name()
+    }
+
+    #[getter]
+    fn module_path(&self) -> &'static str {
+        self.0.module_path()
+    }
+
+    #[getter]
+    fn id_field(&self) -> &'static str {
+        self.0.id_field()
+    }
+
+    /// The sensitivity, eg `"pii"`
+    #[getter]
+    fn sensitivity(&self) -> &'static str {
+        self.0.class().sensitivity().as_str()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("KindClass({:?})", self.prefix())
+    }
+}
+
+/// Tell whether the string is a valid public id of the class with the
+/// given prefix
+#[pyfunction]
+#[pyo3(name = "is_valid_id")]
+fn py_is_valid_id(public_id: &str, class_: &str) -> bool {
+    PyKindId::parse(public_id, Some(class_)).is_ok()
+}
+
+/// Return all the registered classes
+#[pyfunction]
+fn classes() -> Vec<PyKindClass> {
+    registry::classes().iter().map(PyKindClass).collect()
+}
+
+/// Return the registered class with the given prefix, if any
+#[pyfunction]
+fn find_class(prefix: &str) -> Option<PyKindClass> {
+    registry::find(prefix).map(PyKindClass)
+}
+
+/// Add the classes, functions and exception of the bindings to a module
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyKindId>()?;
+    m.add_class::<PyKindClass>()?;
+    m.add("IdError", m.py().get_type::<PyIdError>())?;
+    m.add_function(wrap_pyfunction!(py_is_valid_id, m)?)?;
+    m.add_function(wrap_pyfunction!(classes, m)?)?;
+    m.add_function(wrap_pyfunction!(find_class, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::python::*,
+        pyo3::{ffi::c_str, types::PyModule},
+        std::ffi::CStr,
+    };
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "PyCust", sensitivity = "pii")]
+    pub struct Customer {}
+
+    const TEST: &CStr = c_str!(
+        r#"
+import uuid
+from kind_py import KindId, IdError, classes, find_class, is_valid_id
+
+ID = "371c35ec-34d9-4315-ab31-7ea8889a419a"
+id = KindId("pycust_" + ID.upper(), "PyCust")
+assert str(id) == "PyCust_" + ID
+assert repr(id) == 'KindId("PyCust_' + ID + '")'
+assert id.uuid == uuid.UUID(ID) and id.db_id == ID
+assert id.type_name == "Customer"
+assert KindId("PyCust_" + ID) == id
+assert KindId.from_db_id(ID, "pycust") == id
+assert len({id, KindId("PyCust_" + ID)}) == 1
+assert is_valid_id("PyCust_" + ID, "PyCust")
+assert not is_valid_id("PyCust_" + ID[1:], "PyCust")
+assert not is_valid_id("PyCust_" + ID, "Nope")
+try:
+    KindId("Nope_" + ID)
+    assert False
+except ValueError as e:
+    assert isinstance(e, IdError) and e.args[1] == "KIND_UNKNOWN_PREFIX"
+class_ = find_class("pycust")
+assert class_.sensitivity == "pii" and class_.id_field == "id"
+assert class_.module_path == "kind::python::test"
+assert "PyCust" in [c.prefix for c in classes()]
+assert find_class("Nope") is None
+"#
+    );
+
+    #[test]
+    fn test_python_module() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "kind_py").unwrap();
+            register(&module).unwrap();
+            py.import("sys")
+                .unwrap()
+                .getattr("modules")
+                .unwrap()
+                .set_item("kind_py", &module)
+                .unwrap();
+            if let Err(e) = PyModule::from_code(py, TEST, c_str!("test.py"), c_str!("test")) {
+                panic!("{}", e.value(py));
+            }
+        });
+    }
+}
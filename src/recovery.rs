@@ -0,0 +1,182 @@
+use {super::*, std::fmt};
+
+/// The error of [`Id::parse_with_recovery`], holding the parsing error
+/// and, when the input looks like a mistyped id, the id it most likely
+/// is
+///
+/// The candidate is never accepted implicitly: the caller may propose it
+/// to the user ("did you mean ...?") or accept it explicitly.
+pub struct RecoverableIdError<O: Identifiable> {
+    error: IdError,
+    candidate: Option<Id<O>>,
+}
+
+impl<O: Identifiable> fmt::Debug for RecoverableIdError<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecoverableIdError")
+            .field("error", &self.error)
+            .field("candidate", &self.candidate)
+            .finish()
+    }
+}
+
+impl<O: Identifiable> Clone for RecoverableIdError<O> {
+    fn clone(&self) -> Self {
+        Self {
+            error: self.error.clone(),
+            candidate: self.candidate,
+        }
+    }
+}
+
+impl<O: Identifiable> PartialEq for RecoverableIdError<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error && self.candidate == other.candidate
+    }
+}
+
+impl<O: Identifiable> Eq for RecoverableIdError<O> {}
+
+impl<O: Identifiable> RecoverableIdError<O> {
+    /// The error of the parsing of the input as it is
+    pub fn error(&self) -> &IdError {
+        &self.error
+    }
+    /// The id the input most likely is
+    pub fn candidate(&self) -> Option<Id<O>> {
+        self.candidate
+    }
+    /// The canonical public id of the candidate, eg for "did you mean"
+    /// messages
+    pub fn suggestion(&self) -> Option<String> {
+        self.candidate.map(|id| id.canonical_public_id())
+    }
+    pub fn into_error(self) -> IdError {
+        self.error
+    }
+}
+
+impl<O: Identifiable> fmt::Display for RecoverableIdError<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.candidate {
+            Some(id) => write!(f, "{} (did you mean {id}?)", self.error),
+            None => self.error.fmt(f),
+        }
+    }
+}
+
+impl<O: Identifiable> std::error::Error for RecoverableIdError<O> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<O: Identifiable> From<RecoverableIdError<O>> for IdError {
+    fn from(error: RecoverableIdError<O>) -> Self {
+        error.error
+    }
+}
+
+/// The separators sometimes typed instead of the underscore
+const WRONG_SEPARATORS: &[char] = &['-', ':', '.', ' ', '/'];
+
+/// The quotes which may surround a copied id
+const QUOTES: &[char] = &['"', '\'', '`'];
+
+/// Try to fix the usual mistakes in an id of the class: surrounding
+/// spaces or quotes, a wrong separator, or a missing prefix
+fn recover<O: Identifiable>(input: &str) -> Option<Id<O>> {
+    let input = input.trim().trim_matches(|c| QUOTES.contains(&c)).trim();
+    if let Ok(id) = Id::from_public_id(input) {
+        return Some(id);
+    }
+    let prefix = O::class().prefix();
+    let rest = input
+        .get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .and_then(|_| input[prefix.len()..].strip_prefix(WRONG_SEPARATORS));
+    match rest {
+        Some(db_id) => Id::from_db_id(db_id.trim_start()).ok(),
+        None => Id::from_db_id(input).ok(),
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Parse a public id like [`Id::from_public_id`] but, on failure,
+    /// return an error proposing the id the input most likely is, after
+    /// trimming whitespaces and quotes, fixing the separator, or adding
+    /// the missing prefix:
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let error = Id::<Customer>::parse_with_recovery(" 'cust-371C35EC-34d9-4315-ab31-7ea8889a419a'")
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error.suggestion().unwrap(),
+    ///     "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a",
+    /// );
+    /// ```
+    ///
+    /// Ids of another class are never "recovered".
+    pub fn parse_with_recovery(input: &str) -> Result<Self, RecoverableIdError<O>> {
+        Self::from_public_id(input).map_err(|error| RecoverableIdError {
+            error,
+            candidate: recover(input),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_recovery() {
+        let id: Id<Customer> = format!("Cust_{ID}").parse().unwrap();
+        assert_eq!(Id::parse_with_recovery(&format!("cust_{ID}")), Ok(id));
+        for input in [
+            format!(" Cust_{ID}\n"),
+            format!("\"Cust_{ID}\""),
+            format!("`Cust_{ID}`"),
+            format!("Cust-{ID}"),
+            format!("CUST: {ID}"),
+            ID.to_string(),
+            ID.to_uppercase(),
+        ] {
+            let error = Id::<Customer>::parse_with_recovery(&input).unwrap_err();
+            assert_eq!(error.candidate(), Some(id), "{input:?}");
+        }
+        let error = Id::<Customer>::parse_with_recovery(&format!("Cust-{ID}")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("invalid format for id (did you mean Cust_{ID}?)")
+        );
+    }
+
+    #[test]
+    fn test_no_recovery() {
+        for input in [
+            format!("Cont_{ID}"),
+            format!("'Cont_{ID}'"),
+            format!("Cont-{ID}"),
+            "Cust_371c35ec".to_string(),
+            String::new(),
+            "é".repeat(10),
+        ] {
+            let error = Id::<Customer>::parse_with_recovery(&input).unwrap_err();
+            assert_eq!(error.candidate(), None, "{input:?}");
+            assert_eq!(error.suggestion(), None);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use {super::*, std::fmt};
+
+/// The number of hexadecimal digits of the UUID kept in redacted ids
+const KEPT_DIGITS: usize = 4;
+
+/// A display adapter writing an id with only the first digits of its
+/// UUID, eg `Cust_371c…[redacted]`, for logs which must not hold full
+/// customer ids but should still give a hint of the object.
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// assert_eq!(id.redacted().to_string(), "Cust_371c…[redacted]");
+/// ```
+///
+/// With the "redact" feature, the `Debug` impl of `Id` also writes the
+/// redacted form, so that ids in debug logs are redacted everywhere.
+#[derive(Clone, Copy)]
+pub struct RedactedId<O: Identifiable> {
+    id: Id<O>,
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Return a display adapter writing the redacted id
+    pub fn redacted(&self) -> RedactedId<O> {
+        RedactedId { id: *self }
+    }
+}
+
+impl<O: Identifiable> fmt::Display for RedactedId<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = uuid::Uuid::encode_buffer();
+        let digits = self.id.uuid.simple().encode_lower(&mut buffer);
+        write!(
+            f,
+            "{}_{}…[redacted]",
+            self.id.class().prefix(),
+            &digits[..KEPT_DIGITS]
+        )
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for RedactedId<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_redacted() {
+        let id: Id<Customer> = "Cust_371C35EC-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+        assert_eq!(id.redacted().to_string(), "Cust_371c…[redacted]");
+        assert_eq!(format!("{:?}", id.redacted()), "Cust_371c…[redacted]");
+        #[cfg(feature = "redact")]
+        assert_eq!(format!("{id:?}"), "Cust_371c…[redacted]");
+        #[cfg(not(feature = "redact"))]
+        assert!(format!("{id:?}").contains("371c35ec-34d9-4315-ab31-7ea8889a419a"));
+    }
+}
@@ -0,0 +1,70 @@
+use {
+    super::*,
+    redis::{ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRedisArgs, Value},
+};
+
+/// An Id is given to redis commands as its public id
+impl<O: Identifiable> ToRedisArgs for Id<O> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.public_id().as_bytes())
+    }
+}
+
+/// An Id is read from its public id, whose class is checked
+impl<O: Identifiable> FromRedisValue for Id<O> {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let public_id = String::from_redis_value(v)?;
+        Id::from_public_id(&public_id)
+            .map_err(|e| RedisError::from((ErrorKind::TypeError, "Invalid id", e.to_string())))
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Build a cache key namespaced by the class of the id,
+    /// eg `Cust:371c35ec-34d9-4315-ab31-7ea8889a419a:session`
+    pub fn cache_key(&self, name: &str) -> String {
+        format!("{}:{}:{}", self.class().prefix(), self.uuid, name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        redis::{FromRedisValue, ToRedisArgs, Value},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[test]
+    fn test_redis_values() {
+        let id = Id::<Customer>::random_v4();
+        assert_eq!(id.to_redis_args(), vec![id.public_id().into_bytes()]);
+        let value = Value::BulkString(id.public_id().into_bytes());
+        assert_eq!(Id::<Customer>::from_redis_value(&value).unwrap(), id);
+        assert!(Id::<Contract>::from_redis_value(&value).is_err());
+        assert!(Id::<Customer>::from_redis_value(&Value::Nil).is_err());
+        assert_eq!(
+            Option::<Id<Customer>>::from_redis_value(&Value::Nil).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let id: Id<Customer> = Id::from_db_id("371c35ec-34d9-4315-ab31-7ea8889a419a").unwrap();
+        assert_eq!(
+            id.cache_key("session"),
+            "Cust:371c35ec-34d9-4315-ab31-7ea8889a419a:session"
+        );
+    }
+}
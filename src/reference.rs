@@ -0,0 +1,182 @@
+use {
+    super::*,
+    std::{cmp::Ordering, fmt, hash::Hash, str::FromStr},
+};
+
+/// A reference to another entity, ie a foreign key, as opposed to the
+/// own id of an entity
+///
+/// It's a thin wrapper of an id, with the same public form, and the
+/// same serde and sqlx representations, so that changing a field from
+/// `Id<Customer>` to `Ref<Customer>` only changes the Rust code reading
+/// it.
+///
+/// The derive can generate accessors of the referenced ids, named after
+/// the field with an `_id` suffix, for the classes listed in
+/// `references`:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Inv", references(Customer))]
+/// pub struct Invoice {
+///     customer: Ref<Customer>,
+/// }
+///
+/// let customer: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// let invoice = Invoice { customer: customer.into() };
+/// assert_eq!(invoice.customer_id(), customer);
+/// ```
+#[repr(transparent)]
+pub struct Ref<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Ref<O> {
+    pub fn new(id: Id<O>) -> Self {
+        Self(id)
+    }
+    /// Return the id of the referenced entity
+    pub fn id(&self) -> Id<O> {
+        self.0
+    }
+    /// Return the public representation as a string
+    pub fn public_id(&self) -> String {
+        self.0.public_id()
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for Ref<O> {
+    fn from(id: Id<O>) -> Self {
+        Self(id)
+    }
+}
+
+impl<O: Identifiable> From<Ref<O>> for Id<O> {
+    fn from(reference: Ref<O>) -> Self {
+        reference.0
+    }
+}
+
+impl<O: Identifiable> Clone for Ref<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O: Identifiable> Copy for Ref<O> {}
+
+impl<O: Identifiable> PartialEq for Ref<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<O: Identifiable> Eq for Ref<O> {}
+
+impl<O: Identifiable> PartialEq<Id<O>> for Ref<O> {
+    fn eq(&self, other: &Id<O>) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<O: Identifiable> Ord for Ref<O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+impl<O: Identifiable> PartialOrd for Ref<O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<O: Identifiable> Hash for Ref<O> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl<O: Identifiable> fmt::Display for Ref<O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<O: Identifiable> fmt::Debug for Ref<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Ref").field(&self.0).finish()
+    }
+}
+
+impl<O: Identifiable> FromStr for Ref<O> {
+    type Err = IdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// A reference is serialized as the public id of the referenced entity
+#[cfg(feature = "serde")]
+impl<O: Identifiable> serde::Serialize for Ref<O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O: Identifiable> serde::Deserialize<'de> for Ref<O> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Id::deserialize(deserializer).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Inv", references(Customer))]
+    pub struct Invoice {
+        customer: Ref<Customer>,
+        payer_id: Ref<Customer>,
+        previous_customer: Option<Ref<Customer>>,
+    }
+
+    const ID: &str = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_ref() {
+        let customer: Ref<Customer> = ID.parse().unwrap();
+        assert_eq!(customer.to_string(), ID);
+        assert_eq!(customer, customer.id());
+        assert_eq!(Id::from(customer).public_id(), ID);
+        assert_eq!(format!("{customer:?}"), format!("Ref({:?})", customer.id()));
+        let payer = Id::random_v4();
+        let invoice = Invoice {
+            customer,
+            payer_id: Ref::new(payer),
+            previous_customer: None,
+        };
+        assert_eq!(invoice.customer_id(), customer.id());
+        assert_eq!(invoice.payer_id(), payer);
+        assert_eq!(invoice.previous_customer_id(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let customer: Ref<Customer> = ID.parse().unwrap();
+        let json = serde_json::to_string(&customer).unwrap();
+        assert_eq!(json, serde_json::to_string(&customer.id()).unwrap());
+        assert_eq!(
+            serde_json::from_str::<Ref<Customer>>(&json).unwrap(),
+            customer
+        );
+    }
+}
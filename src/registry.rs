@@ -0,0 +1,417 @@
+//! The registry of the classes declared with the `Identifiable` derive,
+//! in all the crates linked in the binary.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let class = registry::find("cust").unwrap();
+//! assert_eq!(class.type_name(), "Customer");
+//! assert!(registry::classes().iter().any(|c| c.class().prefix() == "Cust"));
+//! ```
+//!
+//! Classes are collected at link time, so the registry is complete as
+//! soon as the program starts.
+//!
+//! The relations between classes declared with the `belongs_to` and
+//! `has_many` attributes of the derive are registered too, eg so that
+//! the records of a customer can be found by walking the classes
+//! belonging to it:
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust", has_many(Invoice))]
+//! pub struct Customer {}
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Inv", belongs_to(Customer))]
+//! pub struct Invoice {}
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Note", belongs_to(Customer))]
+//! pub struct Note {}
+//!
+//! let relations = Customer::class().relations();
+//! assert_eq!(relations[0].kind(), RelationKind::HasMany);
+//! assert_eq!(relations[0].class(), Invoice::class());
+//!
+//! let mut prefixes: Vec<_> = registry::belonging_to(Customer::class())
+//!     .map(|c| c.class().prefix())
+//!     .collect();
+//! prefixes.sort();
+//! assert_eq!(prefixes, ["Inv", "Note"]);
+//! ```
+//!
+//! The hash of each prefix is computed at compile time, by the derive,
+//! and a perfect hash table of the registered classes is built on the
+//! first lookup, so that `find` (and so the parsing of an [`AnyId`]) takes
+//! the same time whatever the number of classes. The table can't be
+//! built at compile time, as the classes of other crates aren't known
+//! before linking. In the unlikely case where no seed gives a perfect
+//! hash table, the classes are searched in a sorted vector.
+
+use {
+    super::*,
+    linkme::distributed_slice,
+    std::{collections::HashSet, sync::OnceLock},
+};
+
+/// A class declared with the `Identifiable` derive
+#[derive(Debug, Clone, Copy)]
+pub struct RegisteredClass {
+    class: IdClass,
+    type_name: &'static str,
+    id_field: &'static str,
+    module_path: &'static str,
+    relations: &'static [Relation],
+    prefix_hash: u64,
+}
+
+/// The kind of a relation between two classes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationKind {
+    /// The objects of the class belong to an object of the other class,
+    /// eg an invoice to a customer
+    BelongsTo,
+    /// The objects of the class have objects of the other class
+    HasMany,
+}
+
+/// A relation of a class to another class, declared with the
+/// `belongs_to` or `has_many` attributes of the derive
+#[derive(Clone, Copy)]
+pub struct Relation {
+    kind: RelationKind,
+    // a function, as the class of another type can't be read in a static
+    class: fn() -> IdClass,
+}
+
+impl Relation {
+    #[doc(hidden)]
+    pub const fn new(kind: RelationKind, class: fn() -> IdClass) -> Self {
+        Self { kind, class }
+    }
+
+    pub fn kind(&self) -> RelationKind {
+        self.kind
+    }
+
+    /// The class at the other end of the relation
+    pub fn class(&self) -> IdClass {
+        (self.class)()
+    }
+}
+
+impl std::fmt::Debug for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Relation")
+            .field("kind", &self.kind)
+            .field("class", &self.class().prefix())
+            .finish()
+    }
+}
+
+impl RegisteredClass {
+    #[doc(hidden)]
+    pub const fn new(class: IdClass, type_name: &'static str, id_field: &'static str) -> Self {
+        Self {
+            class,
+            type_name,
+            id_field,
+            module_path: "",
+            relations: &[],
+            prefix_hash: prefix_hash(class.prefix().as_bytes()),
+        }
+    }
+
+    #[doc(hidden)]
+    pub const fn with_module_path(mut self, module_path: &'static str) -> Self {
+        self.module_path = module_path;
+        self
+    }
+
+    #[doc(hidden)]
+    pub const fn with_relations(mut self, relations: &'static [Relation]) -> Self {
+        self.relations = relations;
+        self
+    }
+
+    pub fn class(&self) -> IdClass {
+        self.class
+    }
+
+    /// The name of the identifiable type, eg `Customer`
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The path of the module declaring the type, eg `billing::models`
+    pub fn module_path(&self) -> &'static str {
+        self.module_path
+    }
+
+    /// The key of the id in serialized objects
+    pub fn id_field(&self) -> &'static str {
+        self.id_field
+    }
+
+    /// The relations declared on the type
+    pub fn relations(&self) -> &'static [Relation] {
+        self.relations
+    }
+}
+
+#[doc(hidden)]
+#[distributed_slice]
+pub static KIND_CLASSES: [RegisteredClass];
+
+/// Return all the registered classes
+pub fn classes() -> &'static [RegisteredClass] {
+    &KIND_CLASSES
+}
+
+/// Return the registered class with the given prefix, which is
+/// compared in a case insensitive way, like in public ids
+pub fn find(prefix: &str) -> Option<&'static RegisteredClass> {
+    table().find(prefix)
+}
+
+/// Return the registered classes belonging to the class, ie those
+/// declaring they belong to it, and those it declares it has many
+pub fn belonging_to(class: IdClass) -> impl Iterator<Item = &'static RegisteredClass> {
+    let has_many = find(class.prefix()).map_or(&[][..], |c| c.relations);
+    classes().iter().filter(move |registered| {
+        registered
+            .relations
+            .iter()
+            .any(|r| r.kind == RelationKind::BelongsTo && r.class() == class)
+            || has_many
+                .iter()
+                .any(|r| r.kind == RelationKind::HasMany && r.class() == registered.class)
+    })
+}
+
+/// The FNV-1a hash of the lowercase prefix, const so that it's computed
+/// at compile time for registered classes
+const fn prefix_hash(prefix: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    let mut i = 0;
+    while i < prefix.len() {
+        hash ^= prefix[i].to_ascii_lowercase() as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+        i += 1;
+    }
+    hash
+}
+
+/// Derive the values used by the table from the hash of a prefix and
+/// the seed of the table
+fn mix(hash: u64, seed: u64) -> u64 {
+    let mut z = hash ^ seed.wrapping_mul(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// A perfect hash table of classes, built with the "hash and displace"
+/// algorithm (like the phf crate): the prefixes are spread in buckets,
+/// and each bucket gets a displacement placing its prefixes in free
+/// slots, so that a lookup is a hash, a displacement read and a single
+/// comparison.
+struct PrefixTable {
+    seed: u64,
+    displacements: Vec<(u32, u32)>,
+    slots: Vec<Option<&'static RegisteredClass>>,
+}
+
+/// The average number of classes per bucket
+const BUCKET_SIZE: usize = 4;
+
+/// The number of seeds tried before falling back to a sorted vector
+const MAX_SEEDS: u64 = 1 << 16;
+
+/// The lookup table of the registered classes
+enum ClassTable {
+    Perfect(PrefixTable),
+    /// The classes sorted by lowercase prefix
+    Sorted(Vec<(String, &'static RegisteredClass)>),
+}
+
+impl ClassTable {
+    fn build(classes: &'static [RegisteredClass], max_seeds: u64) -> Self {
+        // the first class of a prefix wins, as in a linear search
+        let mut prefixes = HashSet::with_capacity(classes.len());
+        let unique: Vec<&'static RegisteredClass> = classes
+            .iter()
+            .filter(|c| prefixes.insert(c.class.prefix().to_ascii_lowercase()))
+            .collect();
+        if let Some(table) = (0..max_seeds).find_map(|seed| PrefixTable::try_build(&unique, seed)) {
+            return Self::Perfect(table);
+        }
+        let mut sorted: Vec<_> = unique
+            .into_iter()
+            .map(|c| (c.class.prefix().to_ascii_lowercase(), c))
+            .collect();
+        sorted.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Self::Sorted(sorted)
+    }
+
+    fn find(&self, prefix: &str) -> Option<&'static RegisteredClass> {
+        match self {
+            Self::Perfect(table) => table.find(prefix),
+            Self::Sorted(sorted) => {
+                let prefix = prefix.to_ascii_lowercase();
+                sorted
+                    .binary_search_by(|(p, _)| p.as_str().cmp(&prefix))
+                    .ok()
+                    .map(|i| sorted[i].1)
+            }
+        }
+    }
+}
+
+impl PrefixTable {
+    fn try_build(classes: &[&'static RegisteredClass], seed: u64) -> Option<Self> {
+        let bucket_count = ((classes.len() + BUCKET_SIZE - 1) / BUCKET_SIZE).max(1);
+        let slot_count = classes.len().max(1);
+        let mut buckets = vec![vec![]; bucket_count];
+        for class in classes {
+            let hash = mix(class.prefix_hash, seed);
+            buckets[(hash >> 32) as usize % bucket_count].push((class, hash as u32));
+        }
+        let mut order: Vec<usize> = (0..bucket_count).collect();
+        order.sort_by_key(|&b| std::cmp::Reverse(buckets[b].len()));
+        let mut displacements = vec![(0, 0); bucket_count];
+        let mut slots = vec![None; slot_count];
+        let mut placed = Vec::with_capacity(BUCKET_SIZE);
+        for b in order {
+            let bucket = &buckets[b];
+            if bucket.is_empty() {
+                continue;
+            }
+            let found = (0..slot_count as u32)
+                .flat_map(|d1| (0..slot_count as u32).map(move |d2| (d1, d2)))
+                .find(|&(d1, d2)| {
+                    placed.clear();
+                    bucket.iter().all(|&(_, h)| {
+                        let slot = slot(h, (d1, d2), slot_count);
+                        let free = slots[slot].is_none() && !placed.contains(&slot);
+                        placed.push(slot);
+                        free
+                    })
+                })?;
+            displacements[b] = found;
+            for &(class, h) in bucket {
+                slots[slot(h, found, slot_count)] = Some(*class);
+            }
+        }
+        Some(Self {
+            seed,
+            displacements,
+            slots,
+        })
+    }
+
+    fn find(&self, prefix: &str) -> Option<&'static RegisteredClass> {
+        let hash = mix(prefix_hash(prefix.as_bytes()), self.seed);
+        let displacement = self.displacements[(hash >> 32) as usize % self.displacements.len()];
+        self.slots[slot(hash as u32, displacement, self.slots.len())]
+            .filter(|c| c.class.prefix().eq_ignore_ascii_case(prefix))
+    }
+}
+
+fn slot(hash: u32, (d1, d2): (u32, u32), slot_count: usize) -> usize {
+    let f1 = hash & 0xffff;
+    let f2 = hash >> 16;
+    (d2 as usize + f1 as usize * d1 as usize + f2 as usize) % slot_count
+}
+
+/// The table of the registered classes, built on first use
+static TABLE: OnceLock<ClassTable> = OnceLock::new();
+
+fn table() -> &'static ClassTable {
+    TABLE.get_or_init(|| ClassTable::build(classes(), MAX_SEEDS))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        registry::{ClassTable, MAX_SEEDS},
+        *,
+    };
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "RegCust", has_many(Contract))]
+    pub struct Customer {}
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "RegCont", id_field = "contract")]
+    pub struct Contract {}
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "RegNote", belongs_to(Customer, Contract))]
+    pub struct Note {}
+
+    #[test]
+    fn test_registry() {
+        let class = registry::find("regcust").unwrap();
+        assert_eq!(class.class().prefix(), "RegCust");
+        assert_eq!(class.type_name(), "Customer");
+        assert_eq!(class.id_field(), "id");
+        assert_eq!(class.module_path(), "kind::registry::test");
+        assert_eq!(registry::find("RegCont").unwrap().id_field(), "contract");
+        assert!(registry::find("Reg").is_none());
+        assert!(registry::find("").is_none());
+    }
+
+    #[test]
+    fn test_relations() {
+        let relations = Note::class().relations();
+        assert_eq!(relations.len(), 2);
+        assert_eq!(relations[1].kind(), RelationKind::BelongsTo);
+        assert_eq!(relations[1].class(), Contract::class());
+        assert_eq!(
+            format!("{:?}", relations[0]),
+            r#"Relation { kind: BelongsTo, class: "RegCust" }"#
+        );
+        assert!(Contract::class().relations().is_empty());
+        let mut belonging: Vec<_> = registry::belonging_to(Customer::class())
+            .map(|c| c.class().prefix())
+            .collect();
+        belonging.sort();
+        assert_eq!(belonging, ["RegCont", "RegNote"]);
+        assert_eq!(registry::belonging_to(Note::class()).count(), 0);
+    }
+
+    #[test]
+    fn test_prefix_table() {
+        for count in [0, 1, 2, 7, 120, 1000] {
+            let classes: Vec<RegisteredClass> = (0..count)
+                .map(|i| {
+                    let prefix: &'static str = Box::leak(format!("Class{i}").into_boxed_str());
+                    RegisteredClass::new(IdClass::new(prefix), "T", "id")
+                })
+                .collect();
+            let classes: &'static [RegisteredClass] = classes.leak();
+            // without seeds, the classes are in a sorted vector
+            for (max_seeds, perfect) in [(MAX_SEEDS, true), (0, false)] {
+                let table = ClassTable::build(classes, max_seeds);
+                assert_eq!(matches!(table, ClassTable::Perfect(_)), perfect);
+                for class in classes {
+                    let prefix = class.class().prefix().to_uppercase();
+                    assert_eq!(table.find(&prefix).unwrap().class(), class.class());
+                }
+                assert!(table.find("Class").is_none());
+                assert!(table.find(&format!("Class{count}")).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use {super::*, serde_json::json, std::fmt};
+
+/// The rejection of the id extractors of the web framework integrations
+///
+/// It's rendered as a JSON body with the expected prefix, by default with
+/// the status 404 when the id is of another class, as no object of the
+/// expected class can have it (see [`WrongClassStatus`]), and 422 when
+/// it isn't a valid id.
+#[derive(Debug, Clone)]
+pub enum IdRejection {
+    /// The id parameter isn't valid
+    Invalid {
+        error: IdError,
+        expected: IdClass,
+        /// The status when the id is of another class, as configured for
+        /// the router
+        wrong_class: WrongClassStatus,
+    },
+    /// There's no id parameter with the given name
+    Missing { parameter: &'static str },
+}
+
+impl IdRejection {
+    pub(crate) fn parse<O: Identifiable>(
+        public_id: &str,
+        wrong_class: WrongClassStatus,
+    ) -> Result<Id<O>, Self> {
+        Id::from_public_id(public_id).map_err(|error| Self::Invalid {
+            error,
+            expected: O::class(),
+            wrong_class,
+        })
+    }
+    /// The default status code of the response
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::Invalid {
+                error, wrong_class, ..
+            } => error.http_status_with(*wrong_class),
+            Self::Missing { .. } => 400,
+        }
+    }
+    /// The JSON body of the response
+    pub fn body(&self) -> serde_json::Value {
+        match self {
+            Self::Invalid {
+                error, expected, ..
+            } => json!({
+                "error": error_code(error),
+                "message": self.to_string(),
+                "expected_prefix": expected.prefix(),
+            }),
+            Self::Missing { parameter } => json!({
+                "error": "missing_id",
+                "message": self.to_string(),
+                "parameter": parameter,
+            }),
+        }
+    }
+}
+
+pub(crate) fn error_code(error: &IdError) -> &'static str {
+    error.kind().as_str()
+}
+
+/// The JSON body of the responses to an IdError
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub(crate) fn error_body(error: &IdError) -> serde_json::Value {
+    json!({
+        "error": error_code(error),
+        "message": error.to_string(),
+    })
+}
+
+impl fmt::Display for IdRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid {
+                error, expected, ..
+            } => {
+                write!(f, "{error} (expected {}_ prefix)", expected.prefix())
+            }
+            Self::Missing { parameter } => write!(f, "missing {parameter} parameter"),
+        }
+    }
+}
+
+impl std::error::Error for IdRejection {}
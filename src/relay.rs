@@ -0,0 +1,116 @@
+use {
+    super::*,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    uuid::Uuid,
+};
+
+impl<O: Identifiable> Id<O> {
+    /// Return the Relay global id, the base64 of `Class:uuid`, eg
+    /// `Q3VzdDozNzFjMzVlYy0zNGQ5LTQzMTUtYWIzMS03ZWE4ODg5YTQxOWE=` for
+    /// `Cust:371c35ec-34d9-4315-ab31-7ea8889a419a`
+    ///
+    /// This is the form of the `id` fields of the `Node` interface expected
+    /// by Relay clients.
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let id: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+    /// let global_id = id.to_relay_global_id();
+    /// assert_eq!(global_id, "Q3VzdDozNzFjMzVlYy0zNGQ5LTQzMTUtYWIzMS03ZWE4ODg5YTQxOWE=");
+    /// assert_eq!(Id::<Customer>::from_relay_global_id(&global_id), Ok(id));
+    /// ```
+    pub fn to_relay_global_id(&self) -> String {
+        let mut buffer = Uuid::encode_buffer();
+        STANDARD.encode(format!(
+            "{}:{}",
+            O::class().prefix(),
+            self.uuid.hyphenated().encode_lower(&mut buffer),
+        ))
+    }
+
+    /// Parse a Relay global id, checking the class, whose case is ignored
+    /// as in public ids
+    ///
+    /// As the position of an error in the decoded text wouldn't help the
+    /// client, the returned errors have no position.
+    pub fn from_relay_global_id(global_id: &str) -> Result<Self, IdError> {
+        let class = O::class();
+        let error = |kind| {
+            IdError::new(kind)
+                .with_input(global_id)
+                .with_expected(class)
+        };
+        let decoded = STANDARD
+            .decode(global_id)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| error(IdErrorKind::InvalidFormat))?;
+        let (prefix, uuid) = decoded
+            .split_once(':')
+            .ok_or_else(|| error(IdErrorKind::InvalidFormat))?;
+        if !prefix.eq_ignore_ascii_case(class.prefix()) {
+            return Err(error(IdErrorKind::WrongClass));
+        }
+        let uuid =
+            Uuid::try_parse(uuid).map_err(|e| error(IdErrorKind::InvalidFormat).with_source(e))?;
+        Ok(Self::unchecked(uuid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        base64::{engine::general_purpose::STANDARD, Engine},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_relay_global_id() {
+        let id = Id::<Customer>::from_db_id(ID).unwrap();
+        let global_id = id.to_relay_global_id();
+        assert_eq!(
+            STANDARD.decode(&global_id).unwrap(),
+            format!("Cust:{ID}").as_bytes()
+        );
+        assert_eq!(Id::from_relay_global_id(&global_id), Ok(id));
+        let lowercase = STANDARD.encode(format!("cust:{ID}"));
+        assert_eq!(Id::from_relay_global_id(&lowercase), Ok(id));
+
+        for (global_id, kind) in [
+            (global_id.clone(), IdErrorKind::WrongClass),
+            (
+                STANDARD.encode(format!("Cust_{ID}")),
+                IdErrorKind::InvalidFormat,
+            ),
+            (
+                STANDARD.encode("Cont:not-a-uuid"),
+                IdErrorKind::InvalidFormat,
+            ),
+            (format!("Cont:{ID}"), IdErrorKind::InvalidFormat),
+            (
+                STANDARD.encode([b'C', b':', 0xff]),
+                IdErrorKind::InvalidFormat,
+            ),
+        ] {
+            let error = Id::<Contract>::from_relay_global_id(&global_id).unwrap_err();
+            assert_eq!(error.kind(), kind, "{global_id}");
+            assert_eq!(error.input(), Some(global_id.as_str()));
+            assert_eq!(error.expected(), Some(Contract::class()));
+        }
+    }
+}
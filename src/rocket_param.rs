@@ -0,0 +1,74 @@
+use {
+    super::*,
+    rocket::{
+        form::{self, FromFormField, ValueField},
+        request::FromParam,
+    },
+};
+
+/// An Id can be a dynamic segment of a route, eg
+/// `#[get("/customers/<id>")] fn get(id: Id<Customer>)`.
+///
+/// The class of the id is checked: a segment of another class
+/// doesn't match the route.
+impl<'a, O: Identifiable> FromParam<'a> for Id<O> {
+    type Error = IdError;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        Id::from_public_id(param)
+    }
+}
+
+/// An Id can be a query parameter or a form field
+impl<'v, O: Identifiable + Send> FromFormField<'v> for Id<O> {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        Id::from_public_id(field.value).map_err(|e| form::Error::validation(e.to_string()).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        rocket::{form::Form, request::FromParam},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[derive(Debug, rocket::FromForm)]
+    struct Search {
+        customer: Id<Customer>,
+        contract: Option<Id<Contract>>,
+    }
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_from_param() {
+        let id = Id::<Customer>::from_param(&format!("Cust_{ID}")).unwrap();
+        assert_eq!(id.db_id(), ID);
+        assert_eq!(
+            Id::<Customer>::from_param(&format!("Cont_{ID}"))
+                .unwrap_err()
+                .kind(),
+            IdErrorKind::WrongClass
+        );
+    }
+
+    #[test]
+    fn test_from_form_field() {
+        let search = Form::<Search>::parse(&format!("customer=Cust_{ID}")).unwrap();
+        assert_eq!(search.customer.db_id(), ID);
+        assert_eq!(search.contract, None);
+        let query = format!("customer=Cust_{ID}&contract=Cont_{ID}");
+        let search = Form::<Search>::parse(&query).unwrap();
+        assert_eq!(search.contract.unwrap().db_id(), ID);
+        assert!(Form::<Search>::parse(&format!("customer=Cont_{ID}")).is_err());
+    }
+}
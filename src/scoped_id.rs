@@ -0,0 +1,252 @@
+use {
+    super::*,
+    std::{cmp::Ordering, fmt, hash::Hash, str::FromStr},
+    uuid::Uuid,
+};
+
+/// An id of an object belonging to a tenant, carrying the id of the
+/// tenant, so that an object of a tenant can't be used where an object
+/// of another tenant is expected
+///
+/// The public form is the prefix of the object class, the simple form
+/// (without hyphens) of the tenant uuid, and the object uuid:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Org")]
+/// pub struct Organization {}
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let org: Id<Organization> = "Org_4b1e2d6e-0b1c-4c2e-9a7e-6f3b9f1d2c3a".parse().unwrap();
+/// let customer: Id<Customer> = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a".parse().unwrap();
+/// let scoped = customer.scoped(org);
+/// assert_eq!(
+///     scoped.to_string(),
+///     "Cust_4b1e2d6e0b1c4c2e9a7e6f3b9f1d2c3a_371c35ec-34d9-4315-ab31-7ea8889a419a",
+/// );
+/// assert_eq!(scoped.to_string().parse(), Ok(scoped));
+///
+/// // the id is only given to requests of its tenant
+/// assert_eq!(scoped.in_tenant(org), Some(customer));
+/// assert_eq!(scoped.in_tenant(Id::random_v4()), None);
+/// ```
+///
+/// With the sqlx feature, it's bound as a composite value of type
+/// `kind_scoped_id`, which must be declared with
+/// `CREATE TYPE kind_scoped_id AS (tenant uuid, id uuid)`, and it can
+/// also be read from an anonymous record, eg `SELECT (tenant_id, id)`.
+pub struct ScopedId<T: Identifiable, O: Identifiable> {
+    tenant: Id<T>,
+    id: Id<O>,
+}
+
+impl<T: Identifiable, O: Identifiable> ScopedId<T, O> {
+    pub fn new(tenant: Id<T>, id: Id<O>) -> Self {
+        Self { tenant, id }
+    }
+    /// Return the id of the tenant
+    pub fn tenant(&self) -> Id<T> {
+        self.tenant
+    }
+    /// Return the id of the object, without its tenant
+    pub fn unscoped(&self) -> Id<O> {
+        self.id
+    }
+    /// Return the id of the object if it belongs to the tenant
+    pub fn in_tenant(&self, tenant: Id<T>) -> Option<Id<O>> {
+        (self.tenant == tenant).then_some(self.id)
+    }
+    pub fn into_parts(self) -> (Id<T>, Id<O>) {
+        (self.tenant, self.id)
+    }
+    /// Return the public representation as a string
+    pub fn public_id(&self) -> String {
+        self.to_string()
+    }
+    /// Parse a scoped id from its public representation, checking
+    /// the class of the object
+    pub fn from_public_id(public_id: &str) -> Result<Self, IdError> {
+        let class = O::class();
+        let rest = class.strip_prefix(public_id)?;
+        let position = public_id.len() - rest.len();
+        let error = |position| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(public_id)
+                .with_expected(class)
+                .with_position(position)
+        };
+        let Some((tenant, id)) = rest.split_once('_') else {
+            return Err(error(public_id.len()));
+        };
+        let tenant =
+            Uuid::try_parse_ascii(tenant.as_bytes()).map_err(|e| error(position).with_source(e))?;
+        let id = Uuid::try_parse_ascii(id.as_bytes())
+            .map_err(|e| error(position + tenant_len(rest)).with_source(e))?;
+        Ok(Self::new(Id::unchecked(tenant), Id::unchecked(id)))
+    }
+}
+
+/// The length of the tenant part and its underscore
+fn tenant_len(rest: &str) -> usize {
+    rest.find('_').map_or(rest.len(), |i| i + 1)
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Attach the id of the tenant of the object
+    pub fn scoped<T: Identifiable>(self, tenant: Id<T>) -> ScopedId<T, O> {
+        ScopedId::new(tenant, self)
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> Clone for ScopedId<T, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: Identifiable, O: Identifiable> Copy for ScopedId<T, O> {}
+
+impl<T: Identifiable, O: Identifiable> PartialEq for ScopedId<T, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tenant == other.tenant && self.id == other.id
+    }
+}
+impl<T: Identifiable, O: Identifiable> Eq for ScopedId<T, O> {}
+
+/// Scoped ids are sorted by tenant, then by id
+impl<T: Identifiable, O: Identifiable> Ord for ScopedId<T, O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.tenant, self.id).cmp(&(other.tenant, other.id))
+    }
+}
+impl<T: Identifiable, O: Identifiable> PartialOrd for ScopedId<T, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> Hash for ScopedId<T, O> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.tenant.hash(state);
+        self.id.hash(state);
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> fmt::Display for ScopedId<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Uuid::encode_buffer();
+        f.write_str(O::class().prefix())?;
+        f.write_str("_")?;
+        f.write_str(self.tenant.uuid.simple().encode_lower(&mut buffer))?;
+        f.write_str("_")?;
+        f.write_str(self.id.uuid.hyphenated().encode_lower(&mut buffer))
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> fmt::Debug for ScopedId<T, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScopedId")
+            .field("tenant", &self.tenant)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl<T: Identifiable, O: Identifiable> FromStr for ScopedId<T, O> {
+    type Err = IdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_public_id(s)
+    }
+}
+
+/// A scoped id is serialized as its public representation
+#[cfg(feature = "serde")]
+impl<T: Identifiable, O: Identifiable> serde::Serialize for ScopedId<T, O> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Identifiable, O: Identifiable> serde::Deserialize<'de> for ScopedId<T, O> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ScopedIdVisitor<T: Identifiable, O: Identifiable>(std::marker::PhantomData<(T, O)>);
+        impl<T: Identifiable, O: Identifiable> serde::de::Visitor<'_> for ScopedIdVisitor<T, O> {
+            type Value = ScopedId<T, O>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a scoped public id")
+            }
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                ScopedId::from_public_id(v).map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_str(ScopedIdVisitor(std::marker::PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Org")]
+    pub struct Organization {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const ORG: &str = "4b1e2d6e0b1c4c2e9a7e6f3b9f1d2c3a";
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_scoped_id_parsing() {
+        let scoped: ScopedId<Organization, Customer> = format!("CUST_{ORG}_{ID}").parse().unwrap();
+        assert_eq!(scoped.tenant().uuid().simple().to_string(), ORG);
+        assert_eq!(scoped.unscoped().db_id(), ID);
+        assert_eq!(scoped.public_id(), format!("Cust_{ORG}_{ID}"));
+
+        let error =
+            ScopedId::<Organization, Contract>::from_public_id(&scoped.public_id()).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::WrongClass);
+        for (public_id, position) in [
+            (format!("Cust_{ID}"), 41),
+            (format!("Cust_{ORG}x_{ID}"), 5),
+            (format!("Cust_{ORG}_{ID}x"), 38),
+        ] {
+            let error = ScopedId::<Organization, Customer>::from_public_id(&public_id).unwrap_err();
+            assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+            assert_eq!(error.position(), Some(position));
+        }
+    }
+
+    #[test]
+    fn test_scoped_id_tenant() {
+        let org = Id::<Organization>::random_v4();
+        let customer = Id::<Customer>::random_v4();
+        let scoped = customer.scoped(org);
+        assert_eq!(scoped.in_tenant(org), Some(customer));
+        assert_eq!(scoped.in_tenant(Id::random_v4()), None);
+        assert_eq!(scoped.into_parts(), (org, customer));
+        assert_ne!(scoped, customer.scoped(Id::random_v4()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_scoped_id_serde() {
+        let scoped = Id::<Customer>::random_v4().scoped(Id::<Organization>::random_v4());
+        let json = serde_json::to_string(&scoped).unwrap();
+        assert_eq!(json, format!("\"{scoped}\""));
+        let parsed: ScopedId<Organization, Customer> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, scoped);
+        assert!(serde_json::from_str::<ScopedId<Organization, Contract>>(&json).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use {
+    super::*,
+    sea_orm::{
+        sea_query::{ArrayType, ColumnType, Nullable, Value, ValueType, ValueTypeErr},
+        ColIdx, DbErr, QueryResult, TryFromU64, TryGetError, TryGetable,
+    },
+    uuid::Uuid,
+};
+
+/// An Id is given to query builders as its uuid, so that typed ids
+/// can be used in conditions without calling `id.uuid()`:
+///
+/// ```ignore
+/// customer::Entity::find().filter(customer::Column::Id.eq(id))
+/// ```
+impl<O: Identifiable> From<Id<O>> for Value {
+    fn from(id: Id<O>) -> Self {
+        id.uuid().into()
+    }
+}
+
+impl<O: Identifiable> Nullable for Id<O> {
+    fn null() -> Value {
+        Value::Uuid(None)
+    }
+}
+
+/// Makes it possible to use an Id as the type of a field of a model
+impl<O: Identifiable> ValueType for Id<O> {
+    fn try_from(v: Value) -> Result<Self, ValueTypeErr> {
+        <Uuid as ValueType>::try_from(v).map(Id::unchecked)
+    }
+    fn type_name() -> String {
+        format!("Id<{}>", O::class().prefix())
+    }
+    fn array_type() -> ArrayType {
+        ArrayType::Uuid
+    }
+    fn column_type() -> ColumnType {
+        ColumnType::Uuid
+    }
+}
+
+impl<O: Identifiable> TryGetable for Id<O> {
+    fn try_get_by<I: ColIdx>(res: &QueryResult, index: I) -> Result<Self, TryGetError> {
+        Uuid::try_get_by(res, index).map(Id::unchecked)
+    }
+}
+
+/// An Id can be a primary key, which is never auto-incremented
+impl<O: Identifiable> TryFromU64 for Id<O> {
+    fn try_from_u64(_: u64) -> Result<Self, DbErr> {
+        Err(DbErr::ConvertFromU64("Id"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        sea_orm::{
+            sea_query::{Nullable, Value, ValueType},
+            ProxyRow, QueryResult,
+        },
+        std::collections::BTreeMap,
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_value() {
+        let id = Id::<Customer>::random_v4();
+        assert_eq!(Value::from(id), Value::from(id.uuid()));
+        assert_eq!(Value::from(Some(id)), Value::from(Some(id.uuid())));
+        assert_eq!(Value::from(None::<Id<Customer>>), Id::<Customer>::null());
+        assert_eq!(
+            <Id<Customer> as ValueType>::try_from(Value::from(id.uuid())).unwrap(),
+            id
+        );
+        assert!(<Id<Customer> as ValueType>::try_from(Value::from("Cust")).is_err());
+    }
+
+    #[test]
+    fn test_try_get() {
+        let id = Id::<Customer>::random_v4();
+        let row: QueryResult = ProxyRow::new(BTreeMap::from([
+            ("id".to_string(), Value::from(id)),
+            ("referrer".to_string(), Id::<Customer>::null()),
+        ]))
+        .into();
+        assert_eq!(row.try_get::<Id<Customer>>("", "id").unwrap(), id);
+        assert_eq!(
+            row.try_get::<Option<Id<Customer>>>("", "id").unwrap(),
+            Some(id)
+        );
+        assert_eq!(
+            row.try_get::<Option<Id<Customer>>>("", "referrer").unwrap(),
+            None
+        );
+    }
+}
@@ -1,32 +1,191 @@
 use {
     super::*,
-    ::serde::{de, Deserialize, Deserializer, Serialize, Serializer},
+    serde::{de, ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer},
+    serde_json::{value::RawValue, Value},
+    std::{fmt, marker::PhantomData},
 };
 
+/// An Id is serialized as its public id, a string, which makes it
+/// usable as map key, including in JSON objects
 impl<O: Identifiable> Serialize for Id<O> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.public_id())
+        // public ids of usual prefixes are written without allocation
+        let mut buffer = [0; 64];
+        if self.class().public_id_len() <= buffer.len() {
+            serializer.serialize_str(self.public_id_buf(&mut buffer))
+        } else {
+            serializer.serialize_str(&self.public_id())
+        }
     }
 }
 
+/// Serialize ids as a sequence of public ids, like a `Vec<Id<O>>`, but
+/// writing the prefix once and formatting all the ids in the same buffer
+///
+/// It's the serialization of the `kind::with::vec_public_id` adapter,
+/// for fields holding pages of thousands of ids:
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// #[derive(serde::Serialize)]
+/// pub struct Page {
+///     #[serde(with = "kind::with::vec_public_id")]
+///     customers: Vec<Id<Customer>>,
+/// }
+///
+/// let customers = vec![Id::random_v4(), Id::random_v4()];
+/// assert_eq!(
+///     serde_json::to_string(&Page { customers: customers.clone() }).unwrap(),
+///     format!(r#"{{"customers":{}}}"#, serde_json::to_string(&customers).unwrap()),
+/// );
+/// ```
+pub fn serialize_ids<O, S>(ids: &[Id<O>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    O: Identifiable,
+    S: Serializer,
+{
+    let class = O::class();
+    let uuid_start = class.public_id_len() - DB_ID_LEN;
+    let mut buffer = vec![0; class.public_id_len()];
+    class.public_id_buf(&uuid::Uuid::nil(), &mut buffer);
+    let mut seq = serializer.serialize_seq(Some(ids.len()))?;
+    for id in ids {
+        id.uuid.hyphenated().encode_lower(&mut buffer[uuid_start..]);
+        let public_id = std::str::from_utf8(&buffer).expect("public ids are UTF-8");
+        seq.serialize_element(public_id)?;
+    }
+    seq.end()
+}
+
+/// An Id is deserialized from its public id, checking the class,
+/// whether it's a value or a map key
 impl<'de, O: Identifiable> Deserialize<'de> for Id<O> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        Self::from_public_id(&s).map_err(de::Error::custom)
+        deserializer.deserialize_str(IdVisitor::public())
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Parse an Id from a JSON value, which must be a string
+    /// holding the public id
+    pub fn from_json_value(value: &Value) -> Result<Self, IdError> {
+        match value {
+            Value::String(s) => Self::from_public_id(s),
+            _ => Err(IdError::new(IdErrorKind::InvalidFormat).with_expected(O::class())),
+        }
+    }
+    /// Parse an Id from a raw JSON value, without building
+    /// a `serde_json::Value`
+    pub fn from_raw_json_value(value: &RawValue) -> Result<Self, IdError> {
+        struct ParseVisitor<O: Identifiable>(PhantomData<O>);
+        impl<'de, O: Identifiable> de::Visitor<'de> for ParseVisitor<O> {
+            type Value = Result<Id<O>, IdError>;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a public id")
+            }
+            // the str is borrowed from the raw value, unless it contains
+            // escape sequences
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Id::from_public_id(v))
+            }
+        }
+        serde_json::Deserializer::from_str(value.get())
+            .deserialize_str(ParseVisitor(PhantomData))
+            .unwrap_or_else(|_| {
+                Err(IdError::new(IdErrorKind::InvalidFormat).with_expected(O::class()))
+            })
+    }
+    /// Return the public id as a JSON value
+    pub fn to_json_value(&self) -> Value {
+        Value::String(self.public_id())
+    }
+}
+
+/// Visitor parsing an id directly from the deserialized str, be
+/// it borrowed from the input or transient, without allocating
+pub(crate) struct IdVisitor<O: Identifiable> {
+    parse: fn(&str) -> Result<Id<O>, IdError>,
+    expecting: &'static str,
+    phantom: PhantomData<O>,
+}
+
+impl<O: Identifiable> IdVisitor<O> {
+    pub(crate) fn new(parse: fn(&str) -> Result<Id<O>, IdError>, expecting: &'static str) -> Self {
+        Self {
+            parse,
+            expecting,
+            phantom: PhantomData,
+        }
+    }
+    pub(crate) fn public() -> Self {
+        Self::new(Id::from_public_id, "a public id")
+    }
+    pub(crate) fn db() -> Self {
+        Self::new(Id::from_db_id, "a hyphenated uuid")
     }
 }
 
+impl<'de, O: Identifiable> de::Visitor<'de> for IdVisitor<O> {
+    type Value = Id<O>;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        (self.parse)(v).map_err(E::custom)
+    }
+    // eg from byte oriented formats, or content buffered by serde
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        match std::str::from_utf8(v) {
+            Ok(v) => self.visit_str(v),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+/// An IdError is serialized as an object with its stable code, its
+/// message, the prefix of the expected class and the (truncated) input,
+/// the last two being null when unknown:
+///
+/// ```json
+/// {
+///   "code": "KIND_WRONG_CLASS",
+///   "message": "wrong object class",
+///   "expected": "Cust",
+///   "found": "Cont_371c35ec-34d9-4315-ab31-7ea8889a419a"
+/// }
+/// ```
+impl Serialize for IdError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut error = serializer.serialize_struct("IdError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("expected", &self.expected().map(IdClass::prefix))?;
+        error.serialize_field("found", &self.input())?;
+        error.end()
+    }
+}
+
+/// Deserialize an Id from its database representation
+#[deprecated(note = "use `#[serde(with = \"kind::with::db_id\")]` instead")]
 pub fn deserialize_raw<'de, O: Identifiable, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<Id<O>, D::Error> {
-    let s = String::deserialize(deserializer)?;
-    Id::from_db_id(&s).map_err(de::Error::custom)
+    crate::with::db_id::deserialize(deserializer)
 }
 
 #[cfg(test)]
@@ -34,7 +193,8 @@ mod test {
     use crate::{Id, IdClass, Ided, Identifiable};
     use rstest::rstest;
     use serde::{Deserialize, Serialize};
-    use serde_json::json;
+    use serde_json::{json, value::RawValue};
+    use std::collections::{BTreeMap, HashMap};
 
     const ID: &str = "86261271-0fc7-46d3-81c6-0b0158628331";
 
@@ -69,4 +229,322 @@ mod test {
 
         assert_eq!(val, deserialized)
     }
+
+    #[rstest]
+    pub fn test_deserialize_transient_str() {
+        // escaped strings can't be borrowed from the input
+        let json = format!(r#"["Test_{ID}", "Test\u005f{ID}"]"#);
+        let ids: Vec<Id<TestStruct>> = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(ids[0].db_id(), ID);
+    }
+
+    #[rstest]
+    pub fn test_deserialize_bytes() {
+        use serde::de::value::{BytesDeserializer, Error};
+        let public_id = format!("Test_{ID}");
+        let deserializer = BytesDeserializer::<Error>::new(public_id.as_bytes());
+        let id = Id::<TestStruct>::deserialize(deserializer).unwrap();
+        assert_eq!(id.db_id(), ID);
+        let deserializer = BytesDeserializer::<Error>::new(b"Test_\xff");
+        let err = Id::<TestStruct>::deserialize(deserializer).unwrap_err();
+        assert!(err.to_string().starts_with("invalid value: byte array"));
+    }
+
+    #[rstest]
+    pub fn test_options() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct WithOptions {
+            some: Option<Id<TestStruct>>,
+            none: Option<Id<TestStruct>>,
+            #[serde(default)]
+            missing: Option<Id<TestStruct>>,
+        }
+        let id = Id::<TestStruct>::from_db_id(ID).unwrap();
+        let val = WithOptions {
+            some: Some(id),
+            none: None,
+            missing: None,
+        };
+        let serialized = serde_json::to_value(&val).unwrap();
+        assert_eq!(
+            serialized,
+            json!({ "some": format!("Test_{ID}"), "none": null, "missing": null })
+        );
+        let deserialized: WithOptions =
+            serde_json::from_value(json!({ "some": format!("Test_{ID}"), "none": null })).unwrap();
+        assert_eq!(val, deserialized);
+
+        // an empty string isn't a missing id
+        assert!(
+            serde_json::from_value::<WithOptions>(json!({ "some": "", "none": null })).is_err()
+        );
+    }
+
+    #[rstest]
+    pub fn test_vec() {
+        let ids: Vec<Id<TestStruct>> = (0..3).map(|_| Id::random_v4()).collect();
+        let serialized = serde_json::to_string(&ids).unwrap();
+        let deserialized: Vec<Id<TestStruct>> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(ids, deserialized);
+        assert!(
+            serde_json::from_str::<Vec<Id<TestStruct>>>(&format!(r#"["Test_{ID}", "{ID}"]"#))
+                .is_err()
+        );
+    }
+
+    #[rstest]
+    pub fn test_map_keys() {
+        let id = Id::<TestStruct>::from_db_id(ID).unwrap();
+        let json = json!({ format!("Test_{ID}"): 42 });
+
+        let hash_map: HashMap<Id<TestStruct>, i32> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(hash_map[&id], 42);
+        assert_eq!(serde_json::to_value(&hash_map).unwrap(), json);
+
+        let btree_map: BTreeMap<Id<TestStruct>, i32> =
+            serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(btree_map[&id], 42);
+        assert_eq!(serde_json::to_value(&btree_map).unwrap(), json);
+
+        // keys are parsed as public ids, so are case insensitive and checked
+        let upper = format!("TEST_{}", ID.to_uppercase());
+        let map: HashMap<Id<TestStruct>, i32> =
+            serde_json::from_str(&format!(r#"{{"{upper}": 1}}"#)).unwrap();
+        assert_eq!(map[&id], 1);
+        assert!(
+            serde_json::from_str::<HashMap<Id<TestStruct>, i32>>(&format!(r#"{{"{ID}": 1}}"#))
+                .is_err()
+        );
+    }
+
+    #[rstest]
+    pub fn test_json_values() {
+        let id = Id::<TestStruct>::from_db_id(ID).unwrap();
+        let value = id.to_json_value();
+        assert_eq!(value, json!(format!("Test_{ID}")));
+        assert_eq!(Id::from_json_value(&value), Ok(id));
+        assert_eq!(
+            Id::<TestStruct>::from_json_value(&json!(42))
+                .unwrap_err()
+                .kind(),
+            crate::IdErrorKind::InvalidFormat
+        );
+
+        #[derive(Deserialize)]
+        struct Envelope<'a> {
+            #[serde(borrow)]
+            customer: &'a RawValue,
+        }
+        let json = format!(r#"{{"customer": "Test_{ID}"}}"#);
+        let envelope: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(Id::from_raw_json_value(envelope.customer), Ok(id));
+        let json = format!(r#"{{"customer": "Other_{ID}"}}"#);
+        let envelope: Envelope = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            Id::<TestStruct>::from_raw_json_value(envelope.customer)
+                .unwrap_err()
+                .kind(),
+            crate::IdErrorKind::WrongClass
+        );
+        let envelope: Envelope = serde_json::from_str(r#"{"customer": [1]}"#).unwrap();
+        assert_eq!(
+            Id::<TestStruct>::from_raw_json_value(envelope.customer)
+                .unwrap_err()
+                .kind(),
+            crate::IdErrorKind::InvalidFormat
+        );
+    }
+
+    #[rstest]
+    pub fn test_serialize_error() {
+        let error = Id::<TestStruct>::from_public_id(&format!("Other_{ID}")).unwrap_err();
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "KIND_WRONG_CLASS",
+                "message": "wrong object class",
+                "expected": "Test",
+                "found": format!("Other_{ID}"),
+            })
+        );
+        let error = Id::<TestStruct>::from_public_id("Test_123").unwrap_err();
+        assert_eq!(error.code(), "KIND_BAD_UUID");
+        let error = crate::IdError::new(crate::IdErrorKind::InvalidFormat);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({
+                "code": "KIND_INVALID_FORMAT",
+                "message": "invalid format for id",
+                "expected": null,
+                "found": null,
+            })
+        );
+    }
+}
+
+/// Round trips through binary formats, which don't all support
+/// the same data model as JSON
+#[cfg(test)]
+mod binary_formats {
+    use crate::*;
+    use rstest::rstest;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+    const ID: &str = "86261271-0fc7-46d3-81c6-0b0158628331";
+
+    #[derive(Debug, Clone, PartialEq, Identifiable, Serialize, Deserialize)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        referrer: Option<Id<Customer>>,
+    }
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    id_enum! {Party: Customer, Contract}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        #[serde(with = "crate::with::ided_tuple")]
+        customer: Ided<Customer>,
+        #[serde(with = "crate::with::uuid_bytes")]
+        contract: Id<Contract>,
+        party: Party,
+    }
+
+    fn rmp_named<V: Serialize + DeserializeOwned>(value: &V) -> V {
+        rmp_serde::from_slice(&rmp_serde::to_vec_named(value).unwrap()).unwrap()
+    }
+
+    fn rmp_compact<V: Serialize + DeserializeOwned>(value: &V) -> V {
+        rmp_serde::from_slice(&rmp_serde::to_vec(value).unwrap()).unwrap()
+    }
+
+    fn cbor<V: Serialize + DeserializeOwned>(value: &V) -> V {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes).unwrap();
+        ciborium::from_reader(bytes.as_slice()).unwrap()
+    }
+
+    fn customer(referrer: Option<Id<Customer>>) -> Ided<Customer> {
+        Ided::new(
+            Id::from_db_id(ID).unwrap(),
+            Customer {
+                name: "John".to_string(),
+                referrer,
+            },
+        )
+    }
+
+    #[rstest]
+    #[case::rmp_named(rmp_named)]
+    #[case::rmp_compact(rmp_compact)]
+    #[case::cbor(cbor)]
+    pub fn test_round_trips(#[case] round_trip: fn(&Message) -> Message) {
+        let contract = Id::<Contract>::random_v4();
+        // the referrer is always set, as skipped fields aren't supported
+        // when structs are serialized as arrays (as rmp_serde::to_vec does)
+        let messages = [
+            Message {
+                customer: customer(Some(Id::random_v4())),
+                contract,
+                party: Party::Contract(contract),
+            },
+            Message {
+                customer: customer(Some(Id::random_v4())),
+                contract,
+                party: Party::Customer(Id::from_db_id(ID).unwrap()),
+            },
+        ];
+        for message in messages {
+            let back = round_trip(&message);
+            assert_eq!(message, back);
+            // Ided equality is based on the id only
+            assert_eq!(message.customer.entity(), back.customer.entity());
+        }
+    }
+
+    #[rstest]
+    #[case::rmp_named(rmp_named)]
+    #[case::rmp_compact(rmp_compact)]
+    #[case::cbor(cbor)]
+    pub fn test_flattened_ided(#[case] round_trip: fn(&Ided<Customer>) -> Ided<Customer>) {
+        for ided in [customer(None), customer(Some(Id::random_v4()))] {
+            let back = round_trip(&ided);
+            assert_eq!(ided.id(), back.id());
+            assert_eq!(ided.entity(), back.entity());
+        }
+    }
+}
+
+/// CSV, which doesn't support maps, and gives the headers as bytes
+#[cfg(test)]
+mod csv_format {
+    use crate::*;
+    use serde::{Deserialize, Serialize};
+
+    const ID: &str = "86261271-0fc7-46d3-81c6-0b0158628331";
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    id_enum! {Party: Customer, Contract}
+
+    #[derive(Debug, PartialEq, Identifiable, Serialize, Deserialize)]
+    #[kind(class = "Inv")]
+    struct Invoice {
+        customer: Id<Customer>,
+        party: Party,
+        amount: u32,
+    }
+
+    fn write<V: Serialize>(rows: impl IntoIterator<Item = V>) -> Result<String, csv::Error> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        for row in rows {
+            writer.serialize(row)?;
+        }
+        Ok(String::from_utf8(writer.into_inner().unwrap()).unwrap())
+    }
+
+    fn read<V: for<'de> Deserialize<'de>>(csv: &str) -> Result<Vec<V>, csv::Error> {
+        csv::Reader::from_reader(csv.as_bytes())
+            .deserialize()
+            .collect()
+    }
+
+    #[test]
+    fn test_csv_ided() {
+        let csv = format!("id,customer,party,amount\nInv_{ID},Cust_{ID},Cont_{ID},42\n");
+        let invoices: Vec<Ided<Invoice>> = read(&csv).unwrap();
+        assert_eq!(invoices[0].id().db_id(), ID);
+        assert_eq!(invoices[0].customer.db_id(), ID);
+        assert!(matches!(invoices[0].party, Party::Contract(_)));
+        assert_eq!(invoices[0].amount, 42);
+
+        // maps can't be written, so Ideds are written with IdedFlat
+        assert!(write(&invoices).is_err());
+        assert_eq!(write(invoices.into_iter().map(IdedFlat)).unwrap(), csv);
+        let flat: Vec<IdedFlat<Invoice>> = read(&csv).unwrap();
+        assert_eq!(flat[0].amount, 42);
+
+        let csv = format!("id,customer,party,amount\nInv_{ID},Inv_{ID},Cont_{ID},42\n");
+        assert!(read::<Ided<Invoice>>(&csv).is_err());
+    }
+
+    #[test]
+    fn test_csv_ided_flat_of_map() {
+        let entity = std::collections::BTreeMap::from([("name", "John")]);
+        let ided = Ided::<Customer, _>::new(Id::from_db_id(ID).unwrap(), entity);
+        let error = write([IdedFlat(ided)]).unwrap_err();
+        assert!(error.to_string().contains("must be a struct"));
+    }
 }
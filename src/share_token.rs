@@ -0,0 +1,224 @@
+use {
+    super::*,
+    base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine},
+    hmac::{Hmac, Mac},
+    sha2::Sha256,
+    std::{
+        fmt,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// The length of the HMAC kept in tokens
+const MAC_LEN: usize = 16;
+
+/// The length of the payload: the uuid and the expiry timestamp
+const PAYLOAD_LEN: usize = 16 + 8;
+
+/// A signed token giving access to an object until an expiry time, eg
+/// for "share this invoice" links
+///
+/// The token packs the id, the expiry timestamp (in seconds) and a
+/// HMAC-SHA256 (truncated to 128 bits) of both and of the class, in
+/// URL safe base64:
+///
+/// ```
+/// use {kind::*, std::time::Duration};
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Inv")]
+/// pub struct Invoice {}
+///
+/// let invoice = Id::<Invoice>::random_v4();
+/// let token = ShareToken::valid_for(invoice, Duration::from_secs(3600)).encode(b"secret");
+/// let shared = ShareToken::<Invoice>::decode(&token, b"secret").unwrap();
+/// assert_eq!(shared.id(), invoice);
+/// ```
+///
+/// Decoding checks the signature, then the expiry. As the class is
+/// signed, the token of an invoice can't be used to access a customer
+/// with the same uuid.
+#[derive(Debug)]
+pub struct ShareToken<O: Identifiable> {
+    id: Id<O>,
+    expires_at: u64,
+}
+
+/// An error returned when decoding an invalid share token
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ShareTokenError {
+    /// The token isn't a share token
+    Malformed,
+    /// The signature doesn't match the payload, or the token is for
+    /// another class
+    Signature,
+    /// The token is valid but expired
+    Expired,
+}
+
+impl fmt::Display for ShareTokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "malformed share token"),
+            Self::Signature => write!(f, "invalid share token signature"),
+            Self::Expired => write!(f, "expired share token"),
+        }
+    }
+}
+
+impl std::error::Error for ShareTokenError {}
+
+impl<O: Identifiable> Clone for ShareToken<O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<O: Identifiable> Copy for ShareToken<O> {}
+
+impl<O: Identifiable> PartialEq for ShareToken<O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.expires_at == other.expires_at
+    }
+}
+impl<O: Identifiable> Eq for ShareToken<O> {}
+
+impl<O: Identifiable> ShareToken<O> {
+    /// Create a token expiring at the given time, rounded down to the
+    /// second
+    pub fn new(id: Id<O>, expires_at: SystemTime) -> Self {
+        let expires_at = expires_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self { id, expires_at }
+    }
+
+    /// Create a token expiring after the given duration
+    pub fn valid_for(id: Id<O>, duration: Duration) -> Self {
+        Self::new(id, SystemTime::now() + duration)
+    }
+
+    /// Return the id of the shared object
+    pub fn id(&self) -> Id<O> {
+        self.id
+    }
+
+    /// Return the expiry time of the token
+    pub fn expires_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.expires_at)
+    }
+
+    fn payload(&self) -> [u8; PAYLOAD_LEN] {
+        let mut payload = [0; PAYLOAD_LEN];
+        payload[..16].copy_from_slice(self.id.uuid.as_bytes());
+        payload[16..].copy_from_slice(&self.expires_at.to_be_bytes());
+        payload
+    }
+
+    /// Return the token, signed with the secret
+    pub fn encode(&self, secret: &[u8]) -> String {
+        let payload = self.payload();
+        let signature = mac::<O>(secret, &payload).finalize().into_bytes();
+        let mut token = [0; PAYLOAD_LEN + MAC_LEN];
+        token[..PAYLOAD_LEN].copy_from_slice(&payload);
+        token[PAYLOAD_LEN..].copy_from_slice(&signature[..MAC_LEN]);
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// Decode a token produced by `encode` with the same secret,
+    /// checking the signature, the class, and that it isn't expired
+    pub fn decode(token: &str, secret: &[u8]) -> Result<Self, ShareTokenError> {
+        Self::decode_at(token, secret, SystemTime::now())
+    }
+
+    /// Decode a token, checking it isn't expired at the given time
+    pub fn decode_at(token: &str, secret: &[u8], now: SystemTime) -> Result<Self, ShareTokenError> {
+        let mut bytes = [0; PAYLOAD_LEN + MAC_LEN];
+        match URL_SAFE_NO_PAD.decode_slice(token, &mut bytes) {
+            Ok(len) if len == bytes.len() => {}
+            _ => return Err(ShareTokenError::Malformed),
+        }
+        let (payload, signature) = bytes.split_at(PAYLOAD_LEN);
+        mac::<O>(secret, payload)
+            .verify_truncated_left(signature)
+            .map_err(|_| ShareTokenError::Signature)?;
+        let mut uuid = [0; 16];
+        uuid.copy_from_slice(&payload[..16]);
+        let mut expires_at = [0; 8];
+        expires_at.copy_from_slice(&payload[16..]);
+        let token = Self {
+            id: Id::unchecked(uuid::Uuid::from_bytes(uuid)),
+            expires_at: u64::from_be_bytes(expires_at),
+        };
+        if now >= token.expires_at() {
+            return Err(ShareTokenError::Expired);
+        }
+        Ok(token)
+    }
+}
+
+/// The HMAC of the class and of the payload
+fn mac<O: Identifiable>(secret: &[u8], payload: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any size");
+    mac.update(O::class().prefix().as_bytes());
+    mac.update(b"_");
+    mac.update(payload);
+    mac
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        std::time::{Duration, SystemTime},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Inv")]
+    pub struct Invoice {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const SECRET: &[u8] = b"secret";
+
+    #[test]
+    fn test_share_token() {
+        let now = SystemTime::now();
+        let token = ShareToken::new(Id::<Invoice>::random_v4(), now + Duration::from_secs(60));
+        let encoded = token.encode(SECRET);
+        assert_eq!(encoded.len(), 54);
+        assert_eq!(ShareToken::decode_at(&encoded, SECRET, now), Ok(token));
+        assert_eq!(ShareToken::decode(&encoded, SECRET), Ok(token));
+        assert_eq!(
+            ShareToken::<Invoice>::decode_at(&encoded, SECRET, now + Duration::from_secs(61)),
+            Err(ShareTokenError::Expired)
+        );
+    }
+
+    #[test]
+    fn test_invalid_share_token() {
+        let token = ShareToken::valid_for(Id::<Invoice>::random_v4(), Duration::from_secs(60));
+        let encoded = token.encode(SECRET);
+        assert_eq!(
+            ShareToken::<Invoice>::decode(&encoded, b"other"),
+            Err(ShareTokenError::Signature)
+        );
+        assert_eq!(
+            ShareToken::<Customer>::decode(&encoded, SECRET),
+            Err(ShareTokenError::Signature)
+        );
+        let longer = ShareToken::new(token.id(), token.expires_at() + Duration::from_secs(3600));
+        let forged = format!("{}{}", &longer.encode(SECRET)[..32], &encoded[32..]);
+        assert_eq!(
+            ShareToken::<Invoice>::decode(&forged, SECRET),
+            Err(ShareTokenError::Signature)
+        );
+        for malformed in ["", "not a token", &encoded[..50], &format!("{encoded}AA")] {
+            assert_eq!(
+                ShareToken::<Invoice>::decode(malformed, SECRET),
+                Err(ShareTokenError::Malformed)
+            );
+        }
+    }
+}
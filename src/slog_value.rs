@@ -0,0 +1,75 @@
+use {
+    super::*,
+    slog::{Key, Record, Serializer, Value},
+};
+
+/// An id is serialized by slog as its public id, eg
+/// `info!(log, "charged"; "customer" => id)`, or in its redacted form with
+/// the "redact" feature
+impl<O: Identifiable> Value for Id<O> {
+    fn serialize(&self, _: &Record, key: Key, serializer: &mut dyn Serializer) -> slog::Result {
+        #[cfg(not(feature = "redact"))]
+        return serializer.emit_arguments(key, &format_args!("{self}"));
+        #[cfg(feature = "redact")]
+        return serializer.emit_arguments(key, &format_args!("{}", self.redacted()));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        slog::{info, o, Drain, Key, Logger, OwnedKVList, Record, Serializer, KV},
+        std::{
+            fmt,
+            sync::{Arc, Mutex},
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    /// A drain keeping the key-values of the records
+    #[derive(Clone, Default)]
+    struct Values(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Serializer for Values {
+        fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments<'_>) -> slog::Result {
+            self.0
+                .lock()
+                .unwrap()
+                .push((key.to_string(), val.to_string()));
+            Ok(())
+        }
+    }
+
+    impl Drain for Values {
+        type Ok = ();
+        type Err = slog::Never;
+        fn log(&self, record: &Record<'_>, values: &OwnedKVList) -> Result<(), slog::Never> {
+            record.kv().serialize(record, &mut self.clone()).unwrap();
+            values.serialize(record, &mut self.clone()).unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_slog_value() {
+        let id = Id::<Customer>::random_v4();
+        let values = Values::default();
+        let logger = Logger::root(values.clone(), o!("customer" => id));
+        info!(logger, "charged"; "referrer" => id);
+        #[cfg(not(feature = "redact"))]
+        let expected = id.public_id();
+        #[cfg(feature = "redact")]
+        let expected = id.redacted().to_string();
+        assert_eq!(
+            *values.0.lock().unwrap(),
+            [
+                ("referrer".to_string(), expected.clone()),
+                ("customer".to_string(), expected),
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! A machine readable description of the public ids of the program,
+//! for the teams validating them in other languages.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let schema = spec::json_schema();
+//! assert_eq!(schema["$defs"]["Cust"]["x-kind"]["prefix"], "Cust");
+//! ```
+
+use {
+    super::*,
+    serde_json::{json, Map, Value},
+};
+
+/// The separator between the prefix and the UUID in public ids
+const SEPARATOR: &str = "_";
+
+/// Return a JSON Schema (draft 2020-12) of the public ids of all the
+/// registered classes.
+///
+/// Each class is defined in `$defs`, under its prefix, as a string
+/// schema with the pattern of its public ids, and an `x-kind` object
+/// describing their grammar: the prefix, the separator and the encoding
+/// of the UUID. The schema itself accepts the public ids of any class.
+pub fn json_schema() -> Value {
+    let mut classes: Vec<&RegisteredClass> = registry::classes().iter().collect();
+    classes.sort_by_key(|registered| registered.class().prefix());
+    let mut defs = Map::new();
+    for registered in classes {
+        let class = registered.class();
+        if defs.contains_key(class.prefix()) {
+            continue;
+        }
+        defs.insert(class.prefix().to_string(), class_schema(registered));
+    }
+    let any_of: Vec<Value> = defs
+        .keys()
+        .map(|prefix| json!({ "$ref": format!("#/$defs/{prefix}") }))
+        .collect();
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Public ids",
+        "description": format!(
+            "A public id is the prefix of the class, a '{SEPARATOR}', and a hyphenated UUID"
+        ),
+        "$defs": defs,
+        "anyOf": any_of,
+    })
+}
+
+fn class_schema(registered: &RegisteredClass) -> Value {
+    let class = registered.class();
+    json!({
+        "title": registered.type_name(),
+        "type": "string",
+        "pattern": accepted_pattern(class),
+        "examples": [class.example_public_id()],
+        "x-kind": {
+            "prefix": class.prefix(),
+            "prefix_case_sensitive": false,
+            "separator": SEPARATOR,
+            "encoding": "uuid-hyphenated",
+            "id_field": registered.id_field(),
+        },
+    })
+}
+
+/// The pattern of the public ids accepted by kind, whose prefix is case
+/// insensitive, eg `^[Cc][Uu][Ss][Tt]_...` (JSON Schema patterns have no
+/// case insensitive flag)
+fn accepted_pattern(class: IdClass) -> String {
+    let prefix: String = class
+        .prefix()
+        .chars()
+        .map(|c| match (c.to_ascii_uppercase(), c.to_ascii_lowercase()) {
+            (upper, lower) if upper != lower => format!("[{upper}{lower}]"),
+            _ => c.to_string(),
+        })
+        .collect();
+    let pattern = class.public_id_pattern();
+    format!("^{prefix}{}", &pattern[1 + class.prefix().len()..])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "SpecInv", id_field = "invoice")]
+    pub struct Invoice {}
+
+    #[test]
+    fn test_json_schema() {
+        let schema = spec::json_schema();
+        let def = &schema["$defs"]["SpecInv"];
+        assert_eq!(def["title"], "Invoice");
+        assert_eq!(def["x-kind"]["prefix_case_sensitive"], false);
+        let pattern = def["pattern"].as_str().unwrap();
+        let uuid_pattern = &Invoice::class().public_id_pattern()["^SpecInv".len()..];
+        assert_eq!(
+            pattern,
+            format!("^[Ss][Pp][Ee][Cc][Ii][Nn][Vv]{uuid_pattern}")
+        );
+        assert_eq!(def["x-kind"]["separator"], "_");
+        assert_eq!(def["x-kind"]["id_field"], "invoice");
+        let any_of = schema["anyOf"].as_array().unwrap();
+        assert_eq!(any_of.len(), schema["$defs"].as_object().unwrap().len());
+        assert!(any_of.iter().any(|r| r["$ref"] == "#/$defs/SpecInv"));
+    }
+}
@@ -0,0 +1,186 @@
+//! Helpers for the most common queries on tables of identified rows.
+//!
+//! The table is expected to have an `id` column of type `uuid`, and the
+//! other columns are read with the `FromRow` implementation of the entity,
+//! as when reading an `Ided` with sqlx.
+//!
+//! ```no_run
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable, sqlx::FromRow)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {
+//!     pub name: String,
+//! }
+//!
+//! async fn example(pool: &sqlx::PgPool, id: Id<Customer>) -> Result<(), sqlx::Error> {
+//!     let customer: Option<Ided<Customer>> = sql::fetch_by_id(pool, "customer", id).await?;
+//!     if sql::exists(pool, "customer", id).await? {
+//!         sql::delete_by_id(pool, "customer", id).await?;
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The table name is inserted in the query, so it must be a plain
+//! identifier, optionally qualified by a schema (eg `billing.customer`):
+//! other names are rejected with `sqlx::Error::InvalidArgument`.
+
+use {
+    crate::*,
+    sqlx::{postgres::PgRow, FromRow, PgExecutor},
+    uuid::Uuid,
+};
+
+/// Fetch the row of the given id, if any
+pub async fn fetch_by_id<'e, T, E, X>(
+    executor: X,
+    table: &str,
+    id: Id<T>,
+) -> Result<Option<Ided<T, E>>, sqlx::Error>
+where
+    T: Identifiable + Send + Unpin,
+    E: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    X: PgExecutor<'e>,
+{
+    let query = format!("SELECT * FROM {} WHERE id = $1", checked_table(table)?);
+    sqlx::query_as(&query)
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+}
+
+/// Fetch the rows of the given ids, in no specific order
+///
+/// Ids without row are ignored.
+pub async fn select_by_ids<'e, T, E, X>(
+    executor: X,
+    table: &str,
+    ids: &[Id<T>],
+) -> Result<Vec<Ided<T, E>>, sqlx::Error>
+where
+    T: Identifiable + Send + Unpin,
+    E: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    X: PgExecutor<'e>,
+{
+    let query = format!("SELECT * FROM {} WHERE id = ANY($1)", checked_table(table)?);
+    sqlx::query_as(&query).bind(ids).fetch_all(executor).await
+}
+
+/// Tell whether there's a row with the given id
+pub async fn exists<'e, T, X>(executor: X, table: &str, id: Id<T>) -> Result<bool, sqlx::Error>
+where
+    T: Identifiable,
+    X: PgExecutor<'e>,
+{
+    let query = format!(
+        "SELECT EXISTS (SELECT 1 FROM {} WHERE id = $1)",
+        checked_table(table)?,
+    );
+    sqlx::query_scalar(&query)
+        .bind(id)
+        .fetch_one(executor)
+        .await
+}
+
+/// Delete the row with the given id, returning whether there was one
+pub async fn delete_by_id<'e, T, X>(
+    executor: X,
+    table: &str,
+    id: Id<T>,
+) -> Result<bool, sqlx::Error>
+where
+    T: Identifiable,
+    X: PgExecutor<'e>,
+{
+    let query = format!("DELETE FROM {} WHERE id = $1", checked_table(table)?);
+    let result = sqlx::query(&query).bind(id).execute(executor).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Fetch the row of the canonical id of the given one, if any
+pub async fn fetch_canonical<'e, T, E, X>(
+    executor: X,
+    table: &str,
+    merges: &MergeMap<T>,
+    id: Id<T>,
+) -> Result<Option<Ided<T, E>>, sqlx::Error>
+where
+    T: Identifiable + Send + Unpin,
+    E: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+    X: PgExecutor<'e>,
+{
+    fetch_by_id(executor, table, merges.canonicalize(id)).await
+}
+
+/// The merges are stored in a table with `id` and `merged_into` uuid
+/// columns, `id` being its primary key
+impl<T: Identifiable> MergeMap<T> {
+    /// Load the merges of the table
+    pub async fn load<'e, X: PgExecutor<'e>>(
+        executor: X,
+        table: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let query = format!("SELECT id, merged_into FROM {}", checked_table(table)?);
+        let rows: Vec<(Uuid, Uuid)> = sqlx::query_as(&query).fetch_all(executor).await?;
+        Ok(rows
+            .into_iter()
+            .map(|(id, into)| (Id::unchecked(id), Id::unchecked(into)))
+            .collect())
+    }
+
+    /// Insert the merges in the table, updating the canonical ids of the
+    /// ids already there
+    pub async fn save<'e, X: PgExecutor<'e>>(
+        &self,
+        executor: X,
+        table: &str,
+    ) -> Result<(), sqlx::Error> {
+        let query = format!(
+            "INSERT INTO {} (id, merged_into) SELECT * FROM UNNEST($1::uuid[], $2::uuid[]) \
+            ON CONFLICT (id) DO UPDATE SET merged_into = EXCLUDED.merged_into",
+            checked_table(table)?,
+        );
+        let (ids, merged_into): (Vec<Id<T>>, Vec<Id<T>>) = self.iter().unzip();
+        sqlx::query(&query)
+            .bind(ids)
+            .bind(merged_into)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Check the table name can be safely inserted in a query
+fn checked_table(table: &str) -> Result<&str, sqlx::Error> {
+    let valid = table.split('.').count() <= 2
+        && table.split('.').all(|part| {
+            part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    if valid {
+        Ok(table)
+    } else {
+        Err(sqlx::Error::InvalidArgument(format!(
+            "invalid table name: {table:?}"
+        )))
+    }
+}
+
+#[test]
+fn test_checked_table() {
+    for table in ["customer", "billing.customer", "_tmp", "Customer_2"] {
+        assert!(checked_table(table).is_ok(), "{table}");
+    }
+    for table in [
+        "",
+        "a.b.c",
+        "2fast",
+        "customer;",
+        "customer c",
+        "\"customer\"",
+        "cust.",
+    ] {
+        assert!(checked_table(table).is_err(), "{table}");
+    }
+}
@@ -0,0 +1,116 @@
+use {
+    super::*,
+    sqlx::{
+        decode::Decode,
+        encode::{Encode, IsNull},
+        error::BoxDynError,
+        sqlite::{Sqlite, SqliteArgumentValue, SqliteRow, SqliteTypeInfo, SqliteValueRef},
+        types::Uuid,
+        Row, Type,
+    },
+};
+
+/// An Id is bound as a 16 bytes BLOB.
+///
+/// It can be read from a BLOB column or from a TEXT one holding the
+/// hyphenated uuid. To write in a TEXT column, bind `id.db_id()`.
+impl<O: Identifiable> Type<Sqlite> for Id<O> {
+    fn type_info() -> SqliteTypeInfo {
+        <Uuid as Type<Sqlite>>::type_info()
+    }
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <Uuid as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q, O: Identifiable> Encode<'q, Sqlite> for Id<O> {
+    fn encode_by_ref(
+        &self,
+        args: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<IsNull, BoxDynError> {
+        <Uuid as Encode<'q, Sqlite>>::encode_by_ref(&self.uuid(), args)
+    }
+}
+
+impl<O: Identifiable> Decode<'_, Sqlite> for Id<O> {
+    fn decode(value: SqliteValueRef<'_>) -> Result<Self, BoxDynError> {
+        let bytes = <&[u8] as Decode<'_, Sqlite>>::decode(value)?;
+        Ok(Id::from_uuid_column(bytes)?)
+    }
+}
+
+/// Binding an Ided in a query binds its id
+impl<T: Identifiable, E> Type<Sqlite> for Ided<T, E> {
+    fn type_info() -> SqliteTypeInfo {
+        <Id<T> as Type<Sqlite>>::type_info()
+    }
+}
+
+impl<'q, T: Identifiable, E> Encode<'q, Sqlite> for Ided<T, E> {
+    fn encode_by_ref(
+        &self,
+        args: &mut Vec<SqliteArgumentValue<'q>>,
+    ) -> Result<IsNull, BoxDynError> {
+        <Id<T> as Encode<'q, Sqlite>>::encode_by_ref(&self.id(), args)
+    }
+}
+
+impl<'e, T, E> sqlx::FromRow<'e, SqliteRow> for Ided<T, E>
+where
+    T: Identifiable,
+    E: sqlx::FromRow<'e, SqliteRow>,
+{
+    fn from_row(row: &'e SqliteRow) -> sqlx::Result<Self> {
+        let id = row.try_get::<Id<T>, _>("id")?;
+        let entity = E::from_row(row)?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        sqlx::{Connection, SqliteConnection},
+    };
+
+    #[derive(Debug, Identifiable, sqlx::FromRow)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        pub name: String,
+    }
+
+    #[test]
+    fn test_blob_and_text_columns() {
+        async_std::task::block_on(async {
+            let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+            sqlx::query("CREATE TABLE customer (id BLOB PRIMARY KEY, name TEXT, legacy_id TEXT)")
+                .execute(&mut conn)
+                .await
+                .unwrap();
+            let id = Id::<Customer>::random_v4();
+            sqlx::query("INSERT INTO customer (id, name, legacy_id) VALUES ($1, $2, $3)")
+                .bind(id)
+                .bind("John")
+                .bind(id.db_id())
+                .execute(&mut conn)
+                .await
+                .unwrap();
+
+            let customer: Ided<Customer> = sqlx::query_as("SELECT id, name FROM customer")
+                .fetch_one(&mut conn)
+                .await
+                .unwrap();
+            assert_eq!(customer.id(), id);
+            assert_eq!(customer.name, "John");
+
+            let (legacy_id,): (Id<Customer>,) =
+                sqlx::query_as("SELECT legacy_id FROM customer WHERE id = $1")
+                    .bind(&customer)
+                    .fetch_one(&mut conn)
+                    .await
+                    .unwrap();
+            assert_eq!(legacy_id, id);
+        });
+    }
+}
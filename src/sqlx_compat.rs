@@ -0,0 +1,93 @@
+//! Using ids with the compile-time checked macros of sqlx
+//! (`query!`, `query_as!`, `query_scalar!`).
+//!
+//! The macros type the `uuid` columns as `Uuid`. When reading into a
+//! struct with `query_as!`, the value of a non nullable column is
+//! converted with `Into`, so an `Id<Customer>` field just works:
+//!
+//! ```ignore
+//! struct Invoice {
+//!     id: Id<Invoice>,
+//!     customer: Id<Customer>,
+//! }
+//! let invoice = sqlx::query_as!(Invoice, "SELECT id, customer FROM invoice")
+//!     .fetch_one(&pool)
+//!     .await?;
+//! ```
+//!
+//! Other columns (nullable ones, arrays, and all the columns of `query!`
+//! records) need a type override in the column alias, which makes sqlx
+//! decode the `Id` directly:
+//!
+//! ```ignore
+//! let invoice = sqlx::query!(
+//!     r#"SELECT
+//!         id AS "id: Id<Invoice>",
+//!         referrer AS "referrer: Id<Customer>",
+//!         contracts AS "contracts: Vec<Id<Contract>>"
+//!     FROM invoice"#
+//! )
+//! .fetch_one(&pool)
+//! .await?;
+//! ```
+//!
+//! (`referrer` being nullable, sqlx makes it an `Option<Id<Customer>>`.)
+//!
+//! Bind parameters are checked against `Uuid` too. Ids can be given as
+//! they are with the `as _` override, or converted with [`uuids`] for
+//! arrays:
+//!
+//! ```ignore
+//! sqlx::query!("DELETE FROM invoice WHERE customer = $1", customer_id as _)
+//!     .execute(&pool)
+//!     .await?;
+//! sqlx::query!("DELETE FROM invoice WHERE id = ANY($1)", &uuids(&ids))
+//!     .execute(&pool)
+//!     .await?;
+//! ```
+//!
+//! Results kept as `Uuid` can be converted back with [`ids`].
+
+use {crate::*, uuid::Uuid};
+
+/// The uuids of the ids, eg to bind an array in a checked query
+pub fn uuids<O: Identifiable>(ids: &[Id<O>]) -> Vec<Uuid> {
+    ids.iter().map(Id::uuid).collect()
+}
+
+/// Ids from uuids read in a checked query, with no check
+pub fn ids<O: Identifiable>(uuids: impl IntoIterator<Item = Uuid>) -> Vec<Id<O>> {
+    uuids.into_iter().map(Id::from).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        sqlx::{postgres::PgArguments, Arguments, Postgres, Type},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_macro_patterns() {
+        // column type overrides decode an Id from an uuid column
+        let uuid_column = <Uuid as Type<Postgres>>::type_info();
+        assert!(<Id<Customer> as Type<Postgres>>::compatible(&uuid_column));
+
+        // query_as! converts non nullable columns with Into
+        let uuid = Uuid::new_v4();
+        let id: Id<Customer> = uuid.into();
+        assert_eq!(id.uuid(), uuid);
+
+        let ids = vec![id, Id::random_v4()];
+        let mut with_ids = PgArguments::default();
+        with_ids.add(&ids).unwrap();
+        let mut with_uuids = PgArguments::default();
+        with_uuids.add(uuids(&ids)).unwrap();
+        assert_eq!(format!("{with_ids:?}"), format!("{with_uuids:?}"));
+        assert_eq!(super::ids::<Customer>(uuids(&ids)), ids);
+    }
+}
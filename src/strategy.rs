@@ -0,0 +1,185 @@
+//! Proptest strategies generating ids, behind the `proptest` feature.
+//!
+//! `Id<O>`, `Ided<T, E>`, `AnyId` (with the `registry` feature) and the
+//! enumerations of `id_enum!` implement `Arbitrary`, generating random
+//! (v4) uuids, and the functions of this module generate public ids,
+//! valid or not, to test the parsing of the inputs of an API:
+//!
+//! ```
+//! use {kind::*, proptest::prelude::*};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! proptest! {
+//!     fn parses_valid_ids(public_id in strategy::public_ids::<Customer>()) {
+//!         prop_assert!(Id::<Customer>::from_public_id(&public_id).is_ok());
+//!     }
+//!
+//!     fn rejects_invalid_ids(input in strategy::invalid_public_ids::<Customer>()) {
+//!         prop_assert!(Id::<Customer>::from_public_id(&input).is_err());
+//!     }
+//! }
+//! # parses_valid_ids();
+//! # rejects_invalid_ids();
+//! ```
+
+use {
+    super::*,
+    proptest::{
+        arbitrary::{any, Arbitrary, StrategyFor},
+        prop_oneof,
+        sample::Index,
+        strategy::{BoxedStrategy, Just, Map, Strategy},
+    },
+    uuid::{Builder, Uuid},
+};
+
+fn random_uuid(bytes: [u8; 16]) -> Uuid {
+    Builder::from_random_bytes(bytes).into_uuid()
+}
+
+/// Ids of random v4 uuids
+impl<O: Identifiable> Arbitrary for Id<O> {
+    type Parameters = ();
+    type Strategy = Map<StrategyFor<[u8; 16]>, fn([u8; 16]) -> Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        any::<[u8; 16]>().prop_map(|bytes| Id::unchecked(random_uuid(bytes)))
+    }
+}
+
+impl<T: Identifiable + std::fmt::Debug, E: Arbitrary> Arbitrary for Ided<T, E> {
+    type Parameters = E::Parameters;
+    type Strategy = Map<(StrategyFor<Id<T>>, E::Strategy), fn((Id<T>, E)) -> Self>;
+
+    fn arbitrary_with(parameters: E::Parameters) -> Self::Strategy {
+        (any::<Id<T>>(), E::arbitrary_with(parameters))
+            .prop_map(|(id, entity)| Ided::new(id, entity))
+    }
+}
+
+/// Ids of any of the registered classes
+#[cfg(feature = "registry")]
+impl Arbitrary for AnyId {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let classes: Vec<IdClass> = registry::classes().iter().map(|c| c.class()).collect();
+        (proptest::sample::select(classes), any::<[u8; 16]>())
+            .prop_map(|(class, bytes)| {
+                AnyId::from_public_id(&class.public_id(&random_uuid(bytes)))
+                    .expect("registered classes are valid")
+            })
+            .boxed()
+    }
+}
+
+/// Change the case of the ASCII letters given by the mask
+fn change_case(s: &str, mask: u64) -> String {
+    s.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if mask >> (i % 64) & 1 == 1 {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+/// Valid public ids of the class, as written by kind or with another
+/// case (parsing being case insensitive)
+pub fn public_ids<O: Identifiable + 'static>() -> BoxedStrategy<String> {
+    prop_oneof![
+        3 => any::<Id<O>>().prop_map(|id| id.public_id()),
+        1 => (any::<Id<O>>(), any::<u64>()).prop_map(|(id, mask)| change_case(&id.public_id(), mask)),
+    ]
+    .boxed()
+}
+
+/// Strings which aren't valid public ids of the class: ids of other
+/// classes, ids with a missing, extra or replaced character, ids cut
+/// anywhere, and random strings
+pub fn invalid_public_ids<O: Identifiable + 'static>() -> BoxedStrategy<String> {
+    let prefix = O::class().prefix();
+    let other_class = "[A-Za-z0-9]{1,8}"
+        .prop_filter("another class", move |p| !p.eq_ignore_ascii_case(prefix))
+        .prop_flat_map(|p| (Just(p), any::<[u8; 16]>()))
+        .prop_map(|(p, bytes)| format!("{p}_{}", random_uuid(bytes)));
+    let edited = (public_ids::<O>(), any::<Index>(), 0..3, any::<char>()).prop_map(
+        |(public_id, index, edit, c)| {
+            let mut chars: Vec<char> = public_id.chars().collect();
+            let i = index.index(chars.len());
+            match edit {
+                0 => {
+                    chars.remove(i);
+                }
+                1 => chars.insert(i, c),
+                _ => chars[i] = c,
+            }
+            chars.into_iter().collect()
+        },
+    );
+    let cut = (public_ids::<O>(), any::<Index>())
+        .prop_map(|(public_id, index)| public_id[..index.index(public_id.len())].to_string());
+    prop_oneof![other_class, edited, cut, ".*", Just(String::new())]
+        .prop_filter("an invalid id", |s| Id::<O>::from_public_id(s).is_err())
+        .boxed()
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, proptest::prelude::*};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    #[cfg(feature = "serde")]
+    id_enum! {PartyId: Customer, Contract}
+
+    proptest! {
+        #[test]
+        fn test_public_ids(public_id in strategy::public_ids::<Customer>()) {
+            let id = Id::<Customer>::from_public_id(&public_id).unwrap();
+            prop_assert!(id.public_id().eq_ignore_ascii_case(&public_id));
+        }
+
+        #[test]
+        fn test_invalid_public_ids(input in strategy::invalid_public_ids::<Customer>()) {
+            prop_assert!(input.parse::<Id<Customer>>().is_err());
+        }
+
+        #[test]
+        fn test_arbitrary(ided in any::<Ided<Customer, u8>>(), contract in any::<Id<Contract>>()) {
+            let id = ided.id();
+            prop_assert_eq!(id.uuid().get_version_num(), 4);
+            prop_assert_eq!(id.public_id().parse::<Id<Customer>>().unwrap(), id);
+            prop_assert!(contract.public_id().starts_with("Cont_"));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    proptest! {
+        #[test]
+        fn test_id_enum(id in any::<PartyId>()) {
+            prop_assert_eq!(id.to_string().parse::<PartyId>().unwrap(), id);
+        }
+    }
+
+    #[cfg(feature = "registry")]
+    proptest! {
+        #[test]
+        fn test_any_id(id in any::<AnyId>()) {
+            prop_assert_eq!(AnyId::from_public_id(&id.public_id()).unwrap(), id);
+        }
+    }
+}
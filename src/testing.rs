@@ -0,0 +1,313 @@
+//! Assertions for tests of code handling ids, behind the `testing`
+//! feature, checking ids with the grammar of the crate rather than with
+//! regular expressions which drift from it.
+//!
+//! ```
+//! use {kind::*, serde_json::json};
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! let id = testing::assert_valid_id::<Customer>("Cust_371c35ec-34d9-4315-ab31-7ea8889a419a");
+//! testing::assert_id_eq_ignoring_case(&id.public_id(), "CUST_371C35EC-34D9-4315-AB31-7EA8889A419A");
+//!
+//! let body = json!({
+//!     "id": "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a",
+//!     "items": [{ "customer_id": "Cust_d6a1e4c2-8b1f-4c2e-9d3a-5f6b7c8d9e0f" }],
+//! });
+//! testing::assert_json_ids_of_class(&body, "Cust", &["$.id", "$.items[*].customer_id"]);
+//! ```
+//!
+//! The assertions panic, with a message giving the offending value, like
+//! `assert_eq!`.
+//!
+//! [`with_deterministic_ids`] makes the ids generated by the code under
+//! test reproducible, eg to compare responses with snapshots.
+
+use {super::*, crate::split_mix::SplitMix, serde_json::Value, std::cell::Cell, uuid::Uuid};
+
+thread_local! {
+    static DETERMINISTIC_IDS: Cell<Option<SplitMix>> = const { Cell::new(None) };
+}
+
+/// Run the closure with the ids generated by [`Id::random_v4`], on this
+/// thread, taken from a sequence given by the seed, so that the same
+/// test generates the same ids on each run
+///
+/// ```
+/// use kind::*;
+///
+/// #[derive(Debug, Identifiable)]
+/// #[kind(class="Cust")]
+/// pub struct Customer {}
+///
+/// let first = testing::with_deterministic_ids(42, || Id::<Customer>::random_v4());
+/// let again = testing::with_deterministic_ids(42, || Id::<Customer>::random_v4());
+/// assert_eq!(first, again);
+/// ```
+///
+/// The ids are still valid v4 ids. The override is thread local: ids
+/// generated on other threads, eg by tasks spawned on a multi-threaded
+/// runtime, are random. Calls can be nested, the previous sequence being
+/// restored when the closure returns or panics.
+pub fn with_deterministic_ids<R>(seed: u64, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<SplitMix>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DETERMINISTIC_IDS.with(|rng| rng.set(self.0));
+        }
+    }
+    let _restore = Restore(DETERMINISTIC_IDS.with(|rng| rng.replace(Some(SplitMix(seed)))));
+    f()
+}
+
+/// Return the next uuid of the sequence of the current
+/// `with_deterministic_ids` closure, if any
+pub(crate) fn deterministic_uuid() -> Option<Uuid> {
+    DETERMINISTIC_IDS.with(|cell| {
+        let mut rng = cell.get()?;
+        let uuid = rng.uuid();
+        cell.set(Some(rng));
+        Some(uuid)
+    })
+}
+
+/// Assert the string is a valid public id of the class of `O`, and
+/// return the id
+#[track_caller]
+pub fn assert_valid_id<O: Identifiable>(public_id: &str) -> Id<O> {
+    match Id::<O>::from_public_id(public_id) {
+        Ok(id) => id,
+        Err(e) => panic!(
+            "{public_id:?} isn't a valid {}_ id: {e}",
+            O::class().prefix()
+        ),
+    }
+}
+
+/// The class of a prefix given to an assertion, leaked as classes need
+/// static prefixes, like in the command line tool
+#[track_caller]
+fn class_of(prefix: &str) -> IdClass {
+    assert!(
+        !prefix.is_empty() && prefix.bytes().all(|b| b.is_ascii_alphanumeric()),
+        "invalid class {prefix:?}"
+    );
+    IdClass::new(Box::leak(prefix.into()))
+}
+
+/// Parse a public id of any class, with the class of the text before its
+/// first underscore, panicking when it isn't a public id
+#[track_caller]
+fn parse_any(public_id: &str) -> (IdClass, Uuid) {
+    let class = match public_id.split_once('_') {
+        Some((prefix, _)) if !prefix.is_empty() => class_of(prefix),
+        _ => panic!("{public_id:?} isn't a public id"),
+    };
+    match class.parse_public_id(public_id) {
+        Ok(uuid) => (class, uuid),
+        Err(e) => panic!("{public_id:?} isn't a public id: {e}"),
+    }
+}
+
+/// Assert the strings are public ids of the same object, the case of
+/// the prefix and of the uuid being ignored, as when parsing
+#[track_caller]
+pub fn assert_id_eq_ignoring_case(left: &str, right: &str) {
+    let (class, left_uuid) = parse_any(left);
+    let right_uuid = match class.parse_public_id(right) {
+        Ok(uuid) => Some(uuid),
+        Err(e) if e.kind() == IdErrorKind::WrongClass => None,
+        Err(_) => Some(parse_any(right).1),
+    };
+    assert!(
+        right_uuid == Some(left_uuid),
+        "assertion `left == right` failed (ignoring case)\n  left: {left:?}\n right: {right:?}"
+    );
+}
+
+/// Assert the values found at the paths of the JSON document are all
+/// public ids of the class with the given prefix, and that each path
+/// matches at least one value
+///
+/// Paths are a subset of JSONPath: `$` followed by keys (`.customer_id`
+/// or `['customer_id']`), indexes (`[0]`) and wildcards (`[*]` or `.*`).
+#[track_caller]
+pub fn assert_json_ids_of_class(body: &Value, prefix: &str, paths: &[&str]) {
+    let class = class_of(prefix);
+    for path in paths {
+        let values = select(body, path);
+        assert!(!values.is_empty(), "no value at {path}");
+        for value in values {
+            let Some(public_id) = value.as_str() else {
+                panic!("{value} at {path} isn't a string");
+            };
+            if let Err(e) = class.parse_public_id(public_id) {
+                panic!("{public_id:?} at {path} isn't a {prefix}_ id: {e}");
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Step<'p> {
+    Key(&'p str),
+    Index(usize),
+    Wildcard,
+}
+
+/// Parse the steps of a path, panicking on unsupported syntax
+#[track_caller]
+fn steps(path: &str) -> Vec<Step<'_>> {
+    let unsupported = || -> ! { panic!("unsupported JSON path {path:?}") };
+    let Some(mut rest) = path.strip_prefix('$') else {
+        unsupported()
+    };
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('.') {
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            steps.push(match &r[..end] {
+                "" => unsupported(),
+                "*" => Step::Wildcard,
+                key => Step::Key(key),
+            });
+            rest = &r[end..];
+        } else if let Some(r) = rest.strip_prefix('[') {
+            let end = r.find(']').unwrap_or_else(|| unsupported());
+            let inner = &r[..end];
+            steps.push(if inner == "*" {
+                Step::Wildcard
+            } else if let Ok(index) = inner.parse() {
+                Step::Index(index)
+            } else if let Some(key) = inner
+                .strip_prefix('\'')
+                .and_then(|k| k.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|k| k.strip_suffix('"')))
+            {
+                Step::Key(key)
+            } else {
+                unsupported()
+            });
+            rest = &r[end + 1..];
+        } else {
+            unsupported()
+        }
+    }
+    steps
+}
+
+/// Return the values of the document matching the path
+#[track_caller]
+fn select<'v>(document: &'v Value, path: &str) -> Vec<&'v Value> {
+    let mut values = vec![document];
+    for step in steps(path) {
+        values = values
+            .into_iter()
+            .flat_map(|value| -> Vec<&Value> {
+                match (step, value) {
+                    (Step::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+                    (Step::Index(index), Value::Array(array)) => {
+                        array.get(index).into_iter().collect()
+                    }
+                    (Step::Wildcard, Value::Array(array)) => array.iter().collect(),
+                    (Step::Wildcard, Value::Object(map)) => map.values().collect(),
+                    _ => vec![],
+                }
+            })
+            .collect();
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, serde_json::json};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_assert_valid_id() {
+        assert_eq!(
+            testing::assert_valid_id::<Customer>(&format!("cust_{ID}")).db_id(),
+            ID
+        );
+        testing::assert_id_eq_ignoring_case(
+            &format!("Cust_{ID}"),
+            &format!("CUST_{}", ID.to_uppercase()),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "isn't a valid Cust_ id: wrong object class")]
+    fn test_assert_invalid_id() {
+        testing::assert_valid_id::<Customer>(&format!("Cont_{ID}"));
+    }
+
+    #[test]
+    #[should_panic(expected = "(ignoring case)")]
+    fn test_assert_id_ne() {
+        testing::assert_id_eq_ignoring_case(&format!("Cust_{ID}"), &format!("Cont_{ID}"));
+    }
+
+    #[test]
+    fn test_assert_json_ids() {
+        let body = json!({
+            "id": format!("Cust_{ID}"),
+            "items": [
+                { "customer_id": format!("CUST_{ID}") },
+                { "customer_id": format!("cust_{ID}") },
+            ],
+            "parent": { "customer": format!("Cust_{ID}") },
+        });
+        testing::assert_json_ids_of_class(
+            &body,
+            "Cust",
+            &[
+                "$.id",
+                "$.items[*].customer_id",
+                "$['items'][1]['customer_id']",
+                "$.parent.*",
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at $.items[*].customer_id isn't a Cust_ id")]
+    fn test_assert_json_ids_of_other_class() {
+        let body = json!({ "items": [{ "customer_id": format!("Cont_{ID}") }] });
+        testing::assert_json_ids_of_class(&body, "Cust", &["$.items[*].customer_id"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at $.id isn't a Cust_ id: invalid format")]
+    fn test_assert_json_ids_invalid() {
+        testing::assert_json_ids_of_class(&json!({ "id": "Cust_123" }), "Cust", &["$.id"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no value at $.customer")]
+    fn test_assert_json_ids_missing() {
+        testing::assert_json_ids_of_class(&json!({ "id": ID }), "Cust", &["$.customer"]);
+    }
+
+    #[test]
+    fn test_deterministic_ids() {
+        let ids = || [(); 3].map(|_| Id::<Customer>::random_v4());
+        let first = testing::with_deterministic_ids(42, ids);
+        assert_eq!(testing::with_deterministic_ids(42, ids), first);
+        assert_ne!(testing::with_deterministic_ids(43, ids), first);
+        assert_ne!(ids(), first);
+        assert_eq!(first[0].uuid().get_version_num(), 4);
+        testing::with_deterministic_ids(42, || {
+            assert_eq!(Id::<Customer>::random_v4(), first[0]);
+            testing::with_deterministic_ids(7, Id::<Customer>::random_v4);
+            assert_eq!(Id::<Customer>::random_v4(), first[1]);
+        });
+    }
+}
@@ -0,0 +1,93 @@
+use {
+    super::*,
+    tonic::{
+        metadata::{AsciiMetadataValue, MetadataMap},
+        Status,
+    },
+};
+
+/// Convert the string field of a message into an id, with an
+/// `INVALID_ARGUMENT` status naming the field and the expected prefix
+/// when it isn't a valid id of the class:
+///
+/// ```ignore
+/// let customer: Id<Customer> = field_id("customer_id", request.customer_id)?;
+/// ```
+pub fn field_id<O, S>(field: &str, value: S) -> Result<Id<O>, Status>
+where
+    O: Identifiable,
+    S: TryInto<Id<O>, Error = IdError>,
+{
+    value.try_into().map_err(|e| invalid::<O>(field, e))
+}
+
+/// Read the id of the given metadata key, with an `INVALID_ARGUMENT`
+/// status when it's missing or invalid
+pub fn metadata_id<O: Identifiable>(metadata: &MetadataMap, key: &str) -> Result<Id<O>, Status> {
+    let value = metadata
+        .get(key)
+        .ok_or_else(|| Status::invalid_argument(format!("missing {key} metadata")))?;
+    let value = value
+        .to_str()
+        .map_err(|_| invalid::<O>(key, IdError::new(IdErrorKind::InvalidFormat)))?;
+    field_id(key, value)
+}
+
+/// Write the public id in the given metadata key, replacing any
+/// previous value
+pub fn set_metadata_id<O: Identifiable>(metadata: &mut MetadataMap, key: &'static str, id: Id<O>) {
+    let value: AsciiMetadataValue = id
+        .public_id()
+        .parse()
+        .expect("public ids are valid metadata values");
+    metadata.insert(key, value);
+}
+
+fn invalid<O: Identifiable>(field: &str, error: IdError) -> Status {
+    Status::invalid_argument(format!(
+        "invalid {field}: {error} (expected {}_ prefix)",
+        O::class().prefix()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        tonic::{metadata::MetadataMap, Code},
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cont")]
+    pub struct Contract {}
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_field_id() {
+        let id: Id<Customer> = field_id("customer_id", format!("Cust_{ID}")).unwrap();
+        assert_eq!(id.db_id(), ID);
+        let status = field_id::<Contract, _>("contract_id", format!("Cust_{ID}")).unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "invalid contract_id: wrong object class (expected Cont_ prefix)"
+        );
+    }
+
+    #[test]
+    fn test_metadata_id() {
+        let mut metadata = MetadataMap::new();
+        let status = metadata_id::<Customer>(&metadata, "x-customer-id").unwrap_err();
+        assert_eq!(status.message(), "missing x-customer-id metadata");
+        let id = Id::<Customer>::random_v4();
+        set_metadata_id(&mut metadata, "x-customer-id", id);
+        assert_eq!(metadata_id(&metadata, "x-customer-id").unwrap(), id);
+        let status = metadata_id::<Contract>(&metadata, "x-customer-id").unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+}
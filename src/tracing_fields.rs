@@ -0,0 +1,157 @@
+use {
+    super::*,
+    tracing::{field::Value, Span},
+};
+
+impl<O: Identifiable> Id<O> {
+    /// Return the id as a tracing field value, its public id, or its
+    /// redacted form with the "redact" feature:
+    ///
+    /// ```ignore
+    /// let span = info_span!("load_invoices", customer = customer_id.as_span_field());
+    /// ```
+    ///
+    /// (`Id` can't implement `tracing::Value` itself, this trait being
+    /// sealed)
+    pub fn as_span_field(&self) -> impl Value {
+        #[cfg(not(feature = "redact"))]
+        return tracing::field::display(*self);
+        #[cfg(feature = "redact")]
+        return tracing::field::display(self.redacted());
+    }
+}
+
+/// Record the class and the id of an entity in the `entity.class` and
+/// `entity.id` fields of a span, which must have been declared when
+/// creating it (tracing ignores the other fields):
+///
+/// ```ignore
+/// let span = info_span!("charge", entity.class = Empty, entity.id = Empty);
+/// record_entity(&span, invoice_id);
+/// ```
+///
+/// See [`entity_span!`](crate::entity_span) to create such a span.
+pub fn record_entity<O: Identifiable>(span: &Span, id: Id<O>) -> &Span {
+    span.record(ENTITY_CLASS_FIELD, O::class().prefix());
+    span.record(ENTITY_ID_FIELD, id.as_span_field());
+    span
+}
+
+/// Create a span with the `entity.class` and `entity.id` fields of the
+/// given id, and optionally other fields:
+///
+/// ```ignore
+/// let span = entity_span!(Level::INFO, "charge", invoice_id, amount = 42);
+/// ```
+#[macro_export]
+macro_rules! entity_span {
+    ($level:expr, $name:expr, $id:expr $(, $($fields:tt)+)?) => {{
+        let id = $id;
+        $crate::__private::tracing::span!(
+            $level,
+            $name,
+            entity.class = id.class().prefix(),
+            entity.id = id.as_span_field()
+            $(, $($fields)+)?
+        )
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        crate::*,
+        std::{
+            fmt,
+            sync::{Arc, Mutex},
+        },
+        tracing::{
+            field::{Field, Visit},
+            span, Event, Level, Metadata, Subscriber,
+        },
+    };
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    /// A subscriber keeping the recorded span fields
+    #[derive(Clone, Default)]
+    struct Fields(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            let value = format!("{value:?}");
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), value));
+        }
+    }
+
+    impl Subscriber for Fields {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            span.record(&mut self.clone());
+            span::Id::from_u64(1)
+        }
+        fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+            values.record(&mut self.clone());
+        }
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+        fn event(&self, _: &Event<'_>) {}
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    fn fields(f: impl FnOnce()) -> Vec<(String, String)> {
+        let fields = Fields::default();
+        tracing::subscriber::with_default(fields.clone(), f);
+        let recorded = fields.0.lock().unwrap().clone();
+        recorded
+    }
+
+    const ID: &str = "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    /// The recorded value of the id field
+    fn recorded_id(id: Id<Customer>) -> String {
+        #[cfg(not(feature = "redact"))]
+        return id.to_string();
+        #[cfg(feature = "redact")]
+        return id.redacted().to_string();
+    }
+
+    #[test]
+    fn test_entity_span() {
+        let id: Id<Customer> = ID.parse().unwrap();
+        let recorded = fields(|| {
+            entity_span!(Level::INFO, "charge", id, amount = 42);
+        });
+        assert_eq!(
+            recorded,
+            [
+                ("entity.class".to_string(), "\"Cust\"".to_string()),
+                ("entity.id".to_string(), recorded_id(id)),
+                ("amount".to_string(), "42".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_entity() {
+        let id: Id<Customer> = ID.parse().unwrap();
+        let recorded = fields(|| {
+            let span = tracing::info_span!(
+                "charge",
+                entity.class = tracing::field::Empty,
+                entity.id = tracing::field::Empty,
+            );
+            record_entity(&span, id);
+        });
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].1, "\"Cust\"");
+        assert_eq!(recorded[1].1, recorded_id(id));
+    }
+}
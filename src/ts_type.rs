@@ -0,0 +1,166 @@
+use {
+    super::*,
+    std::{marker::PhantomData, path::PathBuf},
+    ts_rs::{Dummy, TypeVisitor, TS},
+};
+
+/// The name of the identifiable type, without its path nor generics
+fn entity_name<O>() -> &'static str {
+    let name = std::any::type_name::<O>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Fails the build when the id of `O` is flattened, as a string can't be
+/// the fields of an object
+struct NotFlattenable<O>(PhantomData<O>);
+
+impl<O> NotFlattenable<O> {
+    const ERROR: () = panic!("an Id can't be flattened with #[ts(flatten)]");
+}
+
+/// An Id is exported as a branded string type, named after the
+/// identifiable type, so that ids of different classes can't be mixed
+/// up in TypeScript either:
+///
+/// ```ts
+/// export type CustomerId = string & { __kind: "Cust" };
+/// ```
+///
+/// A `#[ts(flatten)]` id, which serde couldn't flatten either, fails to
+/// compile.
+impl<O: Identifiable> TS for Id<O> {
+    type WithoutGenerics = Self;
+    type OptionInnerType = Self;
+
+    fn name() -> String {
+        format!("{}Id", entity_name::<O>())
+    }
+
+    fn inline() -> String {
+        format!("string & {{ __kind: \"{}\" }}", O::class().prefix())
+    }
+
+    fn inline_flattened() -> String {
+        let () = NotFlattenable::<O>::ERROR;
+        Self::inline()
+    }
+
+    fn decl() -> String {
+        format!("type {} = {};", Self::name(), Self::inline())
+    }
+
+    fn decl_concrete() -> String {
+        Self::decl()
+    }
+
+    fn output_path() -> Option<PathBuf> {
+        Some(PathBuf::from(format!("{}.ts", Self::name())))
+    }
+}
+
+/// An Ided is the intersection of its entity and of an object holding
+/// the id field, eg `Customer & { id: CustomerId }`
+///
+/// This intersection is inlined where the Ided is used, and declared by
+/// `decl()` as `type IdedCustomer = Customer & { id: CustomerId };`. As it
+/// has no file of its own, exporting it returns an error.
+impl<T: Identifiable + 'static, E: TS> TS for Ided<T, E> {
+    type WithoutGenerics = Ided<T, Dummy>;
+    type OptionInnerType = Self;
+
+    fn name() -> String {
+        format!("{} & {{ {}: {} }}", E::name(), T::ID_FIELD, Id::<T>::name())
+    }
+
+    fn inline() -> String {
+        format!(
+            "{} & {{ {}: {} }}",
+            E::inline(),
+            T::ID_FIELD,
+            Id::<T>::inline()
+        )
+    }
+
+    fn inline_flattened() -> String {
+        format!(
+            "{} & {{ {}: {} }}",
+            E::inline_flattened(),
+            T::ID_FIELD,
+            Id::<T>::inline()
+        )
+    }
+
+    fn visit_dependencies(v: &mut impl TypeVisitor)
+    where
+        Self: 'static,
+    {
+        v.visit::<Id<T>>();
+        v.visit::<E>();
+        E::visit_dependencies(v);
+    }
+
+    fn visit_generics(v: &mut impl TypeVisitor)
+    where
+        Self: 'static,
+    {
+        E::visit_generics(v);
+        v.visit::<E>();
+    }
+
+    fn decl() -> String {
+        format!("type Ided{} = {};", E::ident(), Self::name())
+    }
+
+    fn decl_concrete() -> String {
+        Self::decl()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, ts_rs::TS};
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, TS)]
+    #[kind(class = "Cust")]
+    pub struct Customer {
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Identifiable, TS)]
+    #[kind(class = "Inv")]
+    pub struct Invoice {
+        customer: Id<Customer>,
+    }
+
+    #[test]
+    fn test_id_type() {
+        assert_eq!(Id::<Customer>::name(), "CustomerId");
+        assert_eq!(
+            Id::<Customer>::decl(),
+            r#"type CustomerId = string & { __kind: "Cust" };"#
+        );
+        assert_eq!(Invoice::decl(), "type Invoice = { customer: CustomerId, };");
+        let dependencies = Invoice::dependencies();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].ts_name, "CustomerId");
+    }
+
+    #[test]
+    fn test_ided_type() {
+        assert_eq!(Ided::<Customer>::name(), "Customer & { id: CustomerId }");
+        let names: Vec<String> = Ided::<Customer>::dependencies()
+            .into_iter()
+            .map(|dependency| dependency.ts_name)
+            .collect();
+        assert_eq!(names, ["CustomerId", "Customer"]);
+        assert_eq!(
+            Ided::<Customer>::decl(),
+            "type IdedCustomer = Customer & { id: CustomerId };"
+        );
+        assert!(Ided::<Customer>::export_to_string().is_err());
+        assert!(Ided::<Customer>::export().is_err());
+    }
+}
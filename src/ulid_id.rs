@@ -0,0 +1,63 @@
+use {super::*, ::ulid::Ulid, uuid::Uuid};
+
+/// Adopt a ULID, eg received from a partner, as a typed id (which also
+/// provides `TryFrom<Ulid>`)
+///
+/// A ULID and a UUID both being 128 bits, big endian, the conversion keeps
+/// the bits, and the order: ids converted from ULIDs are sorted by time.
+/// As with `Id::from(uuid)`, the class isn't checked.
+impl<O: Identifiable> From<Ulid> for Id<O> {
+    fn from(ulid: Ulid) -> Self {
+        Id::unchecked(Uuid::from_u128(ulid.0))
+    }
+}
+
+impl<O: Identifiable> From<Id<O>> for Ulid {
+    fn from(id: Id<O>) -> Self {
+        id.to_ulid()
+    }
+}
+
+impl<O: Identifiable> Id<O> {
+    /// Return the ULID with the same bits as the uuid of the id
+    ///
+    /// ```
+    /// use kind::*;
+    ///
+    /// #[derive(Debug, Identifiable)]
+    /// #[kind(class="Cust")]
+    /// pub struct Customer {}
+    ///
+    /// let ulid: ulid::Ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".parse().unwrap();
+    /// let id = Id::<Customer>::from(ulid);
+    /// assert_eq!(id.public_id(), "Cust_01563e3a-b5d3-d676-4c61-efb99302bd5b");
+    /// assert_eq!(id.to_ulid(), ulid);
+    /// ```
+    pub fn to_ulid(&self) -> Ulid {
+        Ulid(self.uuid.as_u128())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {crate::*, ::ulid::Ulid};
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Cust")]
+    pub struct Customer {}
+
+    #[test]
+    fn test_ulid_conversions() {
+        let mut ulids: Vec<Ulid> = (0..20u128)
+            .map(|i| Ulid((i % 7) << 80 | i.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+            .collect();
+        let mut ids: Vec<Id<Customer>> = ulids.iter().copied().map(Id::from).collect();
+        for (&ulid, id) in ulids.iter().zip(&ids) {
+            assert_eq!(id.uuid().as_bytes(), &ulid.to_bytes());
+            assert_eq!(Ulid::from(*id), ulid);
+        }
+        ulids.sort();
+        ids.sort();
+        assert!(ids.iter().map(Id::to_ulid).eq(ulids));
+    }
+}
@@ -0,0 +1,150 @@
+use {super::*, std::fmt, uuid::Uuid, wasm_bindgen::prelude::*};
+
+/// An id, for JavaScript, parsed and formatted by the code of the crate
+///
+/// ```ts
+/// import { KindId } from "kind";
+///
+/// const id = new KindId("cust_371C35EC-34d9-4315-ab31-7ea8889a419a", "Cust");
+/// id.class; // "Cust"
+/// id.uuid; // "371c35ec-34d9-4315-ab31-7ea8889a419a"
+/// id.toString(); // "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a"
+/// ```
+///
+/// Invalid ids throw an `Error` whose message is the one of the
+/// `IdError` of the backend.
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KindId {
+    class: IdClass,
+    uuid: Uuid,
+}
+
+impl KindId {
+    fn parse(public_id: &str, class: &str) -> Result<Self, IdError> {
+        let class = IdClass::interned(class)?;
+        let uuid = class.parse_public_id(public_id)?;
+        Ok(Self { class, uuid })
+    }
+
+    fn parse_any(public_id: &str) -> Result<Self, IdError> {
+        let error = |position| {
+            IdError::new(IdErrorKind::InvalidFormat)
+                .with_input(public_id)
+                .with_position(position)
+        };
+        let Some((prefix, db_id)) = public_id.split_once('_') else {
+            return Err(error(public_id.len()));
+        };
+        // the uuid is checked before the prefix is interned, so that
+        // invalid inputs don't leak their prefixes
+        let uuid = Uuid::try_parse_ascii(db_id.as_bytes())
+            .map_err(|e| error(prefix.len() + 1).with_source(e))?;
+        let class = IdClass::interned(prefix).map_err(|e| match e.kind() {
+            IdErrorKind::UnknownPrefix => e,
+            _ => error(0),
+        })?;
+        Ok(Self { class, uuid })
+    }
+}
+
+#[wasm_bindgen]
+impl KindId {
+    /// Parse a public id of the class with the given prefix
+    #[wasm_bindgen(constructor)]
+    pub fn new(public_id: &str, class: &str) -> Result<KindId, JsError> {
+        Self::parse(public_id, class).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Parse a public id of any class
+    #[wasm_bindgen(js_name = parseAny)]
+    pub fn js_parse_any(public_id: &str) -> Result<KindId, JsError> {
+        Self::parse_any(public_id).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Create the id of a class from a db id
+    #[wasm_bindgen(js_name = fromDbId)]
+    pub fn from_db_id(db_id: &str, class: &str) -> Result<KindId, JsError> {
+        let id = IdClass::interned(class).and_then(|class| {
+            let uuid = class.parse_db_id(db_id)?;
+            Ok(Self { class, uuid })
+        });
+        id.map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// The prefix of the class
+    #[wasm_bindgen(getter)]
+    pub fn class(&self) -> String {
+        self.class.prefix().to_string()
+    }
+
+    /// The hyphenated uuid, ie the db id
+    #[wasm_bindgen(getter)]
+    pub fn uuid(&self) -> String {
+        self.uuid.hyphenated().to_string()
+    }
+
+    /// The public id
+    #[wasm_bindgen(js_name = toString)]
+    pub fn js_to_string(&self) -> String {
+        self.class.public_id(&self.uuid)
+    }
+
+    pub fn equals(&self, other: &KindId) -> bool {
+        self == other
+    }
+}
+
+impl fmt::Display for KindId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.class.fmt_public_id(&self.uuid, f)
+    }
+}
+
+impl fmt::Debug for KindId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.class.fmt_debug(&self.uuid, f)
+    }
+}
+
+/// Tell whether the string is a valid public id of the class with the
+/// given prefix
+#[wasm_bindgen(js_name = isValidId)]
+pub fn is_valid_id(public_id: &str, class: &str) -> bool {
+    KindId::parse(public_id, class).is_ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{wasm::*, IdErrorKind};
+
+    const ID: &str = "371c35ec-34d9-4315-ab31-7ea8889a419a";
+
+    #[test]
+    fn test_kind_id() {
+        let id = KindId::parse(&format!("cust_{}", ID.to_uppercase()), "Cust").unwrap();
+        assert_eq!(id.class(), "Cust");
+        assert_eq!(id.uuid(), ID);
+        assert_eq!(id.js_to_string(), format!("Cust_{ID}"));
+        assert_eq!(KindId::parse_any(&format!("Cust_{ID}")).unwrap(), id);
+        assert!(is_valid_id(&format!("Cust_{ID}"), "Cust"));
+        assert!(!is_valid_id(&format!("Cust_{ID}"), "Cont"));
+        assert!(!is_valid_id(&format!("Cust_{ID}"), "Cu_st"));
+        assert_eq!(
+            KindId::parse(&format!("Cust_{ID}"), "Cont")
+                .unwrap_err()
+                .kind(),
+            IdErrorKind::WrongClass
+        );
+        assert_eq!(
+            KindId::parse_any(&format!("Cu-st_{ID}"))
+                .unwrap_err()
+                .kind(),
+            IdErrorKind::InvalidFormat
+        );
+        let error = KindId::parse_any(&format!("Leak_{}", &ID[1..])).unwrap_err();
+        assert_eq!(error.kind(), IdErrorKind::InvalidFormat);
+        assert_eq!(error.position(), Some(5));
+        assert!(KindId::parse_any(&format!("{}_{ID}", "A".repeat(33))).is_err());
+    }
+}
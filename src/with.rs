@@ -0,0 +1,487 @@
+//! Serde adapters for alternate representations of ids.
+//!
+//! By default, an [`Id`] is serialized with its public representation
+//! (`Cust_371c35ec-34d9-4315-ab31-7ea8889a419a`). The modules below are
+//! meant to be used with `#[serde(with = "...")]` on fields which need
+//! another representation, eg in messages exchanged between internal
+//! services.
+//!
+//! ```
+//! use kind::*;
+//!
+//! #[derive(Debug, Identifiable)]
+//! #[kind(class="Cust")]
+//! pub struct Customer {}
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! pub struct QueueMessage {
+//!     #[serde(with = "kind::with::db_id")]
+//!     customer: Id<Customer>,
+//!     #[serde(with = "kind::with::option_db_id")]
+//!     referrer: Option<Id<Customer>>,
+//! }
+//!
+//! let json = r#"{"customer":"371c35ec-34d9-4315-ab31-7ea8889a419a","referrer":null}"#;
+//! let message: QueueMessage = serde_json::from_str(json).unwrap();
+//! assert_eq!(message.customer.public_id(), "Cust_371c35ec-34d9-4315-ab31-7ea8889a419a");
+//! assert_eq!(serde_json::to_string(&message).unwrap(), json);
+//! ```
+//!
+//! The `structured` adapter makes the class and the uuid separate
+//! fields of an object (`{"class": "Cust", "uuid": "371c35ec-..."}`),
+//! for consumers which want to filter by class without parsing strings.
+//!
+//! The `lenient` adapter is an escape hatch for inputs produced by
+//! services predating kind: it serializes the public id but also
+//! accepts bare uuids, without prefix, when deserializing. A prefixed
+//! id is still checked against the class.
+//!
+//! With the `bson` feature, the `bson_uuid` adapter stores the id as a
+//! BSON binary UUID (subtype 4) instead of a string.
+//!
+//! Each representation comes with an `option_` module for `Option<Id<O>>`
+//! fields and a `vec_` module for `Vec<Id<O>>` fields. The `vec_public_id`
+//! and `vec_lenient` modules serialize with [`serialize_ids`](crate::serialize_ids),
+//! faster than the default serialization of a `Vec<Id<O>>`.
+//!
+//! An `Ided` is serialized as a map holding the id and the fields of the
+//! entity. For formats which can't represent it, the `ided_tuple` adapter
+//! serializes an `Ided` field as an `(id, entity)` tuple instead.
+
+use {
+    crate::{serde_serialize::IdVisitor, Id, IdError, IdErrorKind, Ided, Identifiable},
+    serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer},
+    std::{borrow::Cow, fmt},
+    uuid::Uuid,
+};
+
+/// Public representation, the same as the default one
+struct PublicId<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Serialize for PublicId<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, O: Identifiable> Deserialize<'de> for PublicId<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Id::deserialize(deserializer).map(Self)
+    }
+}
+
+/// Hyphenated UUID, without class prefix
+struct DbId<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Serialize for DbId<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.db_id())
+    }
+}
+
+impl<'de, O: Identifiable> Deserialize<'de> for DbId<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(IdVisitor::db()).map(Self)
+    }
+}
+
+/// The 16 bytes of the UUID
+struct UuidBytes<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Serialize for UuidBytes<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0.uuid().as_bytes())
+    }
+}
+
+impl<'de, O: Identifiable> Deserialize<'de> for UuidBytes<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+        impl<'de> de::Visitor<'de> for BytesVisitor {
+            type Value = Uuid;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "16 bytes")
+            }
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Uuid::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+            // formats without a bytes type, like JSON, serialize bytes as a sequence
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; 16];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(de::Error::invalid_length(17, &self));
+                }
+                Ok(Uuid::from_bytes(bytes))
+            }
+        }
+        let uuid = deserializer.deserialize_bytes(BytesVisitor)?;
+        Ok(Self(Id::unchecked(uuid)))
+    }
+}
+
+/// Public representation, but accepting bare uuids too
+struct Lenient<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Serialize for Lenient<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, O: Identifiable> Deserialize<'de> for Lenient<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        fn parse<O: Identifiable>(s: &str) -> Result<Id<O>, IdError> {
+            Id::from_db_id(s).or_else(|_| Id::from_public_id(s))
+        }
+        deserializer
+            .deserialize_str(IdVisitor::new(parse, "a public id or a hyphenated uuid"))
+            .map(Self)
+    }
+}
+
+/// BSON binary UUID (subtype 4)
+#[cfg(feature = "bson")]
+struct BsonUuid<O: Identifiable>(Id<O>);
+
+#[cfg(feature = "bson")]
+impl<O: Identifiable> Serialize for BsonUuid<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        bson::Uuid::from(self.0).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "bson")]
+impl<'de, O: Identifiable> Deserialize<'de> for BsonUuid<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        bson::Uuid::deserialize(deserializer).map(|uuid| Self(uuid.into()))
+    }
+}
+
+/// Object with the class and the uuid as separate fields
+struct Structured<O: Identifiable>(Id<O>);
+
+impl<O: Identifiable> Serialize for Structured<O> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Id", 2)?;
+        s.serialize_field("class", self.0.class().prefix())?;
+        s.serialize_field("uuid", &self.0.db_id())?;
+        s.end()
+    }
+}
+
+impl<'de, O: Identifiable> Deserialize<'de> for Structured<O> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Id")]
+        struct Fields<'a> {
+            #[serde(borrow)]
+            class: Cow<'a, str>,
+            #[serde(borrow)]
+            uuid: Cow<'a, str>,
+        }
+        let fields = Fields::deserialize(deserializer)?;
+        if !fields.class.eq_ignore_ascii_case(O::class().prefix()) {
+            return Err(de::Error::custom(
+                IdError::new(IdErrorKind::WrongClass)
+                    .with_input(&fields.class)
+                    .with_expected(O::class()),
+            ));
+        }
+        Id::from_db_id(&fields.uuid)
+            .map(Self)
+            .map_err(de::Error::custom)
+    }
+}
+
+macro_rules! with_modules {
+    ($Repr:ident, $doc:literal, $single:ident, $option:ident, $vec:ident) => {
+        with_modules!(
+            $Repr,
+            $doc,
+            $single,
+            $option,
+            $vec,
+            |ids: &[Id<O>], serializer: S| serializer.collect_seq(ids.iter().copied().map($Repr))
+        );
+    };
+    (
+        $Repr:ident,
+        $doc:literal,
+        $single:ident,
+        $option:ident,
+        $vec:ident,
+        $serialize_vec:expr
+    ) => {
+        #[doc = concat!("Use the ", $doc, " for an `Id<O>` field")]
+        pub mod $single {
+            use super::*;
+
+            pub fn serialize<O, S>(id: &Id<O>, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                O: Identifiable,
+                S: Serializer,
+            {
+                $Repr(*id).serialize(serializer)
+            }
+
+            pub fn deserialize<'de, O, D>(deserializer: D) -> Result<Id<O>, D::Error>
+            where
+                O: Identifiable,
+                D: Deserializer<'de>,
+            {
+                $Repr::deserialize(deserializer).map(|r| r.0)
+            }
+        }
+
+        #[doc = concat!("Use the ", $doc, " for an `Option<Id<O>>` field")]
+        pub mod $option {
+            use super::*;
+
+            pub fn serialize<O, S>(id: &Option<Id<O>>, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                O: Identifiable,
+                S: Serializer,
+            {
+                id.map($Repr).serialize(serializer)
+            }
+
+            pub fn deserialize<'de, O, D>(deserializer: D) -> Result<Option<Id<O>>, D::Error>
+            where
+                O: Identifiable,
+                D: Deserializer<'de>,
+            {
+                Option::<$Repr<O>>::deserialize(deserializer).map(|r| r.map(|r| r.0))
+            }
+        }
+
+        #[doc = concat!("Use the ", $doc, " for a `Vec<Id<O>>` field")]
+        pub mod $vec {
+            use super::*;
+
+            pub fn serialize<O, S>(ids: &[Id<O>], serializer: S) -> Result<S::Ok, S::Error>
+            where
+                O: Identifiable,
+                S: Serializer,
+            {
+                ($serialize_vec)(ids, serializer)
+            }
+
+            pub fn deserialize<'de, O, D>(deserializer: D) -> Result<Vec<Id<O>>, D::Error>
+            where
+                O: Identifiable,
+                D: Deserializer<'de>,
+            {
+                let ids = Vec::<$Repr<O>>::deserialize(deserializer)?;
+                Ok(ids.into_iter().map(|r| r.0).collect())
+            }
+        }
+    };
+}
+
+with_modules!(
+    PublicId,
+    "public representation (`Cust_371c35ec-34d9-4315-ab31-7ea8889a419a`)",
+    public_id,
+    option_public_id,
+    vec_public_id,
+    crate::serialize_ids
+);
+with_modules!(
+    DbId,
+    "database representation (`371c35ec-34d9-4315-ab31-7ea8889a419a`)",
+    db_id,
+    option_db_id,
+    vec_db_id
+);
+with_modules!(
+    UuidBytes,
+    "16 bytes of the UUID (a byte array in binary formats, an array of numbers in JSON)",
+    uuid_bytes,
+    option_uuid_bytes,
+    vec_uuid_bytes
+);
+with_modules!(
+    Structured,
+    "structured representation (`{\"class\": \"Cust\", \"uuid\": \"371c35ec-...\"}`)",
+    structured,
+    option_structured,
+    vec_structured
+);
+with_modules!(
+    Lenient,
+    "public representation, also accepting bare uuids on deserialization,",
+    lenient,
+    option_lenient,
+    vec_lenient,
+    crate::serialize_ids
+);
+#[cfg(feature = "bson")]
+with_modules!(
+    BsonUuid,
+    "BSON binary UUID (subtype 4), smaller than strings in MongoDB documents and indexes,",
+    bson_uuid,
+    option_bson_uuid,
+    vec_bson_uuid
+);
+
+/// Use an `(id, entity)` tuple for an `Ided<T, E>` field
+pub mod ided_tuple {
+    use super::*;
+
+    pub fn serialize<T, E, S>(ided: &Ided<T, E>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Identifiable,
+        E: Serialize,
+        S: Serializer,
+    {
+        (ided.id(), ided.entity()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, E, D>(deserializer: D) -> Result<Ided<T, E>, D::Error>
+    where
+        T: Identifiable,
+        E: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let (id, entity) = <(Id<T>, E)>::deserialize(deserializer)?;
+        Ok(Ided::new(id, entity))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Id, IdClass, Identifiable};
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    const ID: &str = "86261271-0fc7-46d3-81c6-0b0158628331";
+
+    #[derive(Debug, Identifiable)]
+    #[kind(class = "Test")]
+    struct TestStruct {}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Mixed {
+        #[serde(with = "crate::with::public_id")]
+        public: Id<TestStruct>,
+        #[serde(with = "crate::with::db_id")]
+        db: Id<TestStruct>,
+        #[serde(with = "crate::with::uuid_bytes")]
+        bytes: Id<TestStruct>,
+        #[serde(with = "crate::with::option_db_id")]
+        some_db: Option<Id<TestStruct>>,
+        #[serde(with = "crate::with::option_public_id")]
+        none_public: Option<Id<TestStruct>>,
+        #[serde(with = "crate::with::vec_db_id")]
+        many_db: Vec<Id<TestStruct>>,
+    }
+
+    #[rstest]
+    pub fn test_with_modules() {
+        let id = Id::<TestStruct>::from_db_id(ID).unwrap();
+        let val = Mixed {
+            public: id,
+            db: id,
+            bytes: id,
+            some_db: Some(id),
+            none_public: None,
+            many_db: vec![id, id],
+        };
+
+        let serialized = serde_json::to_value(&val).unwrap();
+
+        let expected = json!({
+            "public": format!("Test_{ID}"),
+            "db": ID,
+            "bytes": id.uuid().as_bytes(),
+            "some_db": ID,
+            "none_public": null,
+            "many_db": [ID, ID],
+        });
+
+        assert_eq!(serialized, expected);
+
+        let deserialized: Mixed = serde_json::from_value(serialized).unwrap();
+
+        assert_eq!(val, deserialized)
+    }
+
+    #[rstest]
+    pub fn test_vec_public_id() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Page {
+            #[serde(with = "crate::with::vec_public_id")]
+            ids: Vec<Id<TestStruct>>,
+        }
+        let page = Page {
+            ids: (0..100).map(|_| Id::random_v4()).collect(),
+        };
+        let json = serde_json::to_value(&page).unwrap();
+        assert_eq!(json["ids"], serde_json::to_value(&page.ids).unwrap());
+        assert_eq!(serde_json::from_value::<Page>(json).unwrap(), page);
+        let bytes = rmp_serde::to_vec(&page).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Page>(&bytes).unwrap(), page);
+        let empty = Page { ids: vec![] };
+        assert_eq!(serde_json::to_value(&empty).unwrap(), json!({ "ids": [] }));
+    }
+
+    #[rstest]
+    pub fn test_db_id_rejects_public_id() {
+        #[derive(Debug, Deserialize)]
+        struct Db {
+            #[serde(with = "crate::with::db_id")]
+            #[allow(dead_code)]
+            id: Id<TestStruct>,
+        }
+        assert!(serde_json::from_value::<Db>(json!({ "id": format!("Test_{ID}") })).is_err());
+        assert!(serde_json::from_value::<Db>(json!({ "id": ID })).is_ok());
+    }
+
+    #[rstest]
+    pub fn test_lenient() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Event {
+            #[serde(with = "crate::with::lenient")]
+            id: Id<TestStruct>,
+        }
+        let public = format!("Test_{ID}");
+        for input in [ID, &public] {
+            let event: Event = serde_json::from_value(json!({ "id": input })).unwrap();
+            assert_eq!(event.id.db_id(), ID);
+            assert_eq!(
+                serde_json::to_value(&event).unwrap(),
+                json!({ "id": public })
+            );
+        }
+        // the class is still checked when there's a prefix
+        assert!(serde_json::from_value::<Event>(json!({ "id": format!("Other_{ID}") })).is_err());
+        assert!(serde_json::from_value::<Event>(json!({ "id": "Test_" })).is_err());
+    }
+
+    #[rstest]
+    pub fn test_structured() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Event {
+            #[serde(with = "crate::with::structured")]
+            id: Id<TestStruct>,
+        }
+        let event: Event =
+            serde_json::from_value(json!({ "id": { "class": "test", "uuid": ID } })).unwrap();
+        assert_eq!(event.id.db_id(), ID);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "id": { "class": "Test", "uuid": ID } }),
+        );
+        assert!(
+            serde_json::from_value::<Event>(json!({ "id": { "class": "Other", "uuid": ID } }))
+                .is_err()
+        );
+        assert!(serde_json::from_value::<Event>(json!({ "id": format!("Test_{ID}") })).is_err());
+    }
+}